@@ -0,0 +1,601 @@
+//! Radial's operations as named calls with JSON arguments and results, shared
+//! by the MCP and HTTP servers.
+
+use anyhow::{Result, anyhow};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value, json};
+
+use crate::commands::{self, status::StatusResult};
+use crate::db::Database;
+use crate::models::{Budget, Task};
+
+/// A call that can be made through `call`, described for clients.
+pub struct Operation {
+    pub name: &'static str,
+    pub description: &'static str,
+    /// JSON Schema of the arguments object.
+    pub input_schema: fn() -> Value,
+}
+
+/// Declare every operation with a struct of its arguments. The structs are what
+/// `call` deserializes, and the published schemas are derived from the same
+/// field types and doc comments, so the two can't drift apart.
+macro_rules! operations {
+    ($(
+        $(#[doc = $doc:literal])+
+        $name:ident => $args:ident {
+            $( $(#[doc = $arg_doc:literal])+ $arg:ident: $ty:ty, )*
+        }
+    )+) => {
+        /// Every operation `call` accepts.
+        pub const OPERATIONS: &[Operation] = &[$(
+            Operation {
+                name: stringify!($name),
+                description: concat!($($doc),+).trim_ascii(),
+                input_schema: || {
+                    schema(&[$((
+                        stringify!($arg),
+                        <$ty as Param>::REQUIRED,
+                        <$ty as Param>::schema(concat!($($arg_doc),+).trim_ascii()),
+                    ),)*])
+                },
+            },
+        )+];
+
+        $(
+            #[derive(Deserialize)]
+            struct $args {
+                $( $arg: $ty, )*
+            }
+        )+
+
+        /// A call's arguments, parsed for the operation it names.
+        enum Call {
+            $( $args($args), )+
+        }
+
+        impl Call {
+            fn parse(name: &str, args: &Value) -> Result<Self> {
+                let args = match args {
+                    Value::Null => Value::Object(Map::new()),
+                    args => args.clone(),
+                };
+                let call = match name {
+                    $( stringify!($name) => serde_json::from_value(args).map(Self::$args), )+
+                    _ => return Err(anyhow!("Unknown operation: {name}")),
+                };
+                call.map_err(|err| anyhow!("{name}: {err}"))
+            }
+        }
+    };
+}
+
+operations! {
+    /// Create a goal, optionally as a sub-goal with a budget
+    goal_create => GoalCreate {
+        /// What the goal should achieve
+        description: String,
+        /// ID of the parent goal
+        parent: Option<String>,
+        /// Token budget for the goal (0 for no limit)
+        max_tokens: Option<i64>,
+        /// Time budget in milliseconds (0 for no limit)
+        max_elapsed_ms: Option<i64>,
+        /// Retries allowed per task (0 for no limit)
+        max_retries: Option<i64>,
+    }
+
+    /// List all goals
+    goal_list => GoalList {}
+
+    /// Create a task under a goal, with an optional contract and blockers
+    task_create => TaskCreate {
+        /// Goal the task belongs to
+        goal_id: String,
+        /// What the task is
+        description: String,
+        /// What the task receives (contract)
+        receives: Option<String>,
+        /// What the task produces (contract)
+        produces: Option<String>,
+        /// How to verify success (contract)
+        verify: Option<String>,
+        /// Shell command that verifies success
+        verify_cmd: Option<String>,
+        /// IDs of tasks this task waits on
+        blocked_by: Option<Vec<String>>,
+        /// 0 is the most urgent
+        priority: Option<Priority>,
+    }
+
+    /// List the tasks of a goal
+    task_list => TaskList {
+        /// Goal to list tasks for
+        goal_id: String,
+        /// Only tasks this agent touched (a filter, not the caller's identity)
+        agent: Option<String>,
+    }
+
+    /// List tasks ready to start, most urgent first, in one goal or all unfinished goals
+    ready => Ready {
+        /// Goal to look in (default: all unfinished goals)
+        goal_id: Option<String>,
+        /// Return at most this many tasks
+        limit: Option<usize>,
+    }
+
+    /// Claim the most urgent ready task of a goal; status is claimed, nothing_ready or goal_done
+    next => Next {
+        /// Goal to take a task from
+        goal_id: String,
+        /// Agent claiming the task
+        agent: Option<String>,
+    }
+
+    /// Claim a pending task and get the context its blockers handed on
+    task_start => TaskStart {
+        /// Task to start
+        task_id: String,
+        /// Agent claiming the task
+        agent: Option<String>,
+        /// Seconds the claim lasts without a heartbeat
+        lease_secs: Option<i64>,
+    }
+
+    /// Renew the lease on an in-progress task so it isn't reaped
+    task_heartbeat => TaskHeartbeat {
+        /// Task to renew
+        task_id: String,
+        /// Agent holding the task
+        agent: Option<String>,
+    }
+
+    /// Complete an in-progress task and record this attempt's usage
+    task_complete => TaskComplete {
+        /// Task to complete
+        task_id: String,
+        /// Summary of what was accomplished
+        result: String,
+        /// Paths of artifacts created
+        artifacts: Option<Vec<String>>,
+        /// Total tokens (default: prompt + completion)
+        tokens: Option<i64>,
+        /// Prompt tokens used
+        prompt_tokens: Option<i64>,
+        /// Completion tokens used
+        completion_tokens: Option<i64>,
+        /// Time spent in milliseconds
+        elapsed_ms: Option<i64>,
+        /// Model that did the work
+        model: Option<String>,
+        /// Agent completing the task
+        agent: Option<String>,
+    }
+
+    /// Submit an in-progress task for review by another agent and record this attempt's usage
+    task_submit => TaskSubmit {
+        /// Task to submit
+        task_id: String,
+        /// Summary of what was accomplished
+        result: String,
+        /// Paths of artifacts created
+        artifacts: Option<Vec<String>>,
+        /// Total tokens (default: prompt + completion)
+        tokens: Option<i64>,
+        /// Prompt tokens used
+        prompt_tokens: Option<i64>,
+        /// Completion tokens used
+        completion_tokens: Option<i64>,
+        /// Time spent in milliseconds
+        elapsed_ms: Option<i64>,
+        /// Model that did the work
+        model: Option<String>,
+        /// Agent submitting the task
+        agent: Option<String>,
+    }
+
+    /// Record a review of a submitted task; it completes only if it passes
+    task_verify => TaskVerify {
+        /// Task to review
+        task_id: String,
+        /// Whether the task meets its contract
+        pass: bool,
+        /// Reviewer's note explaining the verdict
+        note: Option<String>,
+        /// Reviewing agent, which must differ from the submitter
+        agent: Option<String>,
+    }
+
+    /// Mark an in-progress or verifying task as failed and record this attempt's usage
+    task_fail => TaskFail {
+        /// Task that failed
+        task_id: String,
+        /// Total tokens (default: prompt + completion)
+        tokens: Option<i64>,
+        /// Prompt tokens used
+        prompt_tokens: Option<i64>,
+        /// Completion tokens used
+        completion_tokens: Option<i64>,
+        /// Time spent in milliseconds
+        elapsed_ms: Option<i64>,
+        /// Model that did the work
+        model: Option<String>,
+        /// Agent reporting the failure
+        agent: Option<String>,
+    }
+
+    /// Add a comment to a task
+    task_comment => TaskComment {
+        /// Task to comment on
+        task_id: String,
+        /// The comment
+        text: String,
+        /// Agent writing the comment
+        agent: Option<String>,
+    }
+
+    /// Status of one task, one goal, or all goals
+    status => Status {
+        /// Goal to show
+        goal_id: Option<String>,
+        /// Task to show
+        task_id: Option<String>,
+    }
+}
+
+/// Run the operation `name` with a JSON object of arguments. Operations that
+/// act as an agent use their `agent` argument, then `agent`, then the
+/// configured default.
+pub fn call(name: &str, args: &Value, agent: Option<&str>, db: &mut Database) -> Result<Value> {
+    let identity = |own: Option<String>, db: &Database| {
+        own.or_else(|| agent.map(str::to_owned))
+            .or_else(|| db.config().default_agent().map(str::to_owned))
+    };
+
+    match Call::parse(name, args)? {
+        Call::GoalCreate(args) => {
+            let budget = Budget::new(args.max_tokens, args.max_elapsed_ms, args.max_retries);
+            to_json(&commands::goal::create(
+                args.description,
+                args.parent,
+                budget,
+                db,
+            )?)
+        }
+        Call::GoalList(GoalList {}) => to_json(&commands::goal::list(db)),
+        Call::TaskCreate(args) => to_json(&commands::task::create(
+            &args.goal_id,
+            args.description,
+            args.receives,
+            args.produces,
+            args.verify,
+            args.verify_cmd,
+            args.blocked_by,
+            args.priority.map(|p| p.0),
+            db,
+        )?),
+        Call::TaskList(args) => to_json(&commands::task::list(
+            &args.goal_id,
+            args.agent.as_deref(),
+            db,
+        )?),
+        Call::Ready(args) => {
+            commands::reap::run(args.goal_id.as_deref(), db)?;
+            match args.goal_id {
+                Some(goal_id) => to_json(&commands::ready::run(&goal_id, args.limit, db)?),
+                None => to_json(&commands::ready::run_all(args.limit, db)),
+            }
+        }
+        Call::Next(args) => {
+            commands::reap::run(Some(&args.goal_id), db)?;
+            let agent = identity(args.agent, db);
+            to_json(&commands::next::run(&args.goal_id, agent, db)?)
+        }
+        Call::TaskStart(args) => {
+            let agent = identity(args.agent, db);
+            let task = commands::task::start(&args.task_id, agent, args.lease_secs, db)?;
+            let context = commands::handoff::context(&task, db);
+            let mut value = to_json(&task)?;
+            value["context"] = to_json(&context)?;
+            Ok(value)
+        }
+        Call::TaskHeartbeat(args) => {
+            let agent = identity(args.agent, db);
+            to_json(&commands::task::heartbeat(&args.task_id, agent, db)?)
+        }
+        Call::TaskComplete(args) => {
+            let usage = commands::task::usage(
+                args.tokens,
+                args.prompt_tokens,
+                args.completion_tokens,
+                args.elapsed_ms,
+                args.model,
+            );
+            let agent = identity(args.agent, db);
+            to_json(&commands::task::complete(
+                &args.task_id,
+                args.result,
+                args.artifacts,
+                usage,
+                agent,
+                db,
+            )?)
+        }
+        Call::TaskSubmit(args) => {
+            let usage = commands::task::usage(
+                args.tokens,
+                args.prompt_tokens,
+                args.completion_tokens,
+                args.elapsed_ms,
+                args.model,
+            );
+            let agent = identity(args.agent, db);
+            to_json(&commands::task::submit(
+                &args.task_id,
+                args.result,
+                args.artifacts,
+                usage,
+                agent,
+                db,
+            )?)
+        }
+        Call::TaskVerify(args) => {
+            let agent = identity(args.agent, db);
+            to_json(&commands::task::verify(
+                &args.task_id,
+                args.pass,
+                args.note,
+                agent,
+                db,
+            )?)
+        }
+        Call::TaskFail(args) => {
+            let usage = commands::task::usage(
+                args.tokens,
+                args.prompt_tokens,
+                args.completion_tokens,
+                args.elapsed_ms,
+                args.model,
+            );
+            let agent = identity(args.agent, db);
+            to_json(&commands::task::fail(&args.task_id, usage, agent, db)?)
+        }
+        Call::TaskComment(args) => {
+            let agent = identity(args.agent, db);
+            to_json(&commands::task::comment(
+                &args.task_id,
+                args.text,
+                agent,
+                db,
+            )?)
+        }
+        Call::Status(args) => match commands::status::run(args.goal_id, args.task_id, db)? {
+            StatusResult::Task(task) => to_json(&task),
+            StatusResult::Goal(goal_status) => to_json(&goal_status),
+            StatusResult::AllGoals(summaries) => to_json(&summaries),
+        },
+    }
+}
+
+fn to_json<T: Serialize + ?Sized>(value: &T) -> Result<Value> {
+    Ok(serde_json::to_value(value)?)
+}
+
+/// A task priority, checked against the range tasks accept.
+#[derive(Deserialize)]
+#[serde(try_from = "i64")]
+struct Priority(u8);
+
+impl TryFrom<i64> for Priority {
+    type Error = String;
+
+    fn try_from(value: i64) -> Result<Self, Self::Error> {
+        u8::try_from(value)
+            .ok()
+            .filter(|p| *p <= Task::LOWEST_PRIORITY)
+            .map(Priority)
+            .ok_or_else(|| format!("priority must be 0 to {}", Task::LOWEST_PRIORITY))
+    }
+}
+
+/// How an argument type is described in a JSON Schema.
+trait Param {
+    /// Whether the argument must be given.
+    const REQUIRED: bool = true;
+
+    fn schema(description: &str) -> Value;
+}
+
+impl Param for String {
+    fn schema(description: &str) -> Value {
+        json!({ "type": "string", "description": description })
+    }
+}
+
+impl Param for bool {
+    fn schema(description: &str) -> Value {
+        json!({ "type": "boolean", "description": description })
+    }
+}
+
+impl Param for i64 {
+    fn schema(description: &str) -> Value {
+        json!({ "type": "integer", "description": description })
+    }
+}
+
+impl Param for usize {
+    fn schema(description: &str) -> Value {
+        json!({ "type": "integer", "minimum": 0, "description": description })
+    }
+}
+
+impl Param for Priority {
+    fn schema(description: &str) -> Value {
+        json!({
+            "type": "integer",
+            "minimum": 0,
+            "maximum": Task::LOWEST_PRIORITY,
+            "description": description,
+        })
+    }
+}
+
+impl Param for Vec<String> {
+    fn schema(description: &str) -> Value {
+        json!({ "type": "array", "items": { "type": "string" }, "description": description })
+    }
+}
+
+impl<T: Param> Param for Option<T> {
+    const REQUIRED: bool = false;
+
+    fn schema(description: &str) -> Value {
+        T::schema(description)
+    }
+}
+
+/// An object schema from each argument's name, whether it's required, and its
+/// own schema.
+fn schema(arguments: &[(&str, bool, Value)]) -> Value {
+    let properties: Map<String, Value> = arguments
+        .iter()
+        .map(|(name, _, prop)| ((*name).to_owned(), prop.clone()))
+        .collect();
+    let required: Vec<&str> = arguments
+        .iter()
+        .filter(|(_, required, _)| *required)
+        .map(|(name, _, _)| *name)
+        .collect();
+    json!({
+        "type": "object",
+        "properties": properties,
+        "required": required,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::{fixture, rstest};
+    use tempfile::TempDir;
+
+    #[fixture]
+    fn db() -> (TempDir, Database) {
+        let dir = TempDir::new().unwrap();
+        let db = Database::open(dir.path()).unwrap();
+        (dir, db)
+    }
+
+    // -- call --
+
+    // Goals and tasks made through call are returned as their serde models
+    // and show up in later calls.
+    #[rstest]
+    fn call_creates_and_lists(db: (TempDir, Database)) {
+        let (_dir, mut db) = db;
+        let goal = call("goal_create", &json!({ "description": "G" }), None, &mut db).unwrap();
+        let goal_id = goal["id"].as_str().unwrap();
+
+        let task = call(
+            "task_create",
+            &json!({ "goal_id": goal_id, "description": "T", "receives": "r",
+                     "produces": "p", "verify": "v", "priority": 1 }),
+            None,
+            &mut db,
+        )
+        .unwrap();
+        assert_eq!(task["priority"], 1);
+
+        let ready = call("ready", &json!({}), None, &mut db).unwrap();
+        assert_eq!(ready[0]["id"], task["id"]);
+
+        let next = call("next", &json!({ "goal_id": goal_id }), Some("bot"), &mut db).unwrap();
+        assert_eq!(next["status"], "claimed");
+        assert_eq!(next["task"]["claimed_by"], "bot");
+    }
+
+    // A task can be claimed, kept alive, submitted and reviewed through call;
+    // each call's own agent argument wins over the server's.
+    #[rstest]
+    fn call_reviews_submitted_tasks(db: (TempDir, Database)) {
+        let (_dir, mut db) = db;
+        let goal = call("goal_create", &json!({ "description": "G" }), None, &mut db).unwrap();
+        let task = call(
+            "task_create",
+            &json!({ "goal_id": goal["id"], "description": "T", "receives": "r",
+                     "produces": "p", "verify": "v" }),
+            None,
+            &mut db,
+        )
+        .unwrap();
+        let task_id = json!({ "task_id": task["id"] });
+
+        call("task_start", &task_id, Some("builder"), &mut db).unwrap();
+        call("task_heartbeat", &task_id, Some("builder"), &mut db).unwrap();
+        let submitted = call(
+            "task_submit",
+            &json!({ "task_id": task["id"], "result": "done" }),
+            Some("builder"),
+            &mut db,
+        )
+        .unwrap();
+        assert_eq!(submitted["state"], "verifying");
+
+        let verdict = json!({ "task_id": task["id"], "pass": true });
+        assert!(call("task_verify", &verdict, Some("builder"), &mut db).is_err());
+        let verdict = json!({ "task_id": task["id"], "pass": true, "agent": "reviewer" });
+        let verified = call("task_verify", &verdict, Some("builder"), &mut db).unwrap();
+        assert_eq!(verified["task"]["state"], "completed");
+    }
+
+    // Missing or mistyped arguments and unknown operations are errors.
+    #[rstest]
+    #[case::missing("goal_create", json!({}))]
+    #[case::wrong_type("goal_create", json!({ "description": 5 }))]
+    #[case::bad_priority("task_create", json!({ "goal_id": "g", "description": "T", "priority": 9 }))]
+    #[case::negative_limit("ready", json!({ "limit": -1 }))]
+    #[case::missing_verdict("task_verify", json!({ "task_id": "t" }))]
+    #[case::unknown("goal_delete", json!({}))]
+    fn call_rejects_bad_arguments(
+        db: (TempDir, Database),
+        #[case] name: &str,
+        #[case] args: Value,
+    ) {
+        let (_dir, mut db) = db;
+        assert!(call(name, &args, None, &mut db).is_err());
+    }
+
+    // Schemas follow the argument types: optional fields aren't required, and
+    // priorities carry their range.
+    #[rstest]
+    fn operation_schemas_follow_arguments() {
+        let op = OPERATIONS
+            .iter()
+            .find(|op| op.name == "task_create")
+            .unwrap();
+        let schema = (op.input_schema)();
+        assert_eq!(schema["required"], json!(["goal_id", "description"]));
+        assert_eq!(schema["properties"]["blocked_by"]["type"], "array");
+        assert_eq!(
+            schema["properties"]["priority"]["maximum"],
+            Task::LOWEST_PRIORITY
+        );
+        assert_eq!(
+            schema["properties"]["goal_id"]["description"],
+            "Goal the task belongs to"
+        );
+    }
+
+    // Every operation publishes an object schema whose required fields exist.
+    #[rstest]
+    fn operation_schemas_are_consistent() {
+        for op in OPERATIONS {
+            let schema = (op.input_schema)();
+            assert_eq!(schema["type"], "object");
+            for field in schema["required"].as_array().unwrap() {
+                assert!(schema["properties"].get(field.as_str().unwrap()).is_some());
+            }
+        }
+    }
+}
@@ -1,4 +1,8 @@
-use clap::{Parser, Subcommand};
+use std::path::PathBuf;
+
+use clap::{ArgGroup, Parser, Subcommand};
+
+use crate::models::Task;
 
 #[derive(Parser)]
 #[command(name = "radial")]
@@ -25,6 +29,10 @@ pub enum Commands {
     #[command(subcommand)]
     Task(TaskCommands),
 
+    /// Read or change project settings in .radial/config.toml
+    #[command(subcommand)]
+    Config(ConfigCommands),
+
     /// Show status of goals and tasks
     Status {
         /// Show status of a specific goal
@@ -46,9 +54,85 @@ pub enum Commands {
 
     /// Show tasks ready to be worked on
     Ready {
-        /// The goal ID to check for ready tasks
+        /// The goal ID to check for ready tasks, including its sub-goals (default: every goal not yet completed)
+        goal_id: Option<String>,
+
+        /// Show at most this many tasks
+        #[arg(long)]
+        limit: Option<usize>,
+
+        /// Output as JSON
+        #[arg(long)]
+        json: bool,
+    },
+
+    /// Claim the next ready task of a goal in one step
+    ///
+    /// Exits with 2 when nothing is ready yet and 3 when the goal is done.
+    Next {
+        /// The goal to take a task from, or from one of its sub-goals
         goal_id: String,
 
+        /// Identity of the agent claiming the task
+        #[arg(long, env = "RD_AGENT")]
+        agent: Option<String>,
+
+        /// Output as JSON
+        #[arg(long)]
+        json: bool,
+    },
+
+    /// Explain why a task is not ready to start
+    Why {
+        /// The task to explain
+        task_id: String,
+
+        /// Output as JSON
+        #[arg(long)]
+        json: bool,
+    },
+
+    /// Check task dependencies for cycles and missing or invalid blockers
+    Check {
+        /// Only check this goal
+        goal_id: Option<String>,
+
+        /// Output as JSON
+        #[arg(long)]
+        json: bool,
+    },
+
+    /// Show the history of goal and task changes
+    Log {
+        /// Show history for a specific goal
+        #[arg(long)]
+        goal: Option<String>,
+
+        /// Show history for a specific task
+        #[arg(long)]
+        task: Option<String>,
+
+        /// Output as JSON
+        #[arg(long)]
+        json: bool,
+    },
+
+    /// Stream task changes as they happen
+    Watch {
+        /// Only watch this goal and its sub-goals
+        #[arg(long)]
+        goal: Option<String>,
+
+        /// Output one JSON object per line
+        #[arg(long)]
+        json: bool,
+    },
+
+    /// Return in-progress tasks with expired leases to pending
+    Reap {
+        /// Only reap tasks under this goal and its sub-goals
+        goal_id: Option<String>,
+
         /// Output as JSON
         #[arg(long)]
         json: bool,
@@ -56,6 +140,67 @@ pub enum Commands {
 
     /// Output a preparation guide for LLM agents
     Prep,
+
+    /// Render a ready-to-paste agent prompt for a task
+    Prompt {
+        /// The task to write the prompt for
+        task_id: String,
+
+        /// Template file, or the name of one in .radial/templates/ [default: prompt.md]
+        #[arg(long)]
+        template: Option<PathBuf>,
+
+        /// Agent named in the prompt's commands [default: the agent holding the task]
+        #[arg(long, env = "RD_AGENT")]
+        agent: Option<String>,
+    },
+
+    /// Serve radial's operations as MCP tools over stdin/stdout
+    Mcp {
+        /// Agent identity for calls that don't give one
+        #[arg(long, env = "RD_AGENT")]
+        agent: Option<String>,
+    },
+
+    /// Serve a local HTTP/JSON API
+    Serve {
+        /// Address to listen on (port 0 picks a free port)
+        #[arg(long, default_value = "127.0.0.1:7420")]
+        bind: String,
+
+        /// Agent identity for calls that don't give one
+        #[arg(long, env = "RD_AGENT")]
+        agent: Option<String>,
+
+        /// Allow a non-loopback --bind and requests for any Host (the API has no authentication)
+        #[arg(long)]
+        allow_remote: bool,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum ConfigCommands {
+    /// Print the value of a setting
+    Get {
+        /// The setting, e.g. id.length
+        key: String,
+    },
+
+    /// Change a setting (an empty value unsets an optional setting)
+    Set {
+        /// The setting, e.g. id.length
+        key: String,
+
+        /// The new value
+        value: String,
+    },
+
+    /// List all settings and their values
+    List {
+        /// Output as JSON
+        #[arg(long)]
+        json: bool,
+    },
 }
 
 #[derive(Subcommand)]
@@ -65,6 +210,23 @@ pub enum GoalCommands {
         /// The goal description
         description: String,
 
+        /// Create this goal as a sub-goal of another goal
+        #[arg(long)]
+        parent: Option<String>,
+
+        /// Maximum tokens all tasks under the goal may use (0 for no limit)
+        #[arg(long)]
+        max_tokens: Option<i64>,
+
+        /// Maximum elapsed milliseconds for the goal (0 for no limit). This is the sum of
+        /// the elapsed times agents report for their attempts, not wall-clock time.
+        #[arg(long)]
+        max_elapsed: Option<i64>,
+
+        /// Maximum number of times each task under the goal may be retried (0 for no limit)
+        #[arg(long)]
+        max_retries: Option<i64>,
+
         /// Output as JSON
         #[arg(long)]
         json: bool,
@@ -76,6 +238,29 @@ pub enum GoalCommands {
         #[arg(long)]
         json: bool,
     },
+
+    /// Change a goal's budget (a limit of 0 removes it)
+    Budget {
+        /// The goal ID to change
+        goal_id: String,
+
+        /// Maximum tokens all tasks under the goal may use
+        #[arg(long)]
+        max_tokens: Option<i64>,
+
+        /// Maximum elapsed milliseconds for the goal. This is the sum of the elapsed
+        /// times agents report for their attempts, not wall-clock time.
+        #[arg(long)]
+        max_elapsed: Option<i64>,
+
+        /// Maximum number of times each task under the goal may be retried
+        #[arg(long)]
+        max_retries: Option<i64>,
+
+        /// Output as JSON
+        #[arg(long)]
+        json: bool,
+    },
 }
 
 #[derive(Subcommand)]
@@ -100,10 +285,18 @@ pub enum TaskCommands {
         #[arg(long)]
         verify: Option<String>,
 
+        /// Shell command that verifies success, run by `task verify --run`
+        #[arg(long)]
+        verify_cmd: Option<String>,
+
         /// IDs of tasks this task is blocked by
         #[arg(long, value_delimiter = ',')]
         blocked_by: Option<Vec<String>>,
 
+        /// Priority from p0 (most urgent) to p3 [default: p2]
+        #[arg(long, value_parser = parse_priority)]
+        priority: Option<u8>,
+
         /// Output as JSON
         #[arg(long)]
         json: bool,
@@ -121,12 +314,118 @@ pub enum TaskCommands {
         /// Show comments on tasks
         #[arg(short, long)]
         verbose: bool,
+
+        /// Only show tasks claimed, completed, failed, or commented on by this agent
+        #[arg(long)]
+        agent: Option<String>,
+    },
+
+    /// Edit a task's description, contract or dependencies
+    Update {
+        /// The task ID to update
+        task_id: String,
+
+        /// New task description
+        #[arg(long)]
+        description: Option<String>,
+
+        /// What this task receives (contract)
+        #[arg(long)]
+        receives: Option<String>,
+
+        /// What this task produces (contract)
+        #[arg(long)]
+        produces: Option<String>,
+
+        /// How to verify success (contract)
+        #[arg(long)]
+        verify: Option<String>,
+
+        /// Shell command that verifies success (empty to remove)
+        #[arg(long)]
+        verify_cmd: Option<String>,
+
+        /// IDs of tasks to add as blockers
+        #[arg(long, value_delimiter = ',')]
+        add_blocked_by: Vec<String>,
+
+        /// IDs of tasks to remove as blockers
+        #[arg(long, value_delimiter = ',')]
+        remove_blocked_by: Vec<String>,
+
+        /// New priority, from p0 (most urgent) to p3
+        #[arg(long, value_parser = parse_priority)]
+        priority: Option<u8>,
+
+        /// Allow editing a completed task
+        #[arg(long)]
+        force: bool,
+
+        /// Output as JSON
+        #[arg(long)]
+        json: bool,
+    },
+
+    /// Set or change a task's contract
+    Contract {
+        /// The task ID to set the contract on
+        task_id: String,
+
+        /// What this task receives
+        #[arg(long)]
+        receives: Option<String>,
+
+        /// What this task produces
+        #[arg(long)]
+        produces: Option<String>,
+
+        /// How to verify success
+        #[arg(long)]
+        verify: Option<String>,
+
+        /// Shell command that verifies success (empty to remove)
+        #[arg(long)]
+        verify_cmd: Option<String>,
+
+        /// Allow editing a completed task
+        #[arg(long)]
+        force: bool,
+
+        /// Output as JSON
+        #[arg(long)]
+        json: bool,
     },
 
     /// Mark a task as started
     Start {
         /// The task ID to start
         task_id: String,
+
+        /// Identity of the agent claiming the task
+        #[arg(long, env = "RD_AGENT")]
+        agent: Option<String>,
+
+        /// Seconds the claim lasts without a heartbeat
+        #[arg(long)]
+        lease_secs: Option<i64>,
+
+        /// Output as JSON
+        #[arg(long)]
+        json: bool,
+    },
+
+    /// Extend the lease on an in-progress task
+    Heartbeat {
+        /// The task ID to heartbeat
+        task_id: String,
+
+        /// Identity of the agent holding the task
+        #[arg(long, env = "RD_AGENT")]
+        agent: Option<String>,
+
+        /// Output as JSON
+        #[arg(long)]
+        json: bool,
     },
 
     /// Mark a task as completed
@@ -142,25 +441,162 @@ pub enum TaskCommands {
         #[arg(long, value_delimiter = ',')]
         artifacts: Option<Vec<String>>,
 
-        /// Total tokens used for this task
+        /// Total tokens used for this attempt (defaults to prompt + completion tokens)
         #[arg(long)]
         tokens: Option<i64>,
 
-        /// Elapsed time in milliseconds
+        /// Prompt (input) tokens used for this attempt
+        #[arg(long)]
+        prompt_tokens: Option<i64>,
+
+        /// Completion (output) tokens used for this attempt
         #[arg(long)]
+        completion_tokens: Option<i64>,
+
+        /// The model that did the work
+        #[arg(long)]
+        model: Option<String>,
+
+        /// Elapsed time in milliseconds for this attempt
+        #[arg(long, alias = "elapsed-ms")]
         elapsed: Option<i64>,
+
+        /// Identity of the agent making the change
+        #[arg(long, env = "RD_AGENT")]
+        agent: Option<String>,
+
+        /// Output as JSON
+        #[arg(long)]
+        json: bool,
+    },
+
+    /// Submit an in-progress task for review by another agent
+    Submit {
+        /// The task ID to submit
+        task_id: String,
+
+        /// Summary of what was accomplished
+        #[arg(long)]
+        result: String,
+
+        /// Artifact paths created (comma-separated)
+        #[arg(long, value_delimiter = ',')]
+        artifacts: Option<Vec<String>>,
+
+        /// Total tokens used for this attempt (defaults to prompt + completion tokens)
+        #[arg(long)]
+        tokens: Option<i64>,
+
+        /// Prompt (input) tokens used for this attempt
+        #[arg(long)]
+        prompt_tokens: Option<i64>,
+
+        /// Completion (output) tokens used for this attempt
+        #[arg(long)]
+        completion_tokens: Option<i64>,
+
+        /// The model that did the work
+        #[arg(long)]
+        model: Option<String>,
+
+        /// Elapsed time in milliseconds for this attempt
+        #[arg(long, alias = "elapsed-ms")]
+        elapsed: Option<i64>,
+
+        /// Identity of the agent making the change
+        #[arg(long, env = "RD_AGENT")]
+        agent: Option<String>,
+
+        /// Output as JSON
+        #[arg(long)]
+        json: bool,
+    },
+
+    /// Review a submitted task, completing or failing it
+    #[command(group(ArgGroup::new("verdict").required(true).args(["pass", "fail", "run"])))]
+    Verify {
+        /// The task ID to verify
+        task_id: String,
+
+        /// The task meets its contract
+        #[arg(long)]
+        pass: bool,
+
+        /// The task does not meet its contract
+        #[arg(long)]
+        fail: bool,
+
+        /// Run the contract's verify command and pass only if it succeeds
+        #[arg(long)]
+        run: bool,
+
+        /// Seconds to wait for the verify command before failing
+        #[arg(long, default_value_t = 600, requires = "run")]
+        timeout: u64,
+
+        /// Reviewer's note explaining the verdict
+        #[arg(long, conflicts_with = "run")]
+        note: Option<String>,
+
+        /// Identity of the reviewing agent
+        #[arg(long, env = "RD_AGENT")]
+        agent: Option<String>,
+
+        /// Output as JSON
+        #[arg(long)]
+        json: bool,
     },
 
     /// Mark a task as failed
     Fail {
         /// The task ID to fail
         task_id: String,
+
+        /// Total tokens used for this attempt (defaults to prompt + completion tokens)
+        #[arg(long)]
+        tokens: Option<i64>,
+
+        /// Prompt (input) tokens used for this attempt
+        #[arg(long)]
+        prompt_tokens: Option<i64>,
+
+        /// Completion (output) tokens used for this attempt
+        #[arg(long)]
+        completion_tokens: Option<i64>,
+
+        /// The model that did the work
+        #[arg(long)]
+        model: Option<String>,
+
+        /// Elapsed time in milliseconds for this attempt
+        #[arg(long, alias = "elapsed-ms")]
+        elapsed: Option<i64>,
+
+        /// Identity of the agent making the change
+        #[arg(long, env = "RD_AGENT")]
+        agent: Option<String>,
+
+        /// Output as JSON
+        #[arg(long)]
+        json: bool,
     },
 
     /// Retry a failed task
     Retry {
         /// The task ID to retry
         task_id: String,
+
+        /// Identity of the agent making the change
+        #[arg(long, env = "RD_AGENT")]
+        agent: Option<String>,
+
+        /// Seconds the claim lasts without a heartbeat
+        #[arg(long)]
+        lease_secs: Option<i64>,
+
+        /// Output as JSON
+        #[arg(long)]
+        json: bool,
     },
 
     /// Add a comment to a task
@@ -170,5 +606,24 @@ pub enum TaskCommands {
 
         /// The comment text
         text: String,
+
+        /// Identity of the agent making the change
+        #[arg(long, env = "RD_AGENT")]
+        agent: Option<String>,
+
+        /// Output as JSON
+        #[arg(long)]
+        json: bool,
     },
 }
+
+/// Parse a task priority given as `p0`..`p3` or just `0`..`3`.
+fn parse_priority(value: &str) -> Result<u8, String> {
+    value
+        .strip_prefix(['p', 'P'])
+        .unwrap_or(value)
+        .parse()
+        .ok()
+        .filter(|p| *p <= Task::LOWEST_PRIORITY)
+        .ok_or_else(|| format!("expected p0 to p{}", Task::LOWEST_PRIORITY))
+}
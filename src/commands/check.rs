@@ -0,0 +1,39 @@
+use anyhow::Result;
+use serde::Serialize;
+
+use crate::db::Database;
+use crate::graph::{self, Issue};
+use crate::id::IdKind;
+
+/// Dependency problems found in one goal.
+#[derive(Debug, Serialize)]
+pub struct GoalCheck {
+    goal_id: String,
+    issues: Vec<Issue>,
+}
+
+impl GoalCheck {
+    pub fn goal_id(&self) -> &str {
+        &self.goal_id
+    }
+
+    pub fn issues(&self) -> &[Issue] {
+        &self.issues
+    }
+}
+
+/// Validate the dependency graph of one goal, or of every goal.
+pub fn run(goal_id: Option<String>, db: &Database) -> Result<Vec<GoalCheck>> {
+    let goal_ids: Vec<String> = match goal_id {
+        Some(gid) => vec![db.resolve_id(&gid, IdKind::Goal)?],
+        None => db.list_goals().iter().map(|g| g.id().to_owned()).collect(),
+    };
+
+    Ok(goal_ids
+        .into_iter()
+        .map(|goal_id| GoalCheck {
+            issues: graph::check(&goal_id, db),
+            goal_id,
+        })
+        .collect())
+}
@@ -0,0 +1,20 @@
+use anyhow::Result;
+
+use crate::db::Database;
+
+pub fn get(key: &str, db: &Database) -> Result<String> {
+    db.config().get(key)
+}
+
+/// Change a setting and write `.radial/config.toml`.
+pub fn set(key: &str, value: &str, db: &mut Database) -> Result<()> {
+    let mut config = db.config().clone();
+    config.set(key, value)?;
+    config.write_file(db.base_path())?;
+    db.set_config(config);
+    Ok(())
+}
+
+pub fn list(db: &Database) -> Vec<(&'static str, String)> {
+    db.config().entries()
+}
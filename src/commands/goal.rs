@@ -1,27 +1,146 @@
-use anyhow::Result;
+use anyhow::{Result, anyhow};
 use jiff::Timestamp;
 
 use crate::db::Database;
-use crate::id::generate_id;
-use crate::models::{Goal, GoalState, Metrics};
+use crate::id::IdKind;
+use crate::models::{Budget, Goal, GoalState, Metrics, TaskState};
+
+pub fn create(
+    description: String,
+    parent_id: Option<String>,
+    budget: Budget,
+    db: &mut Database,
+) -> Result<Goal> {
+    validate_budget(&budget)?;
+    // A limit of 0 means no limit, as it does for `rd goal budget`.
+    let mut limits = Budget::default();
+    limits.merge(&budget);
+
+    let parent_id = parent_id
+        .map(|pid| db.resolve_id(&pid, IdKind::Goal))
+        .transpose()?;
 
-pub fn create(description: String, db: &mut Database) -> Result<Goal> {
     let now = Timestamp::now();
     let goal = Goal::new(
-        generate_id(),
-        None,
+        db.config().new_id(IdKind::Goal),
+        parent_id.clone(),
         description,
         GoalState::Pending,
         now,
         now,
         None,
         Metrics::default(),
-    );
+    )
+    .with_budget(limits);
 
     db.create_goal(goal.clone())?;
+
+    if let Some(pid) = parent_id {
+        settle(&pid, db)?;
+    }
+
     Ok(goal)
 }
 
 pub fn list(db: &Database) -> Vec<Goal> {
     db.list_goals().into_iter().cloned().collect()
 }
+
+/// Change a goal's budget. Limits that aren't given are left as they are, and a
+/// limit of 0 removes it.
+pub fn set_budget(goal_id: &str, changes: Budget, db: &mut Database) -> Result<Goal> {
+    validate_budget(&changes)?;
+    let goal_id = db.resolve_id(goal_id, IdKind::Goal)?;
+
+    db.update_goal(&goal_id, |goal| {
+        goal.update_budget(&changes);
+        goal.clone()
+    })
+}
+
+fn validate_budget(budget: &Budget) -> Result<()> {
+    let limits = [
+        budget.max_tokens(),
+        budget.max_elapsed_ms(),
+        budget.max_retries(),
+    ];
+    if limits.into_iter().flatten().any(|limit| limit < 0) {
+        return Err(anyhow!("Budget limits must not be negative"));
+    }
+    Ok(())
+}
+
+/// Refuse new work under a goal if it, or any goal above it, has used up its
+/// token or time budget.
+pub fn check_budget(goal_id: &str, db: &Database) -> Result<()> {
+    let mut next = db.get_goal(goal_id);
+    while let Some(goal) = next {
+        if let Some(budget) = goal.budget() {
+            let exceeded = budget.exceeded(&db.compute_goal_metrics(goal.id()));
+            if !exceeded.is_empty() {
+                return Err(anyhow!(
+                    "Goal {} is over budget: {}\nRaise the limit with: rd goal budget {}",
+                    goal.id(),
+                    exceeded.join("; "),
+                    goal.id()
+                ));
+            }
+        }
+        next = goal.parent_id().and_then(|pid| db.get_goal(pid));
+    }
+    Ok(())
+}
+
+/// The strictest per-task retry limit set on a goal or any goal above it.
+pub fn retry_limit(goal_id: &str, db: &Database) -> Option<i64> {
+    let mut limit: Option<i64> = None;
+    let mut next = db.get_goal(goal_id);
+    while let Some(goal) = next {
+        if let Some(max) = goal.budget().and_then(Budget::max_retries) {
+            limit = Some(limit.map_or(max, |l| l.min(max)));
+        }
+        next = goal.parent_id().and_then(|pid| db.get_goal(pid));
+    }
+    limit
+}
+
+/// Recompute a goal's state from its tasks and sub-goals, then do the same for each
+/// of its ancestors.
+///
+/// A goal is completed once all of its tasks and sub-goals are, and failed if any of
+/// them has failed. Otherwise a goal with work under it is in progress, which reopens
+/// a completed goal that gained new work and a failed goal whose tasks were retried.
+pub fn settle(goal_id: &str, db: &mut Database) -> Result<()> {
+    let mut next = Some(goal_id.to_owned());
+
+    while let Some(gid) = next {
+        let tasks = db.list_tasks(&gid);
+        let children = db.list_child_goals(&gid);
+
+        let has_work = !tasks.is_empty() || !children.is_empty();
+        let all_completed = has_work
+            && tasks.iter().all(|t| t.state() == TaskState::Completed)
+            && children.iter().all(|g| g.state() == GoalState::Completed);
+        let any_failed = tasks.iter().any(|t| t.state() == TaskState::Failed)
+            || children.iter().any(|g| g.state() == GoalState::Failed);
+
+        next = db.update_goal(&gid, |goal| {
+            if all_completed {
+                if goal.state() != GoalState::Completed {
+                    goal.mark_completed();
+                }
+            } else if any_failed {
+                if goal.state() != GoalState::Failed {
+                    goal.mark_failed();
+                }
+            } else if goal.state() != GoalState::InProgress && has_work {
+                goal.mark_in_progress();
+            } else {
+                goal.touch();
+            }
+            goal.parent_id().map(str::to_owned)
+        })?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,203 @@
+use std::collections::HashSet;
+
+use serde::Serialize;
+
+use crate::db::Database;
+use crate::models::{Comment, Contract, Task, TaskState};
+
+/// How many of a blocker's most recent comments are handed on.
+pub const FINAL_COMMENTS: usize = 3;
+
+/// What a completed blocker hands to the task it unblocked.
+#[derive(Debug, Serialize)]
+pub struct Handoff {
+    task_id: String,
+    description: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    produces: Option<String>,
+    summary: String,
+    artifacts: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    agent: Option<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    comments: Vec<Comment>,
+}
+
+impl Handoff {
+    pub fn task_id(&self) -> &str {
+        &self.task_id
+    }
+
+    pub fn description(&self) -> &str {
+        &self.description
+    }
+
+    pub fn produces(&self) -> Option<&str> {
+        self.produces.as_deref()
+    }
+
+    pub fn summary(&self) -> &str {
+        &self.summary
+    }
+
+    pub fn artifacts(&self) -> &[String] {
+        &self.artifacts
+    }
+
+    pub fn agent(&self) -> Option<&str> {
+        self.agent.as_deref()
+    }
+
+    /// The blocker's last `FINAL_COMMENTS` comments, oldest first.
+    pub fn comments(&self) -> &[Comment] {
+        &self.comments
+    }
+}
+
+/// Everything a task's completed blockers left for it.
+#[derive(Debug, Default, Serialize)]
+pub struct Context {
+    upstream: Vec<Handoff>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    warnings: Vec<String>,
+}
+
+impl Context {
+    pub fn upstream(&self) -> &[Handoff] {
+        &self.upstream
+    }
+
+    /// Signs that the blockers don't produce what the task receives.
+    pub fn warnings(&self) -> &[String] {
+        &self.warnings
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.upstream.is_empty() && self.warnings.is_empty()
+    }
+}
+
+/// Assemble the outcomes, artifacts and final comments of a task's completed
+/// blockers, and check that what they produce plausibly covers what it receives.
+pub fn context(task: &Task, db: &Database) -> Context {
+    let blockers: Vec<&Task> = task
+        .blocked_by()
+        .iter()
+        .filter_map(|id| db.get_task(id))
+        .filter(|t| t.state() == TaskState::Completed)
+        .collect();
+
+    let upstream = blockers
+        .iter()
+        .filter_map(|blocker| {
+            let result = blocker.result()?;
+            let skip = blocker.comments().len().saturating_sub(FINAL_COMMENTS);
+            Some(Handoff {
+                task_id: blocker.id().to_owned(),
+                description: blocker.description().to_owned(),
+                produces: blocker.contract().map(|c| c.produces().to_owned()),
+                summary: result.summary().to_owned(),
+                artifacts: result.artifacts().to_vec(),
+                agent: result.agent().map(str::to_owned),
+                comments: blocker.comments()[skip..].to_vec(),
+            })
+        })
+        .collect();
+
+    Context {
+        upstream,
+        warnings: contract_warnings(task, &blockers),
+    }
+}
+
+/// Compare the task's `receives` with its blockers' `produces` by the words
+/// they share. Only a rough check: it flags handoffs where most of what the
+/// task expects is never mentioned upstream.
+fn contract_warnings(task: &Task, blockers: &[&Task]) -> Vec<String> {
+    let Some(receives) = task.contract().map(Contract::receives) else {
+        return Vec::new();
+    };
+    if blockers.is_empty() {
+        return Vec::new();
+    }
+
+    let produced: HashSet<String> = blockers
+        .iter()
+        .filter_map(|b| b.contract())
+        .flat_map(|c| keywords(c.produces()))
+        .collect();
+    let expected = keywords(receives);
+    let missing: Vec<&String> = expected
+        .iter()
+        .filter(|word| !produced.contains(*word))
+        .collect();
+
+    if expected.is_empty() || missing.len() * 2 <= expected.len() {
+        return Vec::new();
+    }
+    let mut missing: Vec<&str> = missing.into_iter().map(String::as_str).collect();
+    missing.sort_unstable();
+    vec![format!(
+        "Blockers may not produce what this task receives (\"{receives}\"): nothing upstream mentions {}",
+        missing.join(", ")
+    )]
+}
+
+/// Lowercased words of three or more letters, without common filler words or
+/// a plural "s".
+fn keywords(text: &str) -> HashSet<String> {
+    const FILLER: &[&str] = &[
+        "and", "the", "for", "with", "from", "that", "this", "into", "are", "its", "any", "all",
+    ];
+    text.split(|c: char| !c.is_alphanumeric())
+        .map(str::to_lowercase)
+        .filter(|word| word.len() >= 3 && !FILLER.contains(&word.as_str()))
+        .map(|word| match word.strip_suffix('s') {
+            Some(stem) if stem.len() >= 3 => stem.to_owned(),
+            _ => word,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use jiff::Timestamp;
+    use rstest::rstest;
+
+    fn task_with_contract(receives: &str, produces: &str) -> Task {
+        let now = Timestamp::now();
+        Task::new(
+            "t1".to_string(),
+            "g1".to_string(),
+            "test task".to_string(),
+            Some(Contract::new(
+                receives.to_string(),
+                produces.to_string(),
+                String::new(),
+            )),
+            TaskState::Completed,
+            Vec::new(),
+            now,
+            now,
+        )
+    }
+
+    // -- contract_warnings --
+
+    // A blocker whose produces names most of what the task receives passes;
+    // one that mentions none of it is flagged.
+    #[rstest]
+    #[case::matching("User schema and migrations", "Database schema, migrations", false)]
+    #[case::plural("API endpoint", "REST endpoints for the API", false)]
+    #[case::unrelated("Login page mockups", "Database schema, migrations", true)]
+    fn contract_warnings_compare_keywords(
+        #[case] receives: &str,
+        #[case] produces: &str,
+        #[case] warns: bool,
+    ) {
+        let task = task_with_contract(receives, "");
+        let blocker = task_with_contract("", produces);
+        assert_eq!(!contract_warnings(&task, &[&blocker]).is_empty(), warns);
+    }
+}
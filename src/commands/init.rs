@@ -4,6 +4,7 @@ use std::io::Write;
 use std::path::Path;
 
 use crate::RADIAL_DIR;
+use crate::commands::prompt::{DEFAULT_TEMPLATE, PROMPT_TEMPLATE, TEMPLATES_DIR};
 use crate::db::Database;
 
 pub fn run(stealth: bool) -> Result<()> {
@@ -19,6 +20,11 @@ pub fn run(stealth: bool) -> Result<()> {
     let db = Database::open(&radial_dir)?;
     db.init_schema()?;
 
+    let templates_dir = radial_dir.join(TEMPLATES_DIR);
+    fs::create_dir_all(&templates_dir).context("Failed to create templates directory")?;
+    fs::write(templates_dir.join(PROMPT_TEMPLATE), DEFAULT_TEMPLATE)
+        .context("Failed to write prompt template")?;
+
     if stealth {
         add_to_gitignore()?;
     }
@@ -0,0 +1,30 @@
+use anyhow::Result;
+
+use crate::db::Database;
+use crate::id::IdKind;
+use crate::models::Event;
+
+/// Read the journal for a single task, a single goal, or all goals.
+///
+/// Events from several goals are merged in timestamp order.
+pub fn run(goal_id: Option<String>, task_id: Option<String>, db: &Database) -> Result<Vec<Event>> {
+    if let Some(tid) = task_id {
+        let task = db.resolve_task(&tid)?;
+        return Ok(db
+            .list_events(task.goal_id())?
+            .into_iter()
+            .filter(|e| e.task_id() == Some(task.id()))
+            .collect());
+    }
+
+    if let Some(gid) = goal_id {
+        return db.list_events(&db.resolve_id(&gid, IdKind::Goal)?);
+    }
+
+    let mut events = Vec::new();
+    for goal in db.list_goals() {
+        events.extend(db.list_events(goal.id())?);
+    }
+    events.sort_by_key(Event::timestamp);
+    Ok(events)
+}
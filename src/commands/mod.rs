@@ -1,8 +1,17 @@
 #![allow(clippy::needless_pass_by_value)]
 
+pub mod check;
+pub mod config;
 pub mod goal;
+pub mod handoff;
 pub mod init;
+pub mod log;
+pub mod next;
 pub mod prep;
+pub mod prompt;
 pub mod ready;
+pub mod reap;
 pub mod status;
 pub mod task;
+pub mod watch;
+pub mod why;
@@ -0,0 +1,69 @@
+use anyhow::Result;
+use serde::Serialize;
+
+use crate::commands::handoff::{self, Context};
+use crate::commands::{goal, ready, task};
+use crate::db::Database;
+use crate::id::IdKind;
+use crate::models::{GoalState, Task};
+
+/// Exit code of `rd next` when the goal has work left but none of it can start.
+pub const EXIT_NOTHING_READY: u8 = 2;
+/// Exit code of `rd next` when every task under the goal is completed.
+pub const EXIT_GOAL_DONE: u8 = 3;
+
+/// What `rd next` found for a goal.
+#[derive(Debug, Serialize)]
+#[allow(clippy::large_enum_variant)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum Next {
+    Claimed { task: Task, context: Context },
+    NothingReady { goal_id: String },
+    GoalDone { goal_id: String },
+}
+
+impl Next {
+    /// The process exit code that signals this outcome.
+    pub fn exit_code(&self) -> u8 {
+        match self {
+            Self::Claimed { .. } => 0,
+            Self::NothingReady { .. } => EXIT_NOTHING_READY,
+            Self::GoalDone { .. } => EXIT_GOAL_DONE,
+        }
+    }
+}
+
+/// Pick the first ready task under a goal or any of its sub-goals and claim it.
+///
+/// Candidates are tried in `rd ready` order. Each claim is made under the task's
+/// lock, so when another agent takes a candidate first we move on to the next one
+/// instead of failing. Any other reason a candidate can't start is an error.
+pub fn run(goal_id: &str, agent: Option<String>, db: &mut Database) -> Result<Next> {
+    let goal_id = db.resolve_id(goal_id, IdKind::Goal)?;
+    goal::check_budget(&goal_id, db)?;
+
+    let candidates: Vec<String> = ready::run(&goal_id, None, db)?
+        .iter()
+        .map(|t| t.id().to_owned())
+        .collect();
+
+    for task_id in candidates {
+        match task::start(&task_id, agent.clone(), None, db) {
+            Ok(task) => {
+                let context = handoff::context(&task, db);
+                return Ok(Next::Claimed { task, context });
+            }
+            Err(err) if err.is::<task::NotPending>() => {}
+            Err(err) => return Err(err),
+        }
+    }
+
+    let done = db
+        .get_goal(&goal_id)
+        .is_some_and(|g| g.state() == GoalState::Completed);
+    Ok(if done {
+        Next::GoalDone { goal_id }
+    } else {
+        Next::NothingReady { goal_id }
+    })
+}
@@ -14,13 +14,22 @@ rd init --stealth    # Initialize without committing .radial to repo
 
 ### Goals
 
-Goals are high-level objectives containing tasks.
+Goals are high-level objectives containing tasks. A goal can be split into
+sub-goals; a parent's metrics include its sub-goals' tasks, and it completes
+once its own tasks and all of its sub-goals are completed.
 
 ```bash
-rd goal create "Implement user authentication"   # Create a goal
-rd goal list                                      # List all goals
+rd goal create "Implement user authentication"    # Create a goal
+rd goal create "Add login form" --parent <goal_id> # Create a sub-goal
+rd goal list                                       # List all goals as a tree
 ```
 
+Goals can carry a budget: `--max-tokens`, `--max-elapsed` (ms) and `--max-retries` (per task) on
+`rd goal create`, changed later with `rd goal budget <goal_id>` (0 means no limit). The elapsed
+limit counts the `--elapsed` times agents report for their attempts, not wall-clock time. Once a
+goal or any goal above it has used its token or time budget, `rd task start` and `rd task retry`
+are refused. `rd status` warns when a goal has used 80% of a limit.
+
 ### Tasks
 
 Tasks are units of work under a goal. They can have dependencies and contracts.
@@ -38,18 +47,93 @@ rd task create <goal_id> "Parse config" \
 
 # List tasks for a goal
 rd task list <goal_id>
+
+# Set or change a contract after creation
+rd task contract <task_id> --receives "..." --produces "..." --verify "..."
+
+# Edit description, contract fields, or dependencies
+rd task update <task_id> --description "Parse config and env vars" \
+  --add-blocked-by task_ghi --remove-blocked-by task_abc
+```
+
+Completed tasks can only be edited with `--force`. Blockers must be in the same goal, and edits
+that would create a dependency cycle are refused. Run `rd check [goal_id]` to find cycles,
+missing blockers, and tasks that can never start.
+
+### Handoffs
+
+Starting a task (with `rd task start` or `rd next`) prints the context its completed blockers left:
+each blocker's result summary, artifacts and last few comments, so there is no need to look them
+up. It also warns when the blockers' `produces` don't seem to cover the task's `receives`. With
+`--json` this is the `context` field.
+
+### Priorities
+
+Tasks have a priority from `p0` (most urgent) to `p3`, `p2` by default. `rd ready` and `rd next`
+offer higher priorities first, and older tasks first within a priority. Set
+`tasks.critical_path_boost` to prefer, within a priority, tasks that unblock the most work.
+
+```bash
+rd task create <goal_id> "Fix the outage" --priority p0
+rd task update <task_id> --priority p1
 ```
 
 ### Task Lifecycle
 
 ```bash
-rd task start <task_id>                          # Mark as started
+rd task start <task_id>                          # Mark as started (claims a lease)
+rd task heartbeat <task_id>                      # Extend the lease while working
 rd task complete <task_id> --result "Added login endpoint with JWT"
 rd task complete <task_id> --result "Done" --artifacts "src/auth.rs,src/jwt.rs"
 rd task fail <task_id>                           # Mark as failed
 rd task retry <task_id>                          # Retry a failed task
 ```
 
+Report usage with `--prompt-tokens`, `--completion-tokens` and `--model` on complete, submit
+or fail (`--tokens` defaults to their sum). Usage adds up across attempts, so a retried task
+keeps the cost of its failed attempts.
+If `.radial/pricing.toml` lists the model, each attempt's cost is estimated and shown in
+`rd status`.
+
+### Review
+
+For maker/checker workflows, submit the task instead of completing it. A different agent then
+checks it against the contract's `verify` field. Dependents are only unblocked once it passes.
+
+```bash
+rd task submit <task_id> --result "Added login endpoint with JWT"   # in_progress -> verifying
+rd task verify <task_id> --pass --note "Tests pass"                 # verifying -> completed
+rd task verify <task_id> --fail --note "Missing token expiry"       # verifying -> failed
+```
+
+Tasks with a verify command, and every task when `tasks.require_review` is set, can't be
+completed directly; they must go through submit and verify. Reviewers must identify themselves
+with `--agent` or `RD_AGENT`, and can't verify a task they submitted.
+
+A contract can carry a shell command that checks it mechanically. `--run` executes it from the
+project root (10 minute default timeout, `--timeout <secs>` to change), stores the exit code and
+output on the task, and completes the task only if the command exits 0.
+
+```bash
+rd task contract <task_id> --verify-cmd "cargo test -p api"
+rd task verify <task_id> --run
+```
+
+### Leases
+
+Starting or retrying a task claims a lease (30 minutes by default, `--lease-secs` to change). Run
+`rd task heartbeat <task_id>` periodically on long tasks. If the lease expires, the task is
+returned to pending by `rd reap` or the next `rd ready`, and counted as a retry.
+
+### Agent identity
+
+Set `--agent <name>` or the `RD_AGENT` environment variable so every start, complete, fail, retry
+and comment records which agent made it. Filter a goal's tasks by agent with:
+
+```bash
+rd task list <goal_id> --agent <name>
+```
+
 ### Comments
 
 Comments allow you to attach notes or progress updates to tasks. They are timestamped and
@@ -84,15 +168,73 @@ rd task list <goal_id> --verbose
 rd status                    # Show all goals/tasks
 rd status --goal <goal_id>   # Status of specific goal
 rd status --task <task_id>   # Status of specific task
-rd ready <goal_id>           # Show tasks ready to work on (unblocked)
+rd ready <goal_id>           # Show tasks that can start now
+rd ready --limit 5           # Ready tasks across all unfinished goals
+rd why <task_id>             # Why a task isn't ready (blockers, contract, retries, budget)
+rd watch --goal <goal_id>    # Stream task changes (created, started, completed, unblocked, ...)
+```
+
+### Claiming work
+
+`rd next` picks the most urgent ready task of a goal or its sub-goals and claims it in one step, so
+agents never race each other between `rd ready` and `rd task start`. It prints the task with its
+contract and the results of the tasks that blocked it. It exits with 2 when nothing is ready yet
+(other tasks are still running or blocked) and with 3 once the goal is completed.
+
+```bash
+rd next <goal_id> --agent builder-1             # Claim the next task
+```
+
+### History
+
+Every goal and task change is appended to a per-goal journal with timestamps, the previous and
+next state, and the acting agent.
+
+```bash
+rd log                       # History across all goals
+rd log --goal <goal_id>      # History of one goal and its tasks
+rd log --task <task_id>      # History of one task
+```
+
+### Prompts
+
+`rd prompt <task_id>` renders a prompt for one task: goal, contract, upstream results, comments
+and the commands to finish it, submitting instead of completing when the task needs review. Pass
+`--agent <name>` to put the agent's name in those commands. Edit `.radial/templates/prompt.md` to
+change it, or pass `--template <file>`.
+
+### MCP & HTTP
+
+`rd mcp` serves the same operations as MCP tools over stdin/stdout (`goal_create`, `task_create`,
+`ready`, `next`, `task_start`, `task_heartbeat`, `task_complete`, `task_submit`, `task_verify`,
+`task_fail`, `task_comment`, `status`, ...). Tool results are the JSON that the matching command
+prints with `--json`; `task_complete` and `task_verify` return `{task, unblocked_task_ids}`. Pass
+`agent` to act as a given agent; on `task_list` it only filters.
+
+`rd serve --bind 127.0.0.1:PORT` exposes them as a local HTTP/JSON API, e.g. `POST /goals/<id>/next`
+or `POST /tasks/<id>/complete` with `{"result": "..."}` (see the README for all routes). It only
+listens on loopback unless given `--allow-remote`, and POSTs need `Content-Type: application/json`.
+
+### Configuration
+
+IDs are prefixed by kind (`g-` goals, `t-` tasks, `c-` comments), so passing a goal ID where a
+task ID is expected gives a clear error. Any unique prefix of an ID works in its place, with or
+without the kind prefix (`rd task start 4fz`), as long as it has at least 3 characters after the
+kind prefix; an ambiguous prefix lists the IDs it matches.
+
+Project settings live in `.radial/config.toml` (ID prefixes/length, whether a contract or verify
+command is required before start, default agent, JSON/concise output by default).
+
+```bash
+rd config list                                  # Show all settings
+rd config set tasks.require_verify_cmd true     # Change a setting
 ```
 
 ### Typical Workflow
 
 1. `rd goal create "Build feature X"` -> get goal_id
 2. `rd task create <goal_id> "Task A"` -> create tasks with dependencies
-3. `rd ready <goal_id>` -> see what's unblocked
-4. `rd task start <task_id>` -> claim a task
-5. `rd task complete <task_id> --result "..."` -> finish it
-6. Repeat from step 3"#
+3. `rd next <goal_id>` -> claim the next unblocked task (exit 2: wait and retry, exit 3: done)
+4. `rd task complete <task_id> --result "..."` -> finish it
+5. Repeat from step 3"#
 }
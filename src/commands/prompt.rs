@@ -0,0 +1,301 @@
+use std::fmt::Write as _;
+use std::fs;
+use std::path::Path;
+
+use anyhow::{Context as _, Result, anyhow};
+
+use crate::commands::handoff::{self, Context};
+use crate::commands::task::needs_review;
+use crate::db::Database;
+use crate::models::{Comment, Task};
+
+/// Directory under `.radial/` holding user templates.
+pub const TEMPLATES_DIR: &str = "templates";
+/// Template used by `rd prompt` when `--template` isn't given.
+pub const PROMPT_TEMPLATE: &str = "prompt.md";
+
+/// Built-in prompt template, written to `.radial/templates/prompt.md` by
+/// `rd init` and used when that file is missing.
+pub const DEFAULT_TEMPLATE: &str = r"You are an agent working on the goal {{goal_id}}: {{goal_description}}
+Work is coordinated with rd, a task tracker shared with other agents.
+
+## Your task
+
+{{task_id}} (priority {{priority}}): {{task_description}}
+
+{{contract}}
+
+## Context from upstream tasks
+
+{{upstream}}
+
+## Notes on this task
+
+{{comments}}
+
+## Commands
+
+{{commands}}
+";
+
+/// Every variable a template may use.
+pub const VARIABLES: &[&str] = &[
+    "goal_id",
+    "goal_description",
+    "task_id",
+    "task_description",
+    "priority",
+    "receives",
+    "produces",
+    "verify",
+    "verify_cmd",
+    "contract",
+    "upstream",
+    "comments",
+    "commands",
+];
+
+/// Render a task's prompt from a template.
+///
+/// `template` is a file path, or the name of a file in `.radial/templates/`.
+/// Without one, `.radial/templates/prompt.md` is used if present and the
+/// built-in template otherwise. The commands in the prompt name `agent`, or the
+/// agent holding the task, so the work is attributed to whoever does it.
+pub fn run(
+    task_id: &str,
+    template: Option<&Path>,
+    agent: Option<&str>,
+    db: &Database,
+) -> Result<String> {
+    let task = db.resolve_task(task_id)?;
+    let goal = db.resolve_goal(task.goal_id())?;
+    let template = load_template(template, db)?;
+
+    let contract = task.contract();
+    let field = |value: Option<&str>| value.unwrap_or_default().to_owned();
+    let vars = [
+        ("goal_id", goal.id().to_owned()),
+        ("goal_description", goal.description().to_owned()),
+        ("task_id", task.id().to_owned()),
+        ("task_description", task.description().to_owned()),
+        ("priority", format!("p{}", task.priority())),
+        ("receives", field(contract.map(|c| c.receives()))),
+        ("produces", field(contract.map(|c| c.produces()))),
+        ("verify", field(contract.map(|c| c.verify()))),
+        ("verify_cmd", field(contract.and_then(|c| c.verify_cmd()))),
+        ("contract", contract_block(task)),
+        ("upstream", upstream_block(&handoff::context(task, db))),
+        ("comments", comments_block(task.comments())),
+        (
+            "commands",
+            commands_block(
+                task,
+                agent.or(task.claimed_by()),
+                db.config().require_review(),
+            ),
+        ),
+    ];
+
+    render(&template, &vars)
+}
+
+fn load_template(template: Option<&Path>, db: &Database) -> Result<String> {
+    let templates = db.base_path().join(TEMPLATES_DIR);
+    let path = match template {
+        Some(path) if path.is_file() => path.to_path_buf(),
+        Some(name) if templates.join(name).is_file() => templates.join(name),
+        Some(path) => return Err(anyhow!("Template not found: {}", path.display())),
+        None if templates.join(PROMPT_TEMPLATE).is_file() => templates.join(PROMPT_TEMPLATE),
+        None => return Ok(DEFAULT_TEMPLATE.to_owned()),
+    };
+
+    fs::read_to_string(&path).with_context(|| format!("Failed to read {}", path.display()))
+}
+
+/// Replace every `{{name}}` in `template` with its value. Whitespace inside the
+/// braces is ignored; an unknown name is an error so typos don't go unnoticed.
+pub fn render(template: &str, vars: &[(&str, String)]) -> Result<String> {
+    let mut out = String::with_capacity(template.len());
+    let mut rest = template;
+
+    while let Some(start) = rest.find("{{") {
+        out.push_str(&rest[..start]);
+        let after = &rest[start + 2..];
+        let end = after
+            .find("}}")
+            .ok_or_else(|| anyhow!("Unclosed '{{{{' in template"))?;
+        let name = after[..end].trim();
+        let value = vars
+            .iter()
+            .find(|(var, _)| *var == name)
+            .map(|(_, value)| value)
+            .ok_or_else(|| {
+                anyhow!(
+                    "Unknown template variable: {name}\nAvailable: {}",
+                    VARIABLES.join(", ")
+                )
+            })?;
+        out.push_str(value);
+        rest = &after[end + 2..];
+    }
+    out.push_str(rest);
+    Ok(out)
+}
+
+fn contract_block(task: &Task) -> String {
+    let Some(contract) = task.contract() else {
+        return "No contract has been set for this task.".to_owned();
+    };
+
+    let mut block = format!(
+        "- Receives: {}\n- Produces: {}\n- Verify: {}",
+        contract.receives(),
+        contract.produces(),
+        contract.verify()
+    );
+    if let Some(cmd) = contract.verify_cmd() {
+        let _ = write!(block, "\n- Verify command: `{cmd}`");
+    }
+    block
+}
+
+fn upstream_block(context: &Context) -> String {
+    if context.is_empty() {
+        return "None.".to_owned();
+    }
+
+    let mut block = String::new();
+    for handoff in context.upstream() {
+        let _ = writeln!(
+            block,
+            "- {} ({}): {}",
+            handoff.task_id(),
+            handoff.description(),
+            handoff.summary()
+        );
+        if !handoff.artifacts().is_empty() {
+            let _ = writeln!(block, "  Artifacts: {}", handoff.artifacts().join(", "));
+        }
+        for comment in handoff.comments() {
+            let _ = writeln!(block, "  Note: {}", comment.text());
+        }
+    }
+    for warning in context.warnings() {
+        let _ = writeln!(block, "Warning: {warning}");
+    }
+    block.trim_end().to_owned()
+}
+
+fn comments_block(comments: &[Comment]) -> String {
+    if comments.is_empty() {
+        return "None.".to_owned();
+    }
+
+    comments
+        .iter()
+        .map(|c| match c.agent() {
+            Some(agent) => format!("- {agent}: {}", c.text()),
+            None => format!("- {}", c.text()),
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// The commands for working the task. A task that needs review is submitted
+/// rather than completed, since `rd task complete` would refuse it.
+fn commands_block(task: &Task, agent: Option<&str>, require_review: bool) -> String {
+    let task_id = task.id();
+    let agent_flag = format!("--agent {}", agent.unwrap_or("<your-name>"));
+
+    let mut block = String::new();
+    if agent.is_none() {
+        block.push_str(
+            "- Name yourself on every command: replace `<your-name>`, or set `RD_AGENT` and drop `--agent`\n",
+        );
+    }
+    let _ = writeln!(
+        block,
+        "- Claim it, unless you already have: `rd task start {task_id} {agent_flag}`
+- Extend your lease during long work: `rd task heartbeat {task_id} {agent_flag}`"
+    );
+    if needs_review(task, require_review) {
+        let _ = writeln!(
+            block,
+            "- When the contract is met, submit it for review: `rd task submit {task_id} --result '<summary>' --artifacts '<files>' {agent_flag}`
+  Another agent verifies it; don't complete it yourself."
+        );
+    } else {
+        let _ = writeln!(
+            block,
+            "- When the contract is met: `rd task complete {task_id} --result '<summary>' --artifacts '<files>' {agent_flag}`"
+        );
+    }
+    let _ = write!(
+        block,
+        "- If you can't finish it, explain why and give it up:
+  `rd task comment {task_id} '<reason>' {agent_flag}` then `rd task fail {task_id} {agent_flag}`"
+    );
+    block
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    // -- render --
+
+    // Variables are replaced wherever they appear, with or without spaces
+    // inside the braces.
+    #[rstest]
+    fn render_replaces_variables() {
+        let vars = [
+            ("task_id", "t-1".to_string()),
+            ("priority", "p2".to_string()),
+        ];
+        let out = render("{{task_id}} at {{ priority }}, again {{task_id}}.", &vars).unwrap();
+        assert_eq!(out, "t-1 at p2, again t-1.");
+    }
+
+    // Unknown variables and unclosed braces are errors.
+    #[rstest]
+    #[case::unknown("{{task_name}}")]
+    #[case::unclosed("{{task_id")]
+    fn render_rejects_bad_templates(#[case] template: &str) {
+        let vars = [("task_id", "t-1".to_string())];
+        assert!(render(template, &vars).is_err());
+    }
+
+    // The built-in template only uses known variables.
+    #[rstest]
+    fn default_template_renders() {
+        let vars: Vec<(&str, String)> = VARIABLES.iter().map(|v| (*v, String::new())).collect();
+        assert!(render(DEFAULT_TEMPLATE, &vars).is_ok());
+    }
+
+    // -- commands_block --
+
+    // Tasks that need review are submitted, not completed, and every command
+    // names the agent.
+    #[rstest]
+    #[case::plain(false, "rd task complete t-1")]
+    #[case::review_required(true, "rd task submit t-1")]
+    fn commands_block_finishes_the_right_way(#[case] require_review: bool, #[case] finish: &str) {
+        let now = jiff::Timestamp::now();
+        let task = Task::new(
+            "t-1".to_string(),
+            "g-1".to_string(),
+            "Do it".to_string(),
+            None,
+            crate::models::TaskState::Pending,
+            Vec::new(),
+            now,
+            now,
+        );
+        let block = commands_block(&task, Some("bot"), require_review);
+        assert!(block.contains(finish));
+        assert!(block.contains("rd task start t-1 --agent bot"));
+        assert!(block.contains("rd task fail t-1 --agent bot"));
+        assert!(!block.contains("RD_AGENT"));
+    }
+}
@@ -1,16 +1,91 @@
-use anyhow::{Result, anyhow};
+use std::cmp::Reverse;
 
+use anyhow::Result;
+use serde::Serialize;
+
+use crate::commands::task::StartPolicy;
 use crate::db::Database;
-use crate::models::{Task, TaskState};
+use crate::graph;
+use crate::id::IdKind;
+use crate::models::{GoalState, Task, TaskState};
+
+/// A ready task listed across goals, with the description of its goal.
+#[derive(Debug, Serialize)]
+pub struct ReadyTask {
+    #[serde(flatten)]
+    task: Task,
+    goal_description: String,
+}
+
+impl ReadyTask {
+    pub fn task(&self) -> &Task {
+        &self.task
+    }
+
+    pub fn goal_description(&self) -> &str {
+        &self.goal_description
+    }
+}
+
+/// Pending tasks of a goal and its sub-goals that can start now, most urgent
+/// first.
+///
+/// Tasks are ordered by priority, then by age. With `tasks.critical_path_boost`
+/// set, tasks of equal priority that unblock more downstream work come first.
+pub fn run(goal_id: &str, limit: Option<usize>, db: &Database) -> Result<Vec<Task>> {
+    let goal_id = db.resolve_id(goal_id, IdKind::Goal)?;
+    let mut tasks: Vec<Task> = db
+        .goal_subtree(&goal_id)
+        .iter()
+        .flat_map(|gid| ready_in(gid, db))
+        .collect();
+    sort(&mut tasks, db);
+    tasks.truncate(limit.unwrap_or(usize::MAX));
+    Ok(tasks)
+}
 
-pub fn run(goal_id: &str, db: &Database) -> Result<Vec<Task>> {
-    db.get_goal(goal_id)
-        .ok_or_else(|| anyhow!("Goal not found: {goal_id}"))?;
+/// Ready tasks of every goal that isn't completed, in the same order as `run`.
+pub fn run_all(limit: Option<usize>, db: &Database) -> Vec<ReadyTask> {
+    let mut tasks: Vec<Task> = db
+        .list_goals()
+        .into_iter()
+        .filter(|g| g.state() != GoalState::Completed)
+        .flat_map(|g| ready_in(g.id(), db))
+        .collect();
+    sort(&mut tasks, db);
+    tasks.truncate(limit.unwrap_or(usize::MAX));
 
-    Ok(db
-        .list_tasks(goal_id)
+    tasks
         .into_iter()
-        .filter(|t| t.state() == TaskState::Pending && t.contract().is_some())
+        .map(|task| ReadyTask {
+            goal_description: db
+                .get_goal(task.goal_id())
+                .map(|g| g.description().to_owned())
+                .unwrap_or_default(),
+            task,
+        })
+        .collect()
+}
+
+/// Pending tasks of one goal that `rd task start` would accept.
+fn ready_in(goal_id: &str, db: &Database) -> Vec<Task> {
+    let policy = StartPolicy::for_goal(goal_id, db);
+    db.list_tasks(goal_id)
+        .into_iter()
+        .filter(|t| t.state() == TaskState::Pending)
+        .filter(|t| policy.refusals(t).is_empty())
         .cloned()
-        .collect())
+        .collect()
+}
+
+fn sort(tasks: &mut [Task], db: &Database) {
+    let boost = db.config().critical_path_boost();
+    tasks.sort_by_cached_key(|t| {
+        let downstream = if boost {
+            graph::downstream_count(t.goal_id(), t.id(), db)
+        } else {
+            0
+        };
+        (t.priority(), Reverse(downstream), t.created_at())
+    });
 }
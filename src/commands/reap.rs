@@ -0,0 +1,46 @@
+use anyhow::Result;
+use jiff::Timestamp;
+
+use crate::commands::goal;
+use crate::db::Database;
+use crate::id::IdKind;
+use crate::models::{Task, TaskState};
+
+/// Return in-progress tasks whose lease has expired to pending.
+///
+/// Only tasks under `goal_id` and its sub-goals are considered when it is given. Returns the
+/// tasks that were reaped.
+pub fn run(goal_id: Option<&str>, db: &mut Database) -> Result<Vec<Task>> {
+    let goal_ids: Vec<String> = match goal_id {
+        Some(gid) => db.goal_subtree(&db.resolve_id(gid, IdKind::Goal)?),
+        None => db.list_goals().iter().map(|g| g.id().to_owned()).collect(),
+    };
+
+    let now = Timestamp::now();
+    let expired: Vec<String> = goal_ids
+        .iter()
+        .flat_map(|gid| db.list_tasks(gid))
+        .filter(|t| {
+            t.state() == TaskState::InProgress && t.lease().is_some_and(|l| l.is_expired(now))
+        })
+        .map(|t| t.id().to_owned())
+        .collect();
+
+    let mut reaped = Vec::new();
+    for task_id in &expired {
+        // Another process may have heartbeated or finished the task since we
+        // loaded it; expire_lease re-checks against the locked on-disk copy.
+        let task = db.update_task(task_id, |task| {
+            Ok(task.expire_lease(Timestamp::now()).then(|| task.clone()))
+        })?;
+        reaped.extend(task);
+    }
+
+    let mut goal_ids: Vec<String> = reaped.iter().map(|t| t.goal_id().to_owned()).collect();
+    goal_ids.dedup();
+    for goal_id in &goal_ids {
+        goal::settle(goal_id, db)?;
+    }
+
+    Ok(reaped)
+}
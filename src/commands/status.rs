@@ -1,4 +1,4 @@
-use anyhow::{Result, anyhow};
+use anyhow::Result;
 use serde::Serialize;
 
 use crate::db::Database;
@@ -9,7 +9,10 @@ pub struct GoalStatus {
     #[serde(flatten)]
     goal: Goal,
     tasks: Vec<Task>,
+    sub_goals: Vec<Goal>,
     metrics: Metrics,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    budget_warnings: Vec<String>,
 }
 
 impl GoalStatus {
@@ -21,9 +24,18 @@ impl GoalStatus {
         &self.tasks
     }
 
+    pub fn sub_goals(&self) -> &[Goal] {
+        &self.sub_goals
+    }
+
     pub fn metrics(&self) -> &Metrics {
         &self.metrics
     }
+
+    /// Limits in the goal's budget that are nearly or fully used up.
+    pub fn budget_warnings(&self) -> &[String] {
+        &self.budget_warnings
+    }
 }
 
 #[derive(Debug, Serialize)]
@@ -31,6 +43,8 @@ pub struct GoalSummary {
     #[serde(flatten)]
     goal: Goal,
     computed_metrics: Metrics,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    budget_warnings: Vec<String>,
 }
 
 impl GoalSummary {
@@ -41,10 +55,16 @@ impl GoalSummary {
     pub fn computed_metrics(&self) -> &Metrics {
         &self.computed_metrics
     }
+
+    /// Limits in the goal's budget that are nearly or fully used up.
+    pub fn budget_warnings(&self) -> &[String] {
+        &self.budget_warnings
+    }
 }
 
 /// Result of a status query - can be a single task, single goal, or all goals.
 #[derive(Debug)]
+#[allow(clippy::large_enum_variant)]
 pub enum StatusResult {
     Task(Task),
     Goal(GoalStatus),
@@ -68,24 +88,24 @@ pub fn run(
 }
 
 fn get_task(task_id: &str, db: &Database) -> Result<Task> {
-    db.get_task(task_id)
-        .cloned()
-        .ok_or_else(|| anyhow!("Task not found: {task_id}"))
+    db.resolve_task(task_id).cloned()
 }
 
 fn get_goal(goal_id: &str, db: &Database) -> Result<GoalStatus> {
-    let goal = db
-        .get_goal(goal_id)
-        .ok_or_else(|| anyhow!("Goal not found: {goal_id}"))?
-        .clone();
+    let goal = db.resolve_goal(goal_id)?.clone();
+    let goal_id = goal.id();
 
     let tasks: Vec<Task> = db.list_tasks(goal_id).into_iter().cloned().collect();
+    let sub_goals: Vec<Goal> = db.list_child_goals(goal_id).into_iter().cloned().collect();
     let metrics = db.compute_goal_metrics(goal_id);
+    let budget_warnings = budget_warnings(&goal, &metrics);
 
     Ok(GoalStatus {
         goal,
         tasks,
+        sub_goals,
         metrics,
+        budget_warnings,
     })
 }
 
@@ -96,8 +116,15 @@ fn get_all_goals(db: &Database) -> Vec<GoalSummary> {
             let computed_metrics = db.compute_goal_metrics(goal.id());
             GoalSummary {
                 goal: goal.clone(),
+                budget_warnings: budget_warnings(goal, &computed_metrics),
                 computed_metrics,
             }
         })
         .collect()
 }
+
+fn budget_warnings(goal: &Goal, metrics: &Metrics) -> Vec<String> {
+    goal.budget()
+        .map(|budget| budget.warnings(metrics))
+        .unwrap_or_default()
+}
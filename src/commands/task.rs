@@ -1,30 +1,80 @@
+use std::collections::HashSet;
+use std::path::Path;
+use std::time::Duration;
+
 use anyhow::{Result, anyhow};
 use jiff::Timestamp;
+use serde::Serialize;
 
+use crate::commands::goal;
 use crate::db::Database;
-use crate::helpers::find_similar_id;
-use crate::id::generate_id;
-use crate::models::{Comment, Contract, GoalState, Outcome, Task, TaskMetrics, TaskState};
+use crate::graph;
+use crate::id::IdKind;
+use crate::models::{Comment, Contract, Lease, Outcome, Review, Task, TaskMetrics, TaskState};
+use crate::runner;
+
+/// Edits to an existing task. `None` and empty fields are left unchanged.
+#[derive(Debug, Default)]
+pub struct TaskUpdate {
+    pub description: Option<String>,
+    pub receives: Option<String>,
+    pub produces: Option<String>,
+    pub verify: Option<String>,
+    /// An empty string removes the verify command.
+    pub verify_cmd: Option<String>,
+    pub add_blocked_by: Vec<String>,
+    pub remove_blocked_by: Vec<String>,
+    pub priority: Option<u8>,
+}
+
+impl TaskUpdate {
+    fn is_empty(&self) -> bool {
+        self.description.is_none()
+            && self.priority.is_none()
+            && !self.changes_contract()
+            && self.add_blocked_by.is_empty()
+            && self.remove_blocked_by.is_empty()
+    }
+
+    fn changes_contract(&self) -> bool {
+        self.receives.is_some()
+            || self.produces.is_some()
+            || self.verify.is_some()
+            || self.verify_cmd.is_some()
+    }
+}
 
 /// Result of completing a task, including any unblocked tasks.
-#[derive(Debug)]
+#[derive(Debug, Serialize)]
 pub struct CompleteResult {
     pub task: Task,
     pub unblocked_task_ids: Vec<String>,
 }
 
-fn task_not_found_err(task_id: &str, db: &Database) -> anyhow::Error {
-    let all_task_ids: Vec<&str> = db
-        .list_goals()
+/// Resolve each blocker ID to a full task ID, checking that it names a task in
+/// the same goal.
+fn resolve_blockers(goal_id: &str, blocker_ids: &[String], db: &Database) -> Result<Vec<String>> {
+    blocker_ids
         .iter()
-        .flat_map(|goal| db.list_tasks(goal.id()).into_iter().map(Task::id))
-        .collect();
+        .map(|input| {
+            let task = db.resolve_task(input)?;
+            if task.goal_id() != goal_id {
+                return Err(anyhow!(
+                    "Task {} belongs to goal {}, not {goal_id}\nBlockers must be in the same goal.",
+                    task.id(),
+                    task.goal_id()
+                ));
+            }
+            Ok(task.id().to_owned())
+        })
+        .collect()
+}
 
-    if let Some(suggestion) = find_similar_id(task_id, &all_task_ids) {
-        anyhow!("Task not found: {task_id}\nDid you mean: {suggestion}")
-    } else {
-        anyhow!("Task not found: {task_id}")
-    }
+fn has_incomplete_blocker(blocker_ids: &[String], db: &Database) -> bool {
+    blocker_ids.iter().any(|id| {
+        db.get_task(id)
+            .is_none_or(|t| t.state() != TaskState::Completed)
+    })
 }
 
 #[allow(clippy::too_many_arguments)]
@@ -34,68 +84,40 @@ pub fn create(
     receives: Option<String>,
     produces: Option<String>,
     verify: Option<String>,
+    verify_cmd: Option<String>,
     blocked_by: Option<Vec<String>>,
+    priority: Option<u8>,
     db: &mut Database,
 ) -> Result<Task> {
-    let goal = db.get_goal(goal_id);
-
-    if goal.is_none() {
-        let all_goals = db.list_goals();
-        let goal_ids: Vec<&str> = all_goals.iter().map(|g| g.id()).collect();
+    let goal_id_owned = db.resolve_id(goal_id, IdKind::Goal)?;
+    let blocked_by = blocked_by
+        .map(|task_ids| resolve_blockers(&goal_id_owned, &task_ids, db))
+        .transpose()?;
 
-        return if let Some(suggestion) = find_similar_id(goal_id, &goal_ids) {
-            Err(anyhow!(
-                "Goal not found: {goal_id}\nDid you mean: {suggestion}"
-            ))
+    // Build contract if any contract fields are provided
+    let contract =
+        if receives.is_some() || produces.is_some() || verify.is_some() || verify_cmd.is_some() {
+            Some(
+                Contract::new(
+                    receives.unwrap_or_default(),
+                    produces.unwrap_or_default(),
+                    verify.unwrap_or_default(),
+                )
+                .with_verify_cmd(verify_cmd.filter(|c| !c.is_empty())),
+            )
         } else {
-            Err(anyhow!("Goal not found: {goal_id}"))
+            None
         };
-    }
-
-    let goal = goal.unwrap();
-    let goal_id_owned = goal.id().to_owned();
-    let goal_state = goal.state();
-
-    // Validate blocked_by task IDs exist
-    if let Some(ref task_ids) = blocked_by {
-        let all_tasks = db.list_tasks(&goal_id_owned);
-        let existing_task_ids: Vec<&str> = all_tasks.iter().map(|t| t.id()).collect();
-
-        for task_id in task_ids {
-            if !existing_task_ids.contains(&task_id.as_str()) {
-                return if let Some(suggestion) = find_similar_id(task_id, &existing_task_ids) {
-                    Err(anyhow!(
-                        "Task not found in blocked-by list: {task_id}\nDid you mean: {suggestion}"
-                    ))
-                } else {
-                    Err(anyhow!(
-                        "Task not found in blocked-by list: {task_id}\nTask must exist in the same goal."
-                    ))
-                };
-            }
-        }
-    }
-
-    // Build contract if any contract fields are provided
-    let contract = if receives.is_some() || produces.is_some() || verify.is_some() {
-        Some(Contract::new(
-            receives.unwrap_or_default(),
-            produces.unwrap_or_default(),
-            verify.unwrap_or_default(),
-        ))
-    } else {
-        None
-    };
 
     let blocked_by_ids = blocked_by.unwrap_or_default();
-    let state = if blocked_by_ids.is_empty() {
-        TaskState::Pending
-    } else {
+    let state = if has_incomplete_blocker(&blocked_by_ids, db) {
         TaskState::Blocked
+    } else {
+        TaskState::Pending
     };
     let now = Timestamp::now();
     let task = Task::new(
-        generate_id(),
+        db.config().new_id(IdKind::Task),
         goal_id_owned.clone(),
         description,
         contract,
@@ -103,111 +125,484 @@ pub fn create(
         blocked_by_ids,
         now,
         now,
-    );
+    )
+    .with_priority(priority.unwrap_or(Task::DEFAULT_PRIORITY));
 
     db.create_task(task.clone())?;
 
-    // Update the goal
-    let base = db.base_path().to_owned();
-    let goal = db.get_goal_mut(&goal_id_owned).unwrap();
-    if goal_state == GoalState::Pending {
-        goal.mark_in_progress();
-    } else {
-        goal.touch();
-    }
-    goal.write_file(&base)?;
+    goal::settle(&goal_id_owned, db)?;
 
     Ok(task)
 }
 
-pub fn list(goal_id: &str, db: &Database) -> Result<Vec<Task>> {
-    db.get_goal(goal_id)
-        .ok_or_else(|| anyhow!("Goal not found: {goal_id}"))?;
+pub fn list(goal_id: &str, agent: Option<&str>, db: &Database) -> Result<Vec<Task>> {
+    let goal_id = &db.resolve_id(goal_id, IdKind::Goal)?;
 
-    Ok(db.list_tasks(goal_id).into_iter().cloned().collect())
+    Ok(db
+        .list_tasks(goal_id)
+        .into_iter()
+        .filter(|t| agent.is_none_or(|a| t.touched_by(a)))
+        .cloned()
+        .collect())
 }
 
-pub fn start(task_id: &str, db: &mut Database) -> Result<Task> {
-    let task = db.get_task(task_id);
+pub fn update(task_id: &str, changes: TaskUpdate, force: bool, db: &mut Database) -> Result<Task> {
+    let task = db.resolve_task(task_id)?;
+    let task_id = &task.id().to_owned();
 
-    if task.is_none() {
-        return Err(task_not_found_err(task_id, db));
+    if changes.is_empty() {
+        return Err(anyhow!(
+            "Nothing to update. Pass --description, --priority, a contract field, --add-blocked-by or --remove-blocked-by."
+        ));
     }
 
-    let task = task.unwrap();
+    let goal_id = task.goal_id().to_owned();
 
-    if task.contract().is_none() {
-        return Err(anyhow!(
-            "Task has no contract. Set a contract before starting.\nUse: radial task contract {} --receives \"...\" --produces \"...\" --verify \"...\"",
-            task.id()
-        ));
+    let remove_blocked_by = changes
+        .remove_blocked_by
+        .iter()
+        .map(|input| db.resolve_id(input, IdKind::Task))
+        .collect::<Result<Vec<_>>>()?;
+
+    let add_blocked_by = resolve_blockers(&goal_id, &changes.add_blocked_by, db)?;
+    if add_blocked_by.iter().any(|id| id == task_id) {
+        return Err(anyhow!("Task cannot be blocked by itself: {task_id}"));
+    }
+    for id in &add_blocked_by {
+        if let Some(path) = graph::find_path(&goal_id, id, task_id, db) {
+            return Err(anyhow!(
+                "Adding blocker {id} would create a dependency cycle: {task_id} -> {}",
+                path.join(" -> ")
+            ));
+        }
     }
 
-    if task.state() == TaskState::Blocked && !task.blocked_by().is_empty() {
-        return Err(anyhow!(
-            "Task is blocked by: {}\nComplete those tasks first, or use --force to override.",
-            task.blocked_by().join(", ")
-        ));
+    let blockers_changed = !add_blocked_by.is_empty() || !remove_blocked_by.is_empty();
+    let completed: HashSet<String> = db
+        .list_tasks(&goal_id)
+        .into_iter()
+        .filter(|t| t.state() == TaskState::Completed)
+        .map(|t| t.id().to_owned())
+        .collect();
+
+    db.update_task(task_id, |task| {
+        if task.state() == TaskState::Completed && !force {
+            return Err(anyhow!(
+                "Task is already completed. Use --force to edit it anyway."
+            ));
+        }
+
+        if changes.changes_contract() {
+            let current = task.contract();
+            let field = |new: Option<String>, old: fn(&Contract) -> &str| {
+                new.unwrap_or_else(|| current.map(old).unwrap_or_default().to_owned())
+            };
+            let verify_cmd = match changes.verify_cmd {
+                Some(cmd) => Some(cmd).filter(|c| !c.is_empty()),
+                None => current.and_then(Contract::verify_cmd).map(str::to_owned),
+            };
+            let contract = Contract::new(
+                field(changes.receives, Contract::receives),
+                field(changes.produces, Contract::produces),
+                field(changes.verify, Contract::verify),
+            )
+            .with_verify_cmd(verify_cmd);
+            task.set_contract(contract);
+        }
+
+        if let Some(description) = changes.description {
+            task.set_description(description);
+        }
+
+        if let Some(priority) = changes.priority {
+            task.set_priority(priority);
+        }
+
+        // Edit the blocker list read under the lock, so blockers another process
+        // added or removed in the meantime are kept.
+        if blockers_changed {
+            let mut blocked_by = task.blocked_by().to_vec();
+            for id in &remove_blocked_by {
+                if !blocked_by.contains(id) {
+                    return Err(anyhow!("Task {} is not blocked by {id}", task.id()));
+                }
+                blocked_by.retain(|b| b != id);
+            }
+            for id in add_blocked_by {
+                if !blocked_by.contains(&id) {
+                    blocked_by.push(id);
+                }
+            }
+            let blocked = blocked_by.iter().any(|id| !completed.contains(id));
+            task.set_blocked_by(blocked_by, blocked);
+        }
+
+        Ok(task.clone())
+    })
+}
+
+/// The lease length to claim with, defaulting when none was given.
+fn lease_duration(lease_secs: Option<i64>) -> Result<i64> {
+    let lease_secs = lease_secs.unwrap_or(Lease::DEFAULT_DURATION_SECS);
+    if lease_secs <= 0 {
+        return Err(anyhow!("Lease duration must be positive: {lease_secs}"));
     }
+    Ok(lease_secs)
+}
 
-    if task.state() != TaskState::Pending {
-        return Err(anyhow!(
+/// The error `start` returns when the task has already left pending, usually
+/// because another agent claimed it first.
+#[derive(Debug)]
+pub struct NotPending(pub TaskState);
+
+impl std::fmt::Display for NotPending {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
             "Task must be in 'pending' state to start. Current state: {}",
-            task.state().as_ref()
-        ));
+            self.0.as_ref()
+        )
     }
+}
 
-    let base = db.base_path().to_owned();
-    let task = db.get_task_mut(task_id).unwrap();
-    if !task.transition(TaskState::Pending, TaskState::InProgress) {
-        return Err(anyhow!(
-            "Failed to start task: another process may have already started it"
-        ));
+impl std::error::Error for NotPending {}
+
+/// What a task must satisfy to start, besides being pending with no unfinished
+/// blockers. `rd ready`, `rd why` and `rd task start` all check it, so a task is
+/// only offered when starting it would succeed.
+#[derive(Debug)]
+pub struct StartPolicy {
+    require_contract: bool,
+    require_verify_cmd: bool,
+    retry_limit: Option<i64>,
+    /// Why the goal, or a goal above it, is out of budget.
+    over_budget: Option<String>,
+}
+
+impl StartPolicy {
+    /// The policy for tasks under `goal_id`, from the config and the budgets of
+    /// the goal and its ancestors.
+    pub fn for_goal(goal_id: &str, db: &Database) -> Self {
+        Self {
+            require_contract: db.config().require_contract(),
+            require_verify_cmd: db.config().require_verify_cmd(),
+            retry_limit: goal::retry_limit(goal_id, db),
+            over_budget: goal::check_budget(goal_id, db)
+                .err()
+                .map(|err| err.to_string()),
+        }
+    }
+
+    /// Every reason `task` may not start, in the order `rd task start` reports
+    /// them. Empty when it may.
+    pub fn refusals(&self, task: &Task) -> Vec<String> {
+        let mut reasons: Vec<String> = self.over_budget.iter().cloned().collect();
+
+        if self.require_contract && task.contract().is_none() {
+            reasons.push(format!(
+                "Task has no contract. Set a contract before starting.\nUse: rd task contract {} --receives \"...\" --produces \"...\" --verify \"...\"",
+                task.id()
+            ));
+        } else if self.require_verify_cmd
+            && task.contract().and_then(Contract::verify_cmd).is_none()
+        {
+            reasons.push(format!(
+                "Task has no verify command. Set one before starting.\nUse: rd task contract {} --verify-cmd \"...\"",
+                task.id()
+            ));
+        }
+
+        // Expired leases count as retries, so a reaped task is held to the same limit.
+        if let Some(max) = self.retry_limit
+            && task.metrics().retry_count() > max
+        {
+            reasons.push(format!(
+                "Task has used all {max} retries allowed by its goal's budget"
+            ));
+        }
+
+        reasons
     }
-    task.write_file(&base)?;
 
-    Ok(task.clone())
+    /// Why a failed task may not be retried, once it has used all its retries.
+    pub fn retry_refusal(&self, task: &Task) -> Option<String> {
+        let max = self.retry_limit?;
+        (task.state() == TaskState::Failed && task.metrics().retry_count() >= max)
+            .then(|| format!("Task has used all {max} retries allowed by its goal's budget"))
+    }
+}
+
+pub fn start(
+    task_id: &str,
+    agent: Option<String>,
+    lease_secs: Option<i64>,
+    db: &mut Database,
+) -> Result<Task> {
+    let task = db.resolve_task(task_id)?;
+    let (task_id, goal_id) = (&task.id().to_owned(), task.goal_id().to_owned());
+
+    let lease_secs = lease_duration(lease_secs)?;
+
+    let policy = StartPolicy::for_goal(&goal_id, db);
+
+    // The checks run against the on-disk state under the task lock, so exactly one
+    // process can move a given task out of pending.
+    db.update_task(task_id, |task| {
+        if let Some(reason) = policy.refusals(task).into_iter().next() {
+            return Err(anyhow!(reason));
+        }
+
+        if task.state() == TaskState::Blocked && !task.blocked_by().is_empty() {
+            return Err(anyhow!(
+                "Task is blocked by: {}\nComplete those tasks first, or use --force to override.",
+                task.blocked_by().join(", ")
+            ));
+        }
+
+        let lease = Lease::new(lease_secs, Timestamp::now());
+        let state = task.state();
+        if !task.claim(agent, lease) {
+            return Err(NotPending(state).into());
+        }
+
+        Ok(task.clone())
+    })
+}
+
+pub fn heartbeat(task_id: &str, agent: Option<String>, db: &mut Database) -> Result<Task> {
+    let task_id = &db.resolve_id(task_id, IdKind::Task)?;
+
+    db.update_task(task_id, |task| {
+        if let (Some(holder), Some(agent)) = (task.claimed_by(), agent.as_deref())
+            && holder != agent
+        {
+            return Err(anyhow!("Task lease is held by another agent: {holder}"));
+        }
+
+        if !task.heartbeat(Timestamp::now()) {
+            return Err(anyhow!(
+                "Task must be in 'in_progress' state to heartbeat. Current state: {}",
+                task.state().as_ref()
+            ));
+        }
+
+        Ok(task.clone())
+    })
+}
+
+/// Usage reported for one attempt at a task. The total defaults to the sum of
+/// prompt and completion tokens when it isn't given.
+pub fn usage(
+    tokens: Option<i64>,
+    prompt_tokens: Option<i64>,
+    completion_tokens: Option<i64>,
+    elapsed: Option<i64>,
+    model: Option<String>,
+) -> TaskMetrics {
+    let prompt_tokens = prompt_tokens.unwrap_or(0);
+    let completion_tokens = completion_tokens.unwrap_or(0);
+    let tokens = tokens.unwrap_or(prompt_tokens + completion_tokens);
+
+    TaskMetrics::new(tokens, elapsed.unwrap_or(0), 0)
+        .with_token_split(prompt_tokens, completion_tokens)
+        .with_model(model.filter(|m| !m.is_empty()))
+}
+
+/// Whether a task can only be completed through submit and verify: always when
+/// review is required, and otherwise when its contract has a verify command.
+pub fn needs_review(task: &Task, require_review: bool) -> bool {
+    require_review || task.contract().and_then(Contract::verify_cmd).is_some()
 }
 
 pub fn complete(
     task_id: &str,
     result_summary: String,
     artifacts: Option<Vec<String>>,
-    tokens: Option<i64>,
-    elapsed: Option<i64>,
+    usage: TaskMetrics,
+    agent: Option<String>,
+    db: &mut Database,
+) -> Result<CompleteResult> {
+    let task_id = &db.resolve_id(task_id, IdKind::Task)?;
+
+    let cost = db.pricing().estimate(&usage);
+    let usage = usage.with_estimated_cost(cost);
+    let require_review = db.config().require_review();
+
+    let completed_task = db.update_task(task_id, |task| {
+        if task.state() != TaskState::InProgress {
+            return Err(anyhow!(
+                "Task must be in 'in_progress' state to complete. Current state: {}",
+                task.state().as_ref()
+            ));
+        }
+
+        if needs_review(task, require_review) {
+            return Err(anyhow!(
+                "Task must be reviewed before it completes.\nSubmit it with: rd task submit {} --result \"...\"\nThen another agent verifies it with: rd task verify {} --pass|--fail|--run",
+                task.id(),
+                task.id()
+            ));
+        }
+
+        let outcome = Outcome::new(result_summary, artifacts.unwrap_or_default()).with_agent(agent);
+
+        if !task.complete(outcome, usage) {
+            return Err(anyhow!(
+                "Failed to complete task: another process may have changed its state"
+            ));
+        }
+
+        Ok(task.clone())
+    })?;
+
+    propagate_completion(completed_task, db)
+}
+
+pub fn submit(
+    task_id: &str,
+    result_summary: String,
+    artifacts: Option<Vec<String>>,
+    usage: TaskMetrics,
+    agent: Option<String>,
+    db: &mut Database,
+) -> Result<Task> {
+    let task_id = &db.resolve_id(task_id, IdKind::Task)?;
+
+    let cost = db.pricing().estimate(&usage);
+    let usage = usage.with_estimated_cost(cost);
+
+    db.update_task(task_id, |task| {
+        let outcome = Outcome::new(result_summary, artifacts.unwrap_or_default()).with_agent(agent);
+
+        if !task.submit(outcome, usage) {
+            return Err(anyhow!(
+                "Task must be in 'in_progress' state to submit. Current state: {}",
+                task.state().as_ref()
+            ));
+        }
+
+        Ok(task.clone())
+    })
+}
+
+/// Record a reviewer's verdict on a submitted task. Dependents are only
+/// unblocked when the verdict is a pass.
+pub fn verify(
+    task_id: &str,
+    passed: bool,
+    note: Option<String>,
+    agent: Option<String>,
     db: &mut Database,
 ) -> Result<CompleteResult> {
-    let task = db.get_task(task_id);
+    let task_id = &db.resolve_id(task_id, IdKind::Task)?;
+
+    let task = db.update_task(task_id, |task| {
+        // A review only means something when both sides are known to differ.
+        let submitter = task
+            .result()
+            .and_then(Outcome::agent)
+            .or_else(|| task.claimed_by());
+        let Some(reviewer) = agent.as_deref() else {
+            return Err(anyhow!(
+                "Verifying needs a reviewer identity.\nPass --agent <name> or set RD_AGENT."
+            ));
+        };
+        let Some(submitter) = submitter else {
+            return Err(anyhow!(
+                "Task was submitted anonymously, so no reviewer can be shown to differ.\nVerify it with its command instead: rd task verify {} --run",
+                task.id()
+            ));
+        };
+        if submitter == reviewer {
+            return Err(anyhow!(
+                "Task was submitted by {submitter}; it must be verified by a different agent"
+            ));
+        }
+
+        let review = Review::new(passed, note, agent, Timestamp::now());
+        if !task.verify(review) {
+            return Err(anyhow!(
+                "Task must be in 'verifying' state to verify. Current state: {}",
+                task.state().as_ref()
+            ));
+        }
+
+        Ok(task.clone())
+    })?;
 
-    if task.is_none() {
-        return Err(task_not_found_err(task_id, db));
+    if !passed {
+        goal::settle(task.goal_id(), db)?;
+        return Ok(CompleteResult {
+            task,
+            unblocked_task_ids: Vec::new(),
+        });
     }
 
-    let task = task.unwrap();
+    propagate_completion(task, db)
+}
+
+/// Run the contract's verify command for a submitted task and record the
+/// outcome as its review. The task only completes if the command passes.
+pub fn run_verify(
+    task_id: &str,
+    agent: Option<String>,
+    project_root: &Path,
+    timeout: Duration,
+    db: &mut Database,
+) -> Result<CompleteResult> {
+    let task = db.resolve_task(task_id)?;
+    let task_id = &task.id().to_owned();
 
-    if task.state() != TaskState::InProgress {
+    if task.state() != TaskState::Verifying {
         return Err(anyhow!(
-            "Task must be in 'in_progress' state to complete. Current state: {}",
+            "Task must be in 'verifying' state to verify. Current state: {}\nSubmit it first with: rd task submit {task_id} --result \"...\"",
             task.state().as_ref()
         ));
     }
 
-    let goal_id = task.goal_id().to_owned();
-    let retry_count = task.metrics().retry_count();
-    let artifacts_list = artifacts.unwrap_or_default();
-
-    let outcome = Outcome::new(result_summary, artifacts_list);
-    let metrics = TaskMetrics::new(tokens.unwrap_or(0), elapsed.unwrap_or(0), retry_count);
-
-    let base = db.base_path().to_owned();
-    let task = db.get_task_mut(task_id).unwrap();
-    if !task.complete(outcome, metrics) {
+    let Some(command) = task
+        .contract()
+        .and_then(Contract::verify_cmd)
+        .map(str::to_owned)
+    else {
         return Err(anyhow!(
-            "Failed to complete task: another process may have changed its state"
+            "Task has no verify command.\nUse: rd task contract {task_id} --verify-cmd \"...\""
         ));
+    };
+
+    // The command may take minutes, so it runs without holding the task lock;
+    // the state is re-checked when the verdict is written.
+    let run = runner::run_verify_command(&command, project_root, timeout)?;
+    let passed = run.passed();
+
+    let task = db.update_task(task_id, |task| {
+        let review = Review::new(passed, Some(run.summary()), agent, Timestamp::now());
+        task.set_verify_run(run);
+        if !task.verify(review) {
+            return Err(anyhow!(
+                "Task must be in 'verifying' state to verify. Current state: {}",
+                task.state().as_ref()
+            ));
+        }
+
+        Ok(task.clone())
+    })?;
+
+    if !passed {
+        goal::settle(task.goal_id(), db)?;
+        return Ok(CompleteResult {
+            task,
+            unblocked_task_ids: Vec::new(),
+        });
     }
-    task.write_file(&base)?;
-    let completed_task = task.clone();
+
+    propagate_completion(task, db)
+}
+
+/// Unblock the dependents of a newly completed task and settle its goal's state.
+fn propagate_completion(completed_task: Task, db: &mut Database) -> Result<CompleteResult> {
+    let task_id = completed_task.id().to_owned();
+    let goal_id = completed_task.goal_id().to_owned();
 
     // Snapshot only the fields needed for unblocking
     let tasks_snapshot: Vec<(String, TaskState, Vec<String>)> = db
@@ -220,38 +615,28 @@ pub fn complete(
     let mut unblocked_task_ids = Vec::new();
 
     for (dep_id, _, dep_blocked_by) in &tasks_snapshot {
-        if dep_blocked_by.contains(&task_id.to_owned()) {
+        if dep_blocked_by.contains(&task_id) {
             let all_blockers_done = dep_blocked_by.iter().all(|blocker_id| {
                 db.get_task(blocker_id)
                     .is_some_and(|t| t.state() == TaskState::Completed)
             });
 
             if all_blockers_done {
-                let dep_task = db.get_task_mut(dep_id).unwrap();
-                dep_task.unblock();
-                dep_task.write_file(&base)?;
-                unblocked_task_ids.push(dep_id.clone());
+                let unblocked = db.update_task(dep_id, |dep_task| {
+                    if dep_task.state() != TaskState::Blocked {
+                        return Ok(false);
+                    }
+                    dep_task.unblock();
+                    Ok(true)
+                })?;
+                if unblocked {
+                    unblocked_task_ids.push(dep_id.clone());
+                }
             }
         }
     }
 
-    // Check goal completion
-    let all_tasks = db.list_tasks(&goal_id);
-    let all_completed = all_tasks.iter().all(|t| t.state() == TaskState::Completed);
-    let any_failed = all_tasks.iter().any(|t| t.state() == TaskState::Failed);
-
-    let goal = db
-        .get_goal_mut(&goal_id)
-        .ok_or_else(|| anyhow!("Goal not found: {goal_id}"))?;
-
-    if all_completed {
-        goal.mark_completed();
-    } else if any_failed {
-        goal.mark_failed();
-    } else {
-        goal.touch();
-    }
-    goal.write_file(&base)?;
+    goal::settle(&goal_id, db)?;
 
     Ok(CompleteResult {
         task: completed_task,
@@ -259,74 +644,131 @@ pub fn complete(
     })
 }
 
-pub fn fail(task_id: &str, db: &mut Database) -> Result<Task> {
-    let task = db.get_task(task_id);
-
-    if task.is_none() {
-        return Err(task_not_found_err(task_id, db));
-    }
+/// Mark a task as failed, recording the usage of the attempt that failed.
+pub fn fail(
+    task_id: &str,
+    usage: TaskMetrics,
+    agent: Option<String>,
+    db: &mut Database,
+) -> Result<Task> {
+    let task_id = &db.resolve_id(task_id, IdKind::Task)?;
 
-    let task = task.unwrap();
+    let cost = db.pricing().estimate(&usage);
+    let usage = usage.with_estimated_cost(cost);
 
-    if task.state() != TaskState::InProgress && task.state() != TaskState::Verifying {
-        return Err(anyhow!(
-            "Task must be in 'in_progress' or 'verifying' state to fail. Current state: {}",
-            task.state().as_ref()
-        ));
-    }
+    let task = db.update_task(task_id, |task| {
+        if !task.fail(agent, usage) {
+            return Err(anyhow!(
+                "Task must be in 'in_progress' or 'verifying' state to fail. Current state: {}",
+                task.state().as_ref()
+            ));
+        }
 
-    let base = db.base_path().to_owned();
-    let task = db.get_task_mut(task_id).unwrap();
-    if !task.transition_from_any(
-        &[TaskState::InProgress, TaskState::Verifying],
-        TaskState::Failed,
-    ) {
-        return Err(anyhow!(
-            "Failed to mark task as failed: state may have changed"
-        ));
-    }
-    task.write_file(&base)?;
+        Ok(task.clone())
+    })?;
 
-    Ok(task.clone())
+    goal::settle(task.goal_id(), db)?;
+    Ok(task)
 }
 
-pub fn retry(task_id: &str, db: &mut Database) -> Result<Task> {
-    let task = db.get_task(task_id);
+pub fn retry(
+    task_id: &str,
+    agent: Option<String>,
+    lease_secs: Option<i64>,
+    db: &mut Database,
+) -> Result<Task> {
+    let task = db.resolve_task(task_id)?;
+    let (task_id, goal_id) = (&task.id().to_owned(), task.goal_id().to_owned());
+    let lease_secs = lease_duration(lease_secs)?;
 
-    if task.is_none() {
-        return Err(task_not_found_err(task_id, db));
-    }
+    goal::check_budget(&goal_id, db)?;
+    let policy = StartPolicy::for_goal(&goal_id, db);
 
-    let task = task.unwrap();
+    let task = db.update_task(task_id, |task| {
+        if let Some(reason) = policy.retry_refusal(task) {
+            return Err(anyhow!(reason));
+        }
 
-    if task.state() != TaskState::Failed {
-        return Err(anyhow!(
-            "Task must be in 'failed' state to retry. Current state: {}",
-            task.state().as_ref()
-        ));
-    }
+        if !task.retry(agent, Lease::new(lease_secs, Timestamp::now())) {
+            return Err(anyhow!(
+                "Task must be in 'failed' state to retry. Current state: {}",
+                task.state().as_ref()
+            ));
+        }
 
-    let base = db.base_path().to_owned();
-    let task = db.get_task_mut(task_id).unwrap();
-    if !task.retry() {
-        return Err(anyhow!("Failed to retry task: state may have changed"));
-    }
-    task.write_file(&base)?;
+        Ok(task.clone())
+    })?;
 
-    Ok(task.clone())
+    goal::settle(&goal_id, db)?;
+    Ok(task)
 }
 
-pub fn comment(task_id: &str, text: String, db: &mut Database) -> Result<Task> {
-    if db.get_task(task_id).is_none() {
-        return Err(task_not_found_err(task_id, db));
-    }
+pub fn comment(
+    task_id: &str,
+    text: String,
+    agent: Option<String>,
+    db: &mut Database,
+) -> Result<Task> {
+    let task_id = &db.resolve_id(task_id, IdKind::Task)?;
+
+    let comment =
+        Comment::new(db.config().new_id(IdKind::Comment), text, Timestamp::now()).with_agent(agent);
 
-    let comment = Comment::new(generate_id(), text, Timestamp::now());
+    db.update_task(task_id, |task| {
+        task.add_comment(comment);
+        Ok(task.clone())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::Budget;
+    use rstest::rstest;
+    use tempfile::TempDir;
+
+    fn create_task(goal_id: &str, description: &str, db: &mut Database) -> String {
+        create(
+            goal_id,
+            description.to_string(),
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            db,
+        )
+        .unwrap()
+        .id()
+        .to_owned()
+    }
 
-    let base = db.base_path().to_owned();
-    let task = db.get_task_mut(task_id).unwrap();
-    task.add_comment(comment);
-    task.write_file(&base)?;
+    // -- update --
+
+    // Blocker edits apply to the list read under the task's lock, so a blocker
+    // added by an instance with a stale copy doesn't drop one added meanwhile.
+    #[rstest]
+    fn update_keeps_concurrently_added_blockers() {
+        let dir = TempDir::new().unwrap();
+        let mut first = Database::open(dir.path()).unwrap();
+        let goal_id = goal::create("G".to_string(), None, Budget::default(), &mut first)
+            .unwrap()
+            .id()
+            .to_owned();
+        let task_id = create_task(&goal_id, "task", &mut first);
+        let a = create_task(&goal_id, "a", &mut first);
+        let b = create_task(&goal_id, "b", &mut first);
+        let mut second = Database::open(dir.path()).unwrap();
+
+        let add = |id: &str| TaskUpdate {
+            add_blocked_by: vec![id.to_string()],
+            ..TaskUpdate::default()
+        };
+        update(&task_id, add(&a), false, &mut first).unwrap();
+        let task = update(&task_id, add(&b), false, &mut second).unwrap();
 
-    Ok(task.clone())
+        assert_eq!(task.blocked_by(), [a, b]);
+        assert_eq!(task.state(), TaskState::Blocked);
+    }
 }
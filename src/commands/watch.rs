@@ -0,0 +1,311 @@
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::thread;
+use std::time::{Duration, SystemTime};
+
+use anyhow::Result;
+use jiff::Timestamp;
+use serde::Serialize;
+use strum::AsRefStr;
+
+use crate::db::Database;
+use crate::id::IdKind;
+use crate::models::{Task, TaskState};
+
+/// How often `rd watch` checks `.radial/` for changed files.
+pub const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+#[derive(Debug, Clone, Copy, Serialize, PartialEq, AsRefStr)]
+#[serde(rename_all = "snake_case")]
+#[strum(serialize_all = "snake_case")]
+pub enum ChangeKind {
+    Created,
+    Started,
+    Completed,
+    Unblocked,
+    Failed,
+    Commented,
+}
+
+/// One task transition seen by `rd watch`.
+#[derive(Debug, Serialize)]
+pub struct Change {
+    timestamp: Timestamp,
+    event: ChangeKind,
+    goal_id: String,
+    task_id: String,
+    description: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    agent: Option<String>,
+    /// The comment's text, for `commented` events.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    comment: Option<String>,
+}
+
+impl Change {
+    fn new(kind: ChangeKind, task: &Task, agent: Option<&str>) -> Self {
+        Self {
+            timestamp: task.updated_at(),
+            event: kind,
+            goal_id: task.goal_id().to_owned(),
+            task_id: task.id().to_owned(),
+            description: task.description().to_owned(),
+            agent: agent.map(str::to_owned),
+            comment: None,
+        }
+    }
+
+    pub fn timestamp(&self) -> Timestamp {
+        self.timestamp
+    }
+
+    pub fn kind(&self) -> ChangeKind {
+        self.event
+    }
+
+    pub fn goal_id(&self) -> &str {
+        &self.goal_id
+    }
+
+    pub fn task_id(&self) -> &str {
+        &self.task_id
+    }
+
+    pub fn description(&self) -> &str {
+        &self.description
+    }
+
+    pub fn agent(&self) -> Option<&str> {
+        self.agent.as_deref()
+    }
+
+    pub fn comment(&self) -> Option<&str> {
+        self.comment.as_deref()
+    }
+}
+
+/// Watch `.radial/` and call `emit` for every task change under the goal (and
+/// its sub-goals), or under all goals. Runs until `emit` fails.
+///
+/// The task files are polled every `POLL_INTERVAL`; when any of them changed,
+/// the database is reloaded and compared with the previous load.
+pub fn run(
+    radial_dir: &Path,
+    goal_id: Option<&str>,
+    mut emit: impl FnMut(&Change) -> Result<()>,
+) -> Result<()> {
+    let db = Database::open(radial_dir)?;
+    let goal_id = goal_id
+        .map(|id| db.resolve_id(id, IdKind::Goal))
+        .transpose()?;
+
+    let mut files = fingerprint(radial_dir);
+    let mut tasks = snapshot(&db, goal_id.as_deref());
+
+    loop {
+        thread::sleep(POLL_INTERVAL);
+
+        let current = fingerprint(radial_dir);
+        if current == files {
+            continue;
+        }
+        // A load can fail while another process is mid-write; try again next poll.
+        let Ok(db) = Database::open(radial_dir) else {
+            continue;
+        };
+        files = current;
+
+        let next = snapshot(&db, goal_id.as_deref());
+        for change in changes(&tasks, &next) {
+            emit(&change)?;
+        }
+        tasks = next;
+    }
+}
+
+/// The tasks under a goal subtree, or all tasks, by ID.
+fn snapshot(db: &Database, goal_id: Option<&str>) -> HashMap<String, Task> {
+    let goal_ids = match goal_id {
+        Some(goal_id) => db.goal_subtree(goal_id),
+        None => db.list_goals().iter().map(|g| g.id().to_owned()).collect(),
+    };
+    goal_ids
+        .iter()
+        .flat_map(|goal_id| db.list_tasks(goal_id))
+        .map(|task| (task.id().to_owned(), task.clone()))
+        .collect()
+}
+
+/// Path, modification time and size of every TOML file a load would read.
+fn fingerprint(radial_dir: &Path) -> Vec<(PathBuf, SystemTime, u64)> {
+    let mut files = Vec::new();
+    let goal_dirs = fs::read_dir(radial_dir).into_iter().flatten().flatten();
+    for goal_dir in goal_dirs.filter(|e| e.path().is_dir()) {
+        for entry in fs::read_dir(goal_dir.path())
+            .into_iter()
+            .flatten()
+            .flatten()
+        {
+            let path = entry.path();
+            if path.extension().is_some_and(|ext| ext == "toml")
+                && let Ok(meta) = entry.metadata()
+            {
+                let modified = meta.modified().unwrap_or(SystemTime::UNIX_EPOCH);
+                files.push((path, modified, meta.len()));
+            }
+        }
+    }
+    files.sort();
+    files
+}
+
+/// The transitions between two snapshots, oldest first. A task that appeared
+/// is reported as created, followed by the state it had already reached.
+pub fn changes(before: &HashMap<String, Task>, after: &HashMap<String, Task>) -> Vec<Change> {
+    let mut changes = Vec::new();
+
+    for task in after.values() {
+        let previous = before.get(task.id());
+        if previous.is_none() {
+            changes.push(Change {
+                timestamp: task.created_at(),
+                ..Change::new(ChangeKind::Created, task, None)
+            });
+        }
+
+        let state = previous.map(Task::state);
+        if state != Some(task.state()) {
+            match task.state() {
+                TaskState::InProgress => {
+                    changes.push(Change::new(ChangeKind::Started, task, task.claimed_by()));
+                }
+                TaskState::Completed => {
+                    let agent = task
+                        .review()
+                        .and_then(|r| r.agent())
+                        .or_else(|| task.result().and_then(|r| r.agent()));
+                    changes.push(Change::new(ChangeKind::Completed, task, agent));
+                }
+                TaskState::Failed => {
+                    changes.push(Change::new(ChangeKind::Failed, task, task.failed_by()));
+                }
+                TaskState::Pending if state == Some(TaskState::Blocked) => {
+                    changes.push(Change::new(ChangeKind::Unblocked, task, None));
+                }
+                _ => {}
+            }
+        }
+
+        let seen: HashSet<&str> = previous
+            .map(|t| t.comments().iter().map(|c| c.id()).collect())
+            .unwrap_or_default();
+        for comment in task.comments().iter().filter(|c| !seen.contains(c.id())) {
+            changes.push(Change {
+                timestamp: comment.created_at(),
+                comment: Some(comment.text().to_owned()),
+                ..Change::new(ChangeKind::Commented, task, comment.agent())
+            });
+        }
+    }
+
+    changes.sort_by_key(|c| c.timestamp);
+    changes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{Comment, Outcome, TaskMetrics};
+    use rstest::rstest;
+
+    fn task(id: &str, state: TaskState) -> Task {
+        let now = Timestamp::now();
+        Task::new(
+            id.to_string(),
+            "g1".to_string(),
+            format!("task {id}"),
+            None,
+            state,
+            Vec::new(),
+            now,
+            now,
+        )
+    }
+
+    fn tasks(list: Vec<Task>) -> HashMap<String, Task> {
+        list.into_iter().map(|t| (t.id().to_owned(), t)).collect()
+    }
+
+    fn kinds(changes: &[Change]) -> Vec<ChangeKind> {
+        changes.iter().map(Change::kind).collect()
+    }
+
+    // -- changes --
+
+    // Each state a task moves into that a supervisor cares about is reported once.
+    #[rstest]
+    #[case::started(TaskState::Pending, TaskState::InProgress, ChangeKind::Started)]
+    #[case::unblocked(TaskState::Blocked, TaskState::Pending, ChangeKind::Unblocked)]
+    #[case::failed(TaskState::InProgress, TaskState::Failed, ChangeKind::Failed)]
+    #[case::completed(TaskState::Verifying, TaskState::Completed, ChangeKind::Completed)]
+    fn changes_report_transitions(
+        #[case] from: TaskState,
+        #[case] to: TaskState,
+        #[case] expected: ChangeKind,
+    ) {
+        let before = tasks(vec![task("t1", from)]);
+        let after = tasks(vec![task("t1", to)]);
+        assert_eq!(kinds(&changes(&before, &after)), vec![expected]);
+    }
+
+    // Unchanged tasks and transitions nobody watches for produce nothing.
+    #[rstest]
+    #[case::unchanged(TaskState::Pending, TaskState::Pending)]
+    #[case::reaped(TaskState::InProgress, TaskState::Pending)]
+    #[case::submitted(TaskState::InProgress, TaskState::Verifying)]
+    fn changes_ignore_other_updates(#[case] from: TaskState, #[case] to: TaskState) {
+        let before = tasks(vec![task("t1", from)]);
+        let after = tasks(vec![task("t1", to)]);
+        assert!(changes(&before, &after).is_empty());
+    }
+
+    // A task that appears already finished is reported as created, then
+    // completed, with the agent that completed it.
+    #[rstest]
+    fn changes_report_new_tasks_and_their_state() {
+        let mut done = task("t1", TaskState::InProgress);
+        done.complete(
+            Outcome::new("done".to_string(), Vec::new()).with_agent(Some("bot".to_string())),
+            TaskMetrics::default(),
+        );
+        let found = changes(&HashMap::new(), &tasks(vec![done]));
+
+        assert_eq!(
+            kinds(&found),
+            vec![ChangeKind::Created, ChangeKind::Completed]
+        );
+        assert_eq!(found[1].agent(), Some("bot"));
+    }
+
+    // Only comments that weren't there before are reported.
+    #[rstest]
+    fn changes_report_new_comments() {
+        let mut old = task("t1", TaskState::Pending);
+        old.add_comment(Comment::new(
+            "c1".to_string(),
+            "first".to_string(),
+            Timestamp::now(),
+        ));
+        let mut new = old.clone();
+        new.add_comment(Comment::new(
+            "c2".to_string(),
+            "second".to_string(),
+            Timestamp::now(),
+        ));
+
+        let found = changes(&tasks(vec![old]), &tasks(vec![new]));
+        assert_eq!(kinds(&found), vec![ChangeKind::Commented]);
+        assert_eq!(found[0].comment(), Some("second"));
+    }
+}
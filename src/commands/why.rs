@@ -0,0 +1,178 @@
+use std::collections::HashSet;
+
+use anyhow::Result;
+use serde::Serialize;
+
+use crate::commands::task::StartPolicy;
+use crate::db::Database;
+use crate::models::{Task, TaskState};
+
+/// An unfinished task somewhere upstream of the task being explained.
+#[derive(Debug, Serialize)]
+pub struct Blocker {
+    task_id: String,
+    /// 1 for a direct blocker, 2 for a blocker of a blocker, and so on.
+    depth: usize,
+    /// `None` when the ID no longer names any task.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    state: Option<TaskState>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    description: Option<String>,
+    /// The agent working on it, while it is in progress.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    claimed_by: Option<String>,
+}
+
+impl Blocker {
+    pub fn task_id(&self) -> &str {
+        &self.task_id
+    }
+
+    pub fn depth(&self) -> usize {
+        self.depth
+    }
+
+    pub fn state(&self) -> Option<TaskState> {
+        self.state
+    }
+
+    pub fn description(&self) -> Option<&str> {
+        self.description.as_deref()
+    }
+
+    pub fn claimed_by(&self) -> Option<&str> {
+        self.claimed_by.as_deref()
+    }
+}
+
+/// Why a task is or isn't offered by `rd ready`.
+#[derive(Debug, Serialize)]
+pub struct Why {
+    task_id: String,
+    state: TaskState,
+    ready: bool,
+    reasons: Vec<String>,
+    blockers: Vec<Blocker>,
+}
+
+impl Why {
+    pub fn task_id(&self) -> &str {
+        &self.task_id
+    }
+
+    pub fn state(&self) -> TaskState {
+        self.state
+    }
+
+    pub fn ready(&self) -> bool {
+        self.ready
+    }
+
+    pub fn reasons(&self) -> &[String] {
+        &self.reasons
+    }
+
+    /// Every unfinished task the task waits on, depth first.
+    pub fn blockers(&self) -> &[Blocker] {
+        &self.blockers
+    }
+}
+
+/// Explain what keeps a task from being ready: its own state, the checks
+/// `rd task start` makes (contract, verify command, retries and budget), and the
+/// chain of unfinished tasks it waits on.
+pub fn run(task_id: &str, db: &Database) -> Result<Why> {
+    let task = db.resolve_task(task_id)?;
+    let task_id = task.id();
+    let policy = StartPolicy::for_goal(task.goal_id(), db);
+    let mut reasons = Vec::new();
+
+    match task.state() {
+        TaskState::Pending | TaskState::Blocked => {}
+        TaskState::InProgress => reasons.push(match (task.claimed_by(), task.lease()) {
+            (Some(agent), Some(lease)) => format!(
+                "Task is in progress, claimed by {agent} until {}",
+                lease.expires_at()
+            ),
+            (Some(agent), None) => format!("Task is in progress, claimed by {agent}"),
+            (None, _) => "Task is already in progress".to_owned(),
+        }),
+        TaskState::Verifying => {
+            reasons.push(format!(
+                "Task is waiting for review.\nUse: rd task verify {task_id} --pass|--fail"
+            ));
+        }
+        TaskState::Completed => reasons.push("Task is already completed".to_owned()),
+        TaskState::Failed => match policy.retry_refusal(task) {
+            Some(refusal) => reasons.push(format!("Task has failed. {refusal}")),
+            None => reasons.push(format!(
+                "Task has failed.\nRetry it with: rd task retry {task_id}"
+            )),
+        },
+    }
+
+    let mut blockers = Vec::new();
+    let mut visited = HashSet::new();
+    walk_blockers(task, 1, db, &mut visited, &mut blockers);
+
+    let direct = blockers.iter().filter(|b| b.depth == 1).count();
+    if direct > 0 {
+        reasons.push(format!("Blocked by {direct} unfinished task(s)"));
+    } else if task.state() == TaskState::Blocked {
+        reasons.push("Task is marked blocked but all blockers are completed".to_owned());
+    }
+    for blocker in &blockers {
+        match blocker.state {
+            None => reasons.push(format!("Blocker {} does not exist", blocker.task_id)),
+            Some(TaskState::Failed) => reasons.push(format!(
+                "Blocker {} has failed.\nRetry it with: rd task retry {}",
+                blocker.task_id, blocker.task_id
+            )),
+            Some(_) => {}
+        }
+    }
+
+    if matches!(task.state(), TaskState::Pending | TaskState::Blocked) {
+        reasons.extend(policy.refusals(task));
+    }
+
+    Ok(Why {
+        task_id: task_id.to_owned(),
+        state: task.state(),
+        ready: reasons.is_empty(),
+        reasons,
+        blockers,
+    })
+}
+
+/// Collect the unfinished blockers of `task`, each followed by its own. A task
+/// reached twice (or through a cycle) is only listed the first time.
+fn walk_blockers<'a>(
+    task: &'a Task,
+    depth: usize,
+    db: &'a Database,
+    visited: &mut HashSet<&'a str>,
+    blockers: &mut Vec<Blocker>,
+) {
+    for blocker_id in task.blocked_by() {
+        let blocker = db.get_task(blocker_id);
+        if blocker.is_some_and(|b| b.state() == TaskState::Completed) || !visited.insert(blocker_id)
+        {
+            continue;
+        }
+
+        blockers.push(Blocker {
+            task_id: blocker_id.clone(),
+            depth,
+            state: blocker.map(Task::state),
+            description: blocker.map(|b| b.description().to_owned()),
+            claimed_by: blocker
+                .filter(|b| b.state() == TaskState::InProgress)
+                .and_then(Task::claimed_by)
+                .map(str::to_owned),
+        });
+        if let Some(blocker) = blocker {
+            walk_blockers(blocker, depth + 1, db, visited, blockers);
+        }
+    }
+}
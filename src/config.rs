@@ -0,0 +1,396 @@
+use std::fs;
+use std::path::Path;
+
+use anyhow::{Context, Result, anyhow};
+use serde::{Deserialize, Serialize};
+
+use crate::db::atomic_write;
+use crate::id::{ALPHABET, IdKind, generate_id};
+
+pub const CONFIG_FILE: &str = "config.toml";
+
+/// Every setting `rd config` can read or change, as dotted keys.
+pub const KEYS: &[&str] = &[
+    "id.prefix",
+    "id.goal_prefix",
+    "id.task_prefix",
+    "id.comment_prefix",
+    "id.length",
+    "id.alphabet",
+    "id.suggest_distance",
+    "tasks.require_contract",
+    "tasks.require_verify_cmd",
+    "tasks.require_review",
+    "tasks.critical_path_boost",
+    "defaults.agent",
+    "defaults.json",
+    "defaults.concise",
+];
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct IdConfig {
+    /// Prepended to every new ID, before the kind prefix.
+    prefix: String,
+    /// Marks goal IDs.
+    goal_prefix: String,
+    /// Marks task IDs.
+    task_prefix: String,
+    /// Marks comment IDs.
+    comment_prefix: String,
+    /// Number of random characters in a new ID.
+    length: usize,
+    /// Characters the random part of a new ID is drawn from.
+    alphabet: String,
+    /// How many edits away an unknown ID may be from a known one for it to be
+    /// suggested. 0 turns suggestions off.
+    suggest_distance: usize,
+}
+
+impl Default for IdConfig {
+    fn default() -> Self {
+        Self {
+            prefix: String::new(),
+            goal_prefix: IdKind::Goal.default_prefix().to_owned(),
+            task_prefix: IdKind::Task.default_prefix().to_owned(),
+            comment_prefix: IdKind::Comment.default_prefix().to_owned(),
+            length: 8,
+            alphabet: ALPHABET.to_owned(),
+            suggest_distance: 2,
+        }
+    }
+}
+
+impl IdConfig {
+    fn kind_prefix(&self, kind: IdKind) -> &str {
+        match kind {
+            IdKind::Goal => &self.goal_prefix,
+            IdKind::Task => &self.task_prefix,
+            IdKind::Comment => &self.comment_prefix,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct TaskConfig {
+    /// Refuse to start a task that has no contract.
+    require_contract: bool,
+    /// Refuse to start a task whose contract has no verify command.
+    require_verify_cmd: bool,
+    /// Refuse `rd task complete`, so every task goes through submit and verify.
+    require_review: bool,
+    /// Among ready tasks of equal priority, offer those that unblock the most
+    /// downstream work first.
+    critical_path_boost: bool,
+}
+
+impl Default for TaskConfig {
+    fn default() -> Self {
+        Self {
+            require_contract: true,
+            require_verify_cmd: false,
+            require_review: false,
+            critical_path_boost: false,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct DefaultsConfig {
+    /// Agent identity used when neither `--agent` nor `RD_AGENT` is given.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    agent: Option<String>,
+    /// Print JSON output unless a command is human-only.
+    json: bool,
+    /// Hide comments and command output in `rd status`.
+    concise: bool,
+}
+
+/// Project settings from `.radial/config.toml`. Missing settings use defaults.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct Config {
+    id: IdConfig,
+    tasks: TaskConfig,
+    defaults: DefaultsConfig,
+}
+
+impl Config {
+    /// Read the config from a `.radial/` directory. A missing file gives the
+    /// defaults.
+    pub fn load(dir: &Path) -> Result<Self> {
+        let path = dir.join(CONFIG_FILE);
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+
+        let content = fs::read_to_string(&path)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        let config: Self = toml::from_str(&content)
+            .with_context(|| format!("Failed to parse {}", path.display()))?;
+        config.validate()?;
+        Ok(config)
+    }
+
+    pub fn write_file(&self, dir: &Path) -> Result<()> {
+        let content = toml::to_string(self).context("Failed to serialize config")?;
+        atomic_write(&dir.join(CONFIG_FILE), content.as_bytes())
+    }
+
+    fn validate(&self) -> Result<()> {
+        let kinds = [IdKind::Goal, IdKind::Task, IdKind::Comment];
+
+        // IDs name files under .radial/, so no part of one may carry a path
+        // separator.
+        let prefixes = std::iter::once(("id.prefix".to_owned(), self.id.prefix.as_str())).chain(
+            kinds.map(|kind| {
+                (
+                    format!("id.{}_prefix", kind.noun()),
+                    self.id.kind_prefix(kind),
+                )
+            }),
+        );
+        for (key, prefix) in prefixes {
+            if !prefix.chars().all(is_id_char) {
+                return Err(anyhow!(
+                    "{key} may only contain letters, digits, '_' and '-'"
+                ));
+            }
+        }
+
+        // An ID that starts with a dash would be parsed as a flag.
+        if self.id.prefix.starts_with('-') {
+            return Err(anyhow!("id.prefix must not start with '-'"));
+        }
+        for kind in kinds {
+            if self.id.prefix.is_empty() && self.id.kind_prefix(kind).starts_with('-') {
+                return Err(anyhow!("id.{}_prefix must not start with '-'", kind.noun()));
+            }
+        }
+        if !(4..=32).contains(&self.id.length) {
+            return Err(anyhow!("id.length must be between 4 and 32"));
+        }
+        let mut alphabet: Vec<char> = self.id.alphabet.chars().collect();
+        if !alphabet.iter().all(char::is_ascii_alphanumeric) {
+            return Err(anyhow!("id.alphabet may only contain letters and digits"));
+        }
+        alphabet.sort_unstable();
+        alphabet.dedup();
+        if alphabet.len() < 2 {
+            return Err(anyhow!(
+                "id.alphabet must have at least 2 different characters"
+            ));
+        }
+        Ok(())
+    }
+
+    /// The full prefix of new IDs of the given kind.
+    pub fn id_prefix(&self, kind: IdKind) -> String {
+        format!("{}{}", self.id.prefix, self.id.kind_prefix(kind))
+    }
+
+    /// Generate a new ID of the given kind using the configured prefixes and length.
+    pub fn new_id(&self, kind: IdKind) -> String {
+        generate_id(&self.id_prefix(kind), self.id.length, &self.id.alphabet)
+    }
+
+    /// The largest edit distance at which an unknown ID gets a "Did you mean"
+    /// suggestion.
+    pub fn suggest_distance(&self) -> usize {
+        self.id.suggest_distance
+    }
+
+    pub fn require_contract(&self) -> bool {
+        self.tasks.require_contract
+    }
+
+    pub fn require_verify_cmd(&self) -> bool {
+        self.tasks.require_verify_cmd
+    }
+
+    pub fn require_review(&self) -> bool {
+        self.tasks.require_review
+    }
+
+    pub fn critical_path_boost(&self) -> bool {
+        self.tasks.critical_path_boost
+    }
+
+    pub fn default_agent(&self) -> Option<&str> {
+        self.defaults.agent.as_deref()
+    }
+
+    pub fn default_json(&self) -> bool {
+        self.defaults.json
+    }
+
+    pub fn default_concise(&self) -> bool {
+        self.defaults.concise
+    }
+
+    /// The value of a setting, as `rd config get` prints it. Unset optional
+    /// settings are empty.
+    pub fn get(&self, key: &str) -> Result<String> {
+        Ok(match key {
+            "id.prefix" => self.id.prefix.clone(),
+            "id.goal_prefix" => self.id.goal_prefix.clone(),
+            "id.task_prefix" => self.id.task_prefix.clone(),
+            "id.comment_prefix" => self.id.comment_prefix.clone(),
+            "id.length" => self.id.length.to_string(),
+            "id.alphabet" => self.id.alphabet.clone(),
+            "id.suggest_distance" => self.id.suggest_distance.to_string(),
+            "tasks.require_contract" => self.tasks.require_contract.to_string(),
+            "tasks.require_verify_cmd" => self.tasks.require_verify_cmd.to_string(),
+            "tasks.require_review" => self.tasks.require_review.to_string(),
+            "tasks.critical_path_boost" => self.tasks.critical_path_boost.to_string(),
+            "defaults.agent" => self.defaults.agent.clone().unwrap_or_default(),
+            "defaults.json" => self.defaults.json.to_string(),
+            "defaults.concise" => self.defaults.concise.to_string(),
+            _ => return Err(unknown_key_err(key)),
+        })
+    }
+
+    /// Change a setting from its string form. An empty value unsets an optional
+    /// setting.
+    pub fn set(&mut self, key: &str, value: &str) -> Result<()> {
+        match key {
+            "id.prefix" => self.id.prefix = value.to_owned(),
+            "id.goal_prefix" => self.id.goal_prefix = value.to_owned(),
+            "id.task_prefix" => self.id.task_prefix = value.to_owned(),
+            "id.comment_prefix" => self.id.comment_prefix = value.to_owned(),
+            "id.length" => self.id.length = parse(key, value)?,
+            "id.alphabet" => self.id.alphabet = value.to_owned(),
+            "id.suggest_distance" => self.id.suggest_distance = parse(key, value)?,
+            "tasks.require_contract" => self.tasks.require_contract = parse(key, value)?,
+            "tasks.require_verify_cmd" => self.tasks.require_verify_cmd = parse(key, value)?,
+            "tasks.require_review" => self.tasks.require_review = parse(key, value)?,
+            "tasks.critical_path_boost" => self.tasks.critical_path_boost = parse(key, value)?,
+            "defaults.agent" => {
+                self.defaults.agent = (!value.is_empty()).then(|| value.to_owned());
+            }
+            "defaults.json" => self.defaults.json = parse(key, value)?,
+            "defaults.concise" => self.defaults.concise = parse(key, value)?,
+            _ => return Err(unknown_key_err(key)),
+        }
+        self.validate()
+    }
+
+    /// Every setting and its current value, in `KEYS` order.
+    pub fn entries(&self) -> Vec<(&'static str, String)> {
+        KEYS.iter()
+            .map(|key| (*key, self.get(key).unwrap_or_default()))
+            .collect()
+    }
+}
+
+fn is_id_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || c == '_' || c == '-'
+}
+
+fn parse<T: std::str::FromStr>(key: &str, value: &str) -> Result<T> {
+    value
+        .parse()
+        .map_err(|_| anyhow!("Invalid value for {key}: {value}"))
+}
+
+fn unknown_key_err(key: &str) -> anyhow::Error {
+    anyhow!("Unknown config key: {key}\nValid keys: {}", KEYS.join(", "))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+    use tempfile::TempDir;
+
+    // -- load / write --
+
+    // A missing file yields the defaults, and a written config reads back
+    // unchanged.
+    #[rstest]
+    fn load_round_trips() {
+        let dir = TempDir::new().unwrap();
+        assert_eq!(Config::load(dir.path()).unwrap(), Config::default());
+
+        let mut config = Config::default();
+        config.set("id.prefix", "x").unwrap();
+        config.set("defaults.agent", "builder").unwrap();
+        config.write_file(dir.path()).unwrap();
+
+        assert_eq!(Config::load(dir.path()).unwrap(), config);
+    }
+
+    // A partial file only overrides the settings it names.
+    #[rstest]
+    fn load_partial_file_keeps_defaults() {
+        let dir = TempDir::new().unwrap();
+        fs::write(dir.path().join(CONFIG_FILE), "[id]\nlength = 12\n").unwrap();
+
+        let config = Config::load(dir.path()).unwrap();
+        assert_eq!(config.get("id.length").unwrap(), "12");
+        assert!(config.require_contract());
+    }
+
+    // -- get / set --
+
+    // set should reject unknown keys and values of the wrong type or out of
+    // range.
+    #[rstest]
+    #[case::unknown_key("id.charset", "abc")]
+    #[case::not_a_bool("tasks.require_contract", "maybe")]
+    #[case::not_a_number("id.length", "long")]
+    #[case::too_short("id.length", "2")]
+    #[case::leading_dash("id.prefix", "-x")]
+    #[case::leading_dash_kind("id.task_prefix", "-t")]
+    #[case::path_in_prefix("id.prefix", "../x")]
+    #[case::space_in_prefix("id.prefix", "a b")]
+    #[case::path_in_kind("id.goal_prefix", "../g-")]
+    #[case::slash_in_kind("id.task_prefix", "t/")]
+    #[case::alphabet_too_small("id.alphabet", "aaaa")]
+    #[case::alphabet_symbols("id.alphabet", "ab/")]
+    #[case::negative_distance("id.suggest_distance", "-1")]
+    fn set_rejects_invalid(#[case] key: &str, #[case] value: &str) {
+        let mut config = Config::default();
+        assert!(config.set(key, value).is_err());
+    }
+
+    // list shows every known key with its current value.
+    #[rstest]
+    fn entries_cover_every_key() {
+        let config = Config::default();
+        let entries = config.entries();
+        assert_eq!(entries.len(), KEYS.len());
+        assert!(entries.contains(&("tasks.require_contract", "true".to_string())));
+    }
+
+    // -- new_id --
+
+    // New IDs carry the project prefix, then the prefix for their kind,
+    // followed by the configured number of random characters.
+    #[rstest]
+    #[case::goal(IdKind::Goal, "rdg-")]
+    #[case::task(IdKind::Task, "rdt-")]
+    #[case::comment(IdKind::Comment, "rdc-")]
+    fn new_id_uses_prefixes_and_length(#[case] kind: IdKind, #[case] prefix: &str) {
+        let mut config = Config::default();
+        config.set("id.prefix", "rd").unwrap();
+        config.set("id.length", "10").unwrap();
+
+        let id = config.new_id(kind);
+        assert!(id.starts_with(prefix));
+        assert_eq!(id.len(), prefix.len() + 10);
+    }
+
+    // The random part of a new ID only uses the configured alphabet.
+    #[rstest]
+    fn new_id_uses_alphabet() {
+        let mut config = Config::default();
+        config.set("id.alphabet", "01").unwrap();
+
+        let id = config.new_id(IdKind::Task);
+        let random = id.strip_prefix("t-").unwrap();
+        assert!(random.chars().all(|c| c == '0' || c == '1'));
+    }
+}
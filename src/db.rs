@@ -1,12 +1,60 @@
+use std::cell::OnceCell;
 use std::collections::HashMap;
-use std::fs::{self, File};
-use std::io::Write;
+use std::fs::{self, File, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
 use std::path::{Path, PathBuf};
 
 use anyhow::{Context, Result, bail};
 use fs2::FileExt;
 
-use crate::models::{Goal, Metrics, Task, TaskState};
+use crate::config::Config;
+use crate::helpers::{find_similar_id, wrong_kind_err};
+use crate::id::{IdKind, MIN_PREFIX_LEN};
+use crate::models::{Event, Goal, Metrics, Task, TaskState};
+use crate::pricing::Pricing;
+
+pub const EVENTS_FILE: &str = "events.jsonl";
+
+/// The error returned when an ID names no goal or task.
+#[derive(Debug)]
+pub struct NotFound {
+    pub kind: IdKind,
+    pub id: String,
+    /// A similar ID that does exist.
+    pub suggestion: Option<String>,
+    /// Set when `id` was too short to be matched as a prefix.
+    pub too_short: bool,
+}
+
+impl NotFound {
+    fn new(kind: IdKind, id: &str) -> Self {
+        Self {
+            kind,
+            id: id.to_owned(),
+            suggestion: None,
+            too_short: false,
+        }
+    }
+}
+
+impl std::fmt::Display for NotFound {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} not found: {}", self.kind.label(), self.id)?;
+        if self.too_short {
+            write!(
+                f,
+                "\nA partial ID needs at least {MIN_PREFIX_LEN} characters after the {} prefix",
+                self.kind.noun()
+            )?;
+        }
+        if let Some(suggestion) = &self.suggestion {
+            write!(f, "\nDid you mean: {suggestion}")?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for NotFound {}
 
 /// Atomically write content to a file using a temporary file + rename.
 pub fn atomic_write(path: &Path, content: &[u8]) -> Result<()> {
@@ -27,6 +75,8 @@ pub struct Database {
     path: PathBuf,
     goals: HashMap<String, Goal>,
     tasks: HashMap<String, Task>,
+    config: Config,
+    pricing: OnceCell<Pricing>,
 }
 
 impl Database {
@@ -38,10 +88,13 @@ impl Database {
             bail!("Database directory does not exist: {}", path.display());
         }
 
+        let config = Config::load(&path)?;
         let mut db = Self {
             path,
             goals: HashMap::new(),
             tasks: HashMap::new(),
+            config,
+            pricing: OnceCell::new(),
         };
 
         db.load()?;
@@ -58,6 +111,42 @@ impl Database {
         &self.path
     }
 
+    /// Project settings from `.radial/config.toml`.
+    pub fn config(&self) -> &Config {
+        &self.config
+    }
+
+    pub fn set_config(&mut self, config: Config) {
+        self.config = config;
+    }
+
+    /// The local model price table, read the first time it is needed. It is
+    /// empty if `.radial/pricing.toml` is absent; a file that can't be read is
+    /// reported on stderr and treated as empty, so a typo there only costs the
+    /// estimates rather than every command.
+    pub fn pricing(&self) -> &Pricing {
+        self.pricing.get_or_init(|| {
+            Pricing::load(&self.path).unwrap_or_else(|err| {
+                eprintln!("Warning: {err:#}; costs will not be estimated");
+                Pricing::default()
+            })
+        })
+    }
+
+    /// Read a single goal file from disk.
+    fn read_goal(path: &Path) -> Result<Goal> {
+        let content = fs::read_to_string(path)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        toml::from_str(&content).with_context(|| format!("Failed to parse {}", path.display()))
+    }
+
+    /// Read a single task file from disk.
+    fn read_task(path: &Path) -> Result<Task> {
+        let content = fs::read_to_string(path)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        toml::from_str(&content).with_context(|| format!("Failed to parse {}", path.display()))
+    }
+
     /// Load all data from the per-entity TOML files into memory.
     fn load(&mut self) -> Result<()> {
         let dir = fs::read_dir(&self.path).context("Failed to read .radial directory")?;
@@ -75,39 +164,41 @@ impl Database {
                 continue;
             }
 
-            let goal_content = fs::read_to_string(&goal_toml_path)
-                .with_context(|| format!("Failed to read {}", goal_toml_path.display()))?;
-            let goal: Goal = toml::from_str(&goal_content)
-                .with_context(|| format!("Failed to parse {}", goal_toml_path.display()))?;
+            let goal = Self::read_goal(&goal_toml_path)?;
 
             let goal_id = goal.id().to_owned();
             self.goals.insert(goal_id, goal);
 
-            let task_dir = fs::read_dir(&path)
-                .with_context(|| format!("Failed to read goal directory: {}", path.display()))?;
+            for task in Self::read_goal_tasks(&path)? {
+                self.tasks.insert(task.id().to_owned(), task);
+            }
+        }
 
-            for task_entry in task_dir {
-                let task_entry = task_entry.context("Failed to read task entry")?;
-                let task_path = task_entry.path();
+        Ok(())
+    }
 
-                if task_path.file_name() == Some(std::ffi::OsStr::new("goal.toml")) {
-                    continue;
-                }
+    /// Read every task file in a goal's directory.
+    fn read_goal_tasks(goal_dir: &Path) -> Result<Vec<Task>> {
+        let task_dir = fs::read_dir(goal_dir)
+            .with_context(|| format!("Failed to read goal directory: {}", goal_dir.display()))?;
 
-                if task_path.extension() != Some(std::ffi::OsStr::new("toml")) {
-                    continue;
-                }
+        let mut tasks = Vec::new();
+        for task_entry in task_dir {
+            let task_entry = task_entry.context("Failed to read task entry")?;
+            let task_path = task_entry.path();
 
-                let task_content = fs::read_to_string(&task_path)
-                    .with_context(|| format!("Failed to read {}", task_path.display()))?;
-                let task: Task = toml::from_str(&task_content)
-                    .with_context(|| format!("Failed to parse {}", task_path.display()))?;
+            if task_path.file_name() == Some(std::ffi::OsStr::new("goal.toml")) {
+                continue;
+            }
 
-                self.tasks.insert(task.id().to_owned(), task);
+            if task_path.extension() != Some(std::ffi::OsStr::new("toml")) {
+                continue;
             }
+
+            tasks.push(Self::read_task(&task_path)?);
         }
 
-        Ok(())
+        Ok(tasks)
     }
 
     // Goal operations
@@ -121,6 +212,7 @@ impl Database {
         fs::create_dir_all(&goal_dir).context("Failed to create goal directory")?;
 
         goal.write_file(&self.path)?;
+        self.append_event(&Event::goal_created(&goal))?;
         self.goals.insert(goal.id().to_owned(), goal);
 
         Ok(())
@@ -134,12 +226,93 @@ impl Database {
         self.goals.get_mut(id)
     }
 
+    /// Apply `f` to a goal while holding an exclusive cross-process lock on it,
+    /// journaling any state change.
+    ///
+    /// As with `update_task`, the goal is re-read from disk once the lock is held and
+    /// only written back if `f` changed it, so concurrent writers don't overwrite each
+    /// other's state, budget or metrics.
+    pub fn update_goal<T>(&mut self, id: &str, f: impl FnOnce(&mut Goal) -> T) -> Result<T> {
+        let lock = self.lock_goal(id)?;
+        let result = self.update_goal_locked(id, f);
+        lock.unlock().context("Failed to release goal lock")?;
+        result
+    }
+
+    /// Take the exclusive cross-process lock on a goal. It is held until the
+    /// returned file is unlocked or dropped.
+    fn lock_goal(&self, id: &str) -> Result<File> {
+        let path = self
+            .goals
+            .get(id)
+            .ok_or_else(|| NotFound::new(IdKind::Goal, id))?
+            .file_path(&self.path);
+
+        let lock_path = path.with_extension("lock");
+        let lock = File::create(&lock_path)
+            .with_context(|| format!("Failed to create lock file: {}", lock_path.display()))?;
+        lock.lock_exclusive()
+            .context("Failed to acquire goal lock")?;
+        Ok(lock)
+    }
+
+    /// The body of `update_goal`, for callers that already hold the goal's lock.
+    fn update_goal_locked<T>(&mut self, id: &str, f: impl FnOnce(&mut Goal) -> T) -> Result<T> {
+        let path = self
+            .goals
+            .get(id)
+            .ok_or_else(|| NotFound::new(IdKind::Goal, id))?
+            .file_path(&self.path);
+
+        let fresh = Self::read_goal(&path)?;
+        let mut goal = fresh.clone();
+        let result = f(&mut goal);
+
+        // Every Goal mutator bumps updated_at, so an unchanged timestamp means
+        // there is nothing to write.
+        if goal.updated_at() != fresh.updated_at() {
+            goal.write_file(&self.path)?;
+            if let Some(event) = Event::goal_changed(&fresh, &goal) {
+                self.append_event(&event)?;
+            }
+        }
+        self.goals.insert(id.to_owned(), goal);
+
+        Ok(result)
+    }
+
     pub fn list_goals(&self) -> Vec<&Goal> {
         let mut goals: Vec<&Goal> = self.goals.values().collect();
         goals.sort_by_key(|g| std::cmp::Reverse(g.created_at()));
         goals
     }
 
+    /// Direct sub-goals of a goal, newest first.
+    pub fn list_child_goals(&self, parent_id: &str) -> Vec<&Goal> {
+        let mut goals: Vec<&Goal> = self
+            .goals
+            .values()
+            .filter(|g| g.parent_id() == Some(parent_id))
+            .collect();
+        goals.sort_by_key(|g| std::cmp::Reverse(g.created_at()));
+        goals
+    }
+
+    /// IDs of a goal and all of its sub-goals, parents before children.
+    pub fn goal_subtree(&self, goal_id: &str) -> Vec<String> {
+        let mut ids = vec![goal_id.to_owned()];
+        let mut i = 0;
+        while i < ids.len() {
+            for child in self.list_child_goals(&ids[i]) {
+                if !ids.iter().any(|id| id == child.id()) {
+                    ids.push(child.id().to_owned());
+                }
+            }
+            i += 1;
+        }
+        ids
+    }
+
     // Task operations
 
     pub fn create_task(&mut self, task: Task) -> Result<()> {
@@ -148,7 +321,10 @@ impl Database {
         }
 
         task.write_file(&self.path)?;
+        self.append_event(&Event::task_created(&task))?;
+        let goal_id = task.goal_id().to_owned();
         self.tasks.insert(task.id().to_owned(), task);
+        self.refresh_goal_metrics(&goal_id)?;
 
         Ok(())
     }
@@ -161,6 +337,51 @@ impl Database {
         self.tasks.get_mut(id)
     }
 
+    /// Apply `f` to a task while holding an exclusive cross-process lock on it.
+    ///
+    /// The task is re-read from disk once the lock is held, so `f` always sees the latest
+    /// committed state even if another process wrote it after this database was loaded.
+    /// If `f` succeeds and changed the task, it is written back and journaled before the
+    /// lock is released; otherwise nothing is written and the in-memory copy is refreshed
+    /// from disk.
+    pub fn update_task<T>(
+        &mut self,
+        id: &str,
+        f: impl FnOnce(&mut Task) -> Result<T>,
+    ) -> Result<T> {
+        let path = self
+            .tasks
+            .get(id)
+            .ok_or_else(|| NotFound::new(IdKind::Task, id))?
+            .file_path(&self.path);
+
+        let lock_path = path.with_extension("lock");
+        let lock = File::create(&lock_path)
+            .with_context(|| format!("Failed to create lock file: {}", lock_path.display()))?;
+        lock.lock_exclusive()
+            .context("Failed to acquire task lock")?;
+
+        let fresh = Self::read_task(&path)?;
+        let mut task = fresh.clone();
+        let result = f(&mut task);
+
+        // Every Task mutator bumps updated_at, so an unchanged timestamp means
+        // there is nothing to write.
+        if result.is_ok() && task.updated_at() != fresh.updated_at() {
+            task.write_file(&self.path)?;
+            self.append_event(&Event::task_changed(&fresh, &task))?;
+            let goal_id = task.goal_id().to_owned();
+            self.tasks.insert(id.to_owned(), task);
+            lock.unlock().context("Failed to release task lock")?;
+            self.refresh_goal_metrics(&goal_id)?;
+        } else {
+            self.tasks.insert(id.to_owned(), fresh);
+            lock.unlock().context("Failed to release task lock")?;
+        }
+
+        result
+    }
+
     pub fn list_tasks(&self, goal_id: &str) -> Vec<&Task> {
         let mut tasks: Vec<&Task> = self
             .tasks
@@ -171,11 +392,200 @@ impl Database {
         tasks
     }
 
+    /// Resolve a full ID or any unambiguous prefix of one to the full ID of a goal,
+    /// task or comment.
+    ///
+    /// A prefix may leave out the configured prefix for its kind, so `t-4fz` and
+    /// `4fz` both find `t-4fzQ81a`, but needs `MIN_PREFIX_LEN` characters after
+    /// it. An ambiguous prefix lists every candidate.
+    pub fn resolve_id(&self, input: &str, kind: IdKind) -> Result<String> {
+        let ids: Vec<&str> = match kind {
+            IdKind::Goal => self.goals.keys().map(String::as_str).collect(),
+            IdKind::Task => self.tasks.keys().map(String::as_str).collect(),
+            IdKind::Comment => self
+                .tasks
+                .values()
+                .flat_map(|t| t.comments().iter().map(|c| c.id()))
+                .collect(),
+        };
+
+        if ids.contains(&input) {
+            return Ok(input.to_owned());
+        }
+
+        let kind_prefix = self.config.id_prefix(kind);
+        let too_short = input
+            .strip_prefix(kind_prefix.as_str())
+            .unwrap_or(input)
+            .len()
+            < MIN_PREFIX_LEN;
+        if !too_short {
+            let mut matches: Vec<&str> = ids
+                .iter()
+                .copied()
+                .filter(|id| {
+                    id.starts_with(input)
+                        || id
+                            .strip_prefix(kind_prefix.as_str())
+                            .is_some_and(|rest| rest.starts_with(input))
+                })
+                .collect();
+            matches.sort_unstable();
+
+            match matches.as_slice() {
+                [id] => return Ok((*id).to_owned()),
+                [] => {}
+                _ => bail!(
+                    "Ambiguous {} ID: {input} matches {}\n  {}",
+                    kind.noun(),
+                    matches.len(),
+                    matches.join("\n  ")
+                ),
+            }
+        }
+
+        if let Some(err) = wrong_kind_err(input, kind, self) {
+            return Err(err);
+        }
+        Err(NotFound {
+            suggestion: find_similar_id(input, &ids, self.config.suggest_distance())
+                .map(str::to_owned),
+            too_short,
+            ..NotFound::new(kind, input)
+        }
+        .into())
+    }
+
+    /// Look up a goal by its full ID or an unambiguous prefix.
+    pub fn resolve_goal(&self, input: &str) -> Result<&Goal> {
+        let id = self.resolve_id(input, IdKind::Goal)?;
+        Ok(&self.goals[&id])
+    }
+
+    /// Look up a task by its full ID or an unambiguous prefix.
+    pub fn resolve_task(&self, input: &str) -> Result<&Task> {
+        let id = self.resolve_id(input, IdKind::Task)?;
+        Ok(&self.tasks[&id])
+    }
+
+    /// What kind of entity `id` names, if it names anything.
+    pub fn id_kind(&self, id: &str) -> Option<IdKind> {
+        if self.goals.contains_key(id) {
+            Some(IdKind::Goal)
+        } else if self.tasks.contains_key(id) {
+            Some(IdKind::Task)
+        } else if self
+            .tasks
+            .values()
+            .any(|t| t.comments().iter().any(|c| c.id() == id))
+        {
+            Some(IdKind::Comment)
+        } else {
+            None
+        }
+    }
+
+    // Event journal
+
+    fn events_path(&self, goal_id: &str) -> PathBuf {
+        self.path.join(goal_id).join(EVENTS_FILE)
+    }
+
+    /// Append an event to its goal's journal as one JSON line.
+    fn append_event(&self, event: &Event) -> Result<()> {
+        let path = self.events_path(event.goal_id());
+        let mut line = serde_json::to_vec(event).context("Failed to serialize event")?;
+        line.push(b'\n');
+
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&path)
+            .with_context(|| format!("Failed to open {}", path.display()))?;
+        file.lock_exclusive()
+            .context("Failed to acquire journal lock")?;
+        file.write_all(&line)
+            .context("Failed to append to journal")?;
+        file.unlock().context("Failed to release journal lock")?;
+        Ok(())
+    }
+
+    /// Read a goal's journal in the order it was written. A goal without a
+    /// journal yields no events.
+    pub fn list_events(&self, goal_id: &str) -> Result<Vec<Event>> {
+        let path = self.events_path(goal_id);
+        if !path.exists() {
+            return Ok(Vec::new());
+        }
+
+        let file =
+            File::open(&path).with_context(|| format!("Failed to open {}", path.display()))?;
+        BufReader::new(file)
+            .lines()
+            .enumerate()
+            .filter(|(_, line)| !line.as_ref().is_ok_and(|l| l.trim().is_empty()))
+            .map(|(n, line)| {
+                let line = line.with_context(|| format!("Failed to read {}", path.display()))?;
+                serde_json::from_str(&line)
+                    .with_context(|| format!("Failed to parse {} line {}", path.display(), n + 1))
+            })
+            .collect()
+    }
+
+    /// Recompute the stored metrics of a goal and each of its ancestors, writing
+    /// back any that changed.
+    ///
+    /// Each goal is locked and its subtree's task files are re-read before its
+    /// metrics are computed, and only the metrics are written. Every task write is
+    /// followed by a refresh, so whichever process refreshes last has seen all the
+    /// writes before it.
+    fn refresh_goal_metrics(&mut self, goal_id: &str) -> Result<()> {
+        let mut next = Some(goal_id.to_owned());
+        while let Some(gid) = next {
+            if !self.goals.contains_key(&gid) {
+                break;
+            }
+
+            let lock = self.lock_goal(&gid)?;
+            let result = self.reload_tasks(&gid).and_then(|()| {
+                let metrics = self.compute_goal_metrics(&gid);
+                self.update_goal_locked(&gid, |goal| {
+                    goal.set_metrics(metrics);
+                    goal.parent_id().map(str::to_owned)
+                })
+            });
+            lock.unlock().context("Failed to release goal lock")?;
+            next = result?;
+        }
+        Ok(())
+    }
+
+    /// Re-read the task files of a goal and all its sub-goals from disk.
+    fn reload_tasks(&mut self, goal_id: &str) -> Result<()> {
+        for gid in self.goal_subtree(goal_id) {
+            for task in Self::read_goal_tasks(&self.path.join(&gid))? {
+                self.tasks.insert(task.id().to_owned(), task);
+            }
+        }
+        Ok(())
+    }
+
+    /// Aggregate metrics over a goal's tasks and those of all its sub-goals.
     #[allow(clippy::missing_panics_doc)]
     pub fn compute_goal_metrics(&self, goal_id: &str) -> Metrics {
-        let tasks = self.list_tasks(goal_id);
+        let tasks: Vec<&Task> = self
+            .goal_subtree(goal_id)
+            .iter()
+            .flat_map(|gid| self.list_tasks(gid))
+            .collect();
 
         let total_tokens: i64 = tasks.iter().map(|t| t.metrics().tokens()).sum();
+        let prompt_tokens: i64 = tasks.iter().map(|t| t.metrics().prompt_tokens()).sum();
+        let completion_tokens: i64 = tasks.iter().map(|t| t.metrics().completion_tokens()).sum();
+        let estimated_cost = tasks
+            .iter()
+            .filter_map(|t| t.metrics().estimated_cost())
+            .reduce(|a, b| a + b);
         let elapsed_ms: i64 = tasks.iter().map(|t| t.metrics().elapsed_ms()).sum();
         let task_count = i64::try_from(tasks.len()).unwrap_or(0);
         let tasks_completed = i64::try_from(
@@ -195,20 +605,21 @@ impl Database {
 
         Metrics::new(
             total_tokens,
-            0,
-            0,
+            prompt_tokens,
+            completion_tokens,
             elapsed_ms,
             task_count,
             tasks_completed,
             tasks_failed,
         )
+        .with_estimated_cost(estimated_cost)
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::models::{GoalState, Metrics, TaskMetrics};
+    use crate::models::{Budget, Comment, EventAction, GoalState, Metrics, Outcome, TaskMetrics};
     use jiff::Timestamp;
     use rstest::{fixture, rstest};
     use tempfile::TempDir;
@@ -227,6 +638,20 @@ mod tests {
         )
     }
 
+    fn make_sub_goal(id: &str, parent_id: &str) -> Goal {
+        let now = Timestamp::now();
+        Goal::new(
+            id.to_string(),
+            Some(parent_id.to_string()),
+            "test sub-goal".to_string(),
+            GoalState::Pending,
+            now,
+            now,
+            None,
+            Metrics::default(),
+        )
+    }
+
     fn make_task(id: &str, goal_id: &str, state: TaskState) -> Task {
         let now = Timestamp::now();
         Task::new(
@@ -249,6 +674,8 @@ mod tests {
             path: dir.path().to_path_buf(),
             goals: HashMap::new(),
             tasks: HashMap::new(),
+            config: Config::default(),
+            pricing: OnceCell::new(),
         };
         (dir, db)
     }
@@ -261,6 +688,8 @@ mod tests {
             path: dir.path().to_path_buf(),
             goals: HashMap::new(),
             tasks: HashMap::new(),
+            config: Config::default(),
+            pricing: OnceCell::new(),
         };
         db.create_goal(make_goal("g1")).unwrap();
         db.create_task(make_task("t1", "g1", TaskState::Pending))
@@ -432,6 +861,129 @@ mod tests {
         assert_eq!(db.get_task("t1").unwrap().state(), TaskState::InProgress);
     }
 
+    // -- update_task --
+
+    // update_task re-reads the task from disk under the lock, so a change
+    // written by another Database instance is visible to the closure even
+    // though this instance loaded a stale copy.
+    #[rstest]
+    fn update_task_sees_changes_from_other_instances(db_with_goal_and_task: (TempDir, Database)) {
+        let (dir, mut first) = db_with_goal_and_task;
+        let mut second = Database::open(dir.path()).unwrap();
+
+        first
+            .update_task("t1", |t| {
+                assert!(t.transition(TaskState::Pending, TaskState::InProgress));
+                Ok(())
+            })
+            .unwrap();
+
+        let claimed = second
+            .update_task("t1", |t| {
+                Ok(t.transition(TaskState::Pending, TaskState::InProgress))
+            })
+            .unwrap();
+        assert!(!claimed);
+        assert_eq!(
+            second.get_task("t1").unwrap().state(),
+            TaskState::InProgress
+        );
+    }
+
+    // When the closure fails nothing is written, but the in-memory copy is
+    // still refreshed to the latest on-disk state.
+    #[rstest]
+    fn update_task_error_does_not_write(db_with_goal_and_task: (TempDir, Database)) {
+        let (dir, mut db) = db_with_goal_and_task;
+        let result: Result<()> = db.update_task("t1", |t| {
+            t.transition(TaskState::Pending, TaskState::InProgress);
+            bail!("rejected")
+        });
+        assert!(result.is_err());
+        assert_eq!(db.get_task("t1").unwrap().state(), TaskState::Pending);
+
+        let reloaded = Database::open(dir.path()).unwrap();
+        assert_eq!(reloaded.get_task("t1").unwrap().state(), TaskState::Pending);
+        assert!(db.update_task("nonexistent", |_| Ok(())).is_err());
+    }
+
+    // -- update_goal --
+
+    // update_goal re-reads the goal under its lock, so a write from an instance
+    // holding a stale copy keeps what another instance stored in between.
+    #[rstest]
+    fn update_goal_sees_changes_from_other_instances(db_with_goal_and_task: (TempDir, Database)) {
+        let (dir, mut first) = db_with_goal_and_task;
+        let mut second = Database::open(dir.path()).unwrap();
+
+        first
+            .update_goal("g1", |g| {
+                g.update_budget(&Budget::new(Some(100), None, None));
+            })
+            .unwrap();
+        second.update_goal("g1", Goal::mark_in_progress).unwrap();
+
+        let reloaded = Database::open(dir.path()).unwrap();
+        let goal = reloaded.get_goal("g1").unwrap();
+        assert_eq!(goal.state(), GoalState::InProgress);
+        assert_eq!(goal.budget().and_then(Budget::max_tokens), Some(100));
+        assert!(second.update_goal("nonexistent", Goal::touch).is_err());
+    }
+
+    // -- event journal --
+
+    // Creating entities and changing a task's state each append one event
+    // to the goal's journal, in order, with the previous and next state.
+    #[rstest]
+    fn mutations_are_journaled(db_with_goal_and_task: (TempDir, Database)) {
+        let (_dir, mut db) = db_with_goal_and_task;
+        db.update_task("t1", |t| {
+            t.transition(TaskState::Pending, TaskState::InProgress);
+            Ok(())
+        })
+        .unwrap();
+
+        let events = db.list_events("g1").unwrap();
+        assert_eq!(events.len(), 3);
+        assert_eq!(events[0].action(), EventAction::Created);
+        assert_eq!(events[0].task_id(), None);
+        assert_eq!(events[1].action(), EventAction::Created);
+        assert_eq!(events[1].task_id(), Some("t1"));
+        assert_eq!(events[2].action(), EventAction::Transition);
+        assert_eq!(events[2].from(), Some("pending"));
+        assert_eq!(events[2].to(), "in_progress");
+    }
+
+    // An update that leaves the task untouched writes nothing, and goal
+    // writes that only touch it or refresh its metrics are not journaled.
+    // Budget changes and state changes are.
+    #[rstest]
+    fn unchanged_writes_are_not_journaled(db_with_goal_and_task: (TempDir, Database)) {
+        let (_dir, mut db) = db_with_goal_and_task;
+        db.update_task("t1", |_| Ok(())).unwrap();
+        db.update_goal("g1", Goal::touch).unwrap();
+        db.update_goal("g1", |g| g.set_metrics(Metrics::new(5, 5, 0, 0, 1, 0, 0)))
+            .unwrap();
+        assert_eq!(db.list_events("g1").unwrap().len(), 2);
+
+        db.update_goal("g1", |g| {
+            g.update_budget(&Budget::new(Some(10), None, None))
+        })
+        .unwrap();
+        db.update_goal("g1", Goal::mark_in_progress).unwrap();
+        let events = db.list_events("g1").unwrap();
+        assert_eq!(events.len(), 4);
+        assert_eq!(events[2].action(), EventAction::Updated);
+        assert_eq!(events[3].to(), "in_progress");
+    }
+
+    // A goal without a journal file has no events rather than an error.
+    #[rstest]
+    fn list_events_missing_journal(db: (TempDir, Database)) {
+        let (_dir, db) = db;
+        assert!(db.list_events("nonexistent").unwrap().is_empty());
+    }
+
     // -- list_tasks --
 
     // list_tasks filters by goal_id and sorts by created_at ascending
@@ -480,6 +1032,94 @@ mod tests {
         assert!(db.list_tasks("nonexistent").is_empty());
     }
 
+    // -- id_kind --
+
+    // id_kind should tell goals, tasks and comments apart regardless of their
+    // prefix, and return None for unknown IDs.
+    #[rstest]
+    fn id_kind_identifies_entities(db_with_goal_and_task: (TempDir, Database)) {
+        let (_dir, mut db) = db_with_goal_and_task;
+        db.update_task("t1", |t| {
+            t.add_comment(Comment::new(
+                "c1".to_string(),
+                "hi".to_string(),
+                Timestamp::now(),
+            ));
+            Ok(())
+        })
+        .unwrap();
+
+        assert_eq!(db.id_kind("g1"), Some(IdKind::Goal));
+        assert_eq!(db.id_kind("t1"), Some(IdKind::Task));
+        assert_eq!(db.id_kind("c1"), Some(IdKind::Comment));
+        assert_eq!(db.id_kind("nope"), None);
+    }
+
+    // -- resolve_id --
+
+    // resolve_id should accept a full ID, or a prefix that is unique with or
+    // without the kind prefix, and return the full ID.
+    #[rstest]
+    #[case::exact("t1", "t1")]
+    #[case::unique_prefix("t-abc", "t-abc1")]
+    #[case::without_kind_prefix("abd", "t-abd2")]
+    fn resolve_id_finds_unique_match(
+        db_with_goal_and_task: (TempDir, Database),
+        #[case] input: &str,
+        #[case] expected: &str,
+    ) {
+        let (_dir, mut db) = db_with_goal_and_task;
+        db.create_task(make_task("t-abc1", "g1", TaskState::Pending))
+            .unwrap();
+        db.create_task(make_task("t-abd2", "g1", TaskState::Pending))
+            .unwrap();
+
+        assert_eq!(db.resolve_id(input, IdKind::Task).unwrap(), expected);
+    }
+
+    // An ambiguous prefix should list every candidate, and an ID of the wrong
+    // kind should say what it is rather than matching.
+    #[rstest]
+    fn resolve_id_rejects_ambiguous_and_wrong_kind(db_with_goal_and_task: (TempDir, Database)) {
+        let (_dir, mut db) = db_with_goal_and_task;
+        db.create_task(make_task("t-abc1", "g1", TaskState::Pending))
+            .unwrap();
+        db.create_task(make_task("t-abc2", "g1", TaskState::Pending))
+            .unwrap();
+
+        let err = db
+            .resolve_id("t-abc", IdKind::Task)
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("Ambiguous task ID"));
+        assert!(err.contains("t-abc1") && err.contains("t-abc2"));
+
+        let err = db.resolve_id("g1", IdKind::Task).unwrap_err().to_string();
+        assert_eq!(err, "g1 is a goal ID, not a task ID");
+
+        let err = db.resolve_id("zzz", IdKind::Goal).unwrap_err().to_string();
+        assert_eq!(err, "Goal not found: zzz");
+    }
+
+    // A prefix too short to be deliberate matches nothing, even when only one
+    // task could match it, and the error says how long it must be.
+    #[rstest]
+    #[case::bare_kind_prefix("t-")]
+    #[case::one_char("t-a")]
+    #[case::two_chars("ab")]
+    fn resolve_id_requires_minimum_prefix(
+        db_with_goal_and_task: (TempDir, Database),
+        #[case] input: &str,
+    ) {
+        let (_dir, mut db) = db_with_goal_and_task;
+        db.create_task(make_task("t-abc1", "g1", TaskState::Pending))
+            .unwrap();
+
+        let err = db.resolve_id(input, IdKind::Task).unwrap_err().to_string();
+        assert!(err.starts_with(&format!("Task not found: {input}")));
+        assert!(err.contains(&format!("at least {MIN_PREFIX_LEN} characters")));
+    }
+
     // -- compute_goal_metrics --
 
     // Metrics should aggregate tokens and elapsed_ms across all tasks,
@@ -508,6 +1148,123 @@ mod tests {
         assert_eq!(metrics.elapsed_ms(), 800);
     }
 
+    // A goal's metrics should include the tasks of its sub-goals at every depth,
+    // while a sub-goal's metrics cover only its own subtree.
+    #[rstest]
+    fn compute_goal_metrics_rolls_up_sub_goals(db: (TempDir, Database)) {
+        let (_dir, mut db) = db;
+        db.create_goal(make_goal("g1")).unwrap();
+        db.create_goal(make_sub_goal("g2", "g1")).unwrap();
+        db.create_goal(make_sub_goal("g3", "g2")).unwrap();
+
+        db.create_task(
+            make_task("t1", "g1", TaskState::Completed).with_metrics(TaskMetrics::new(10, 1, 0)),
+        )
+        .unwrap();
+        db.create_task(
+            make_task("t2", "g2", TaskState::Failed).with_metrics(TaskMetrics::new(20, 2, 0)),
+        )
+        .unwrap();
+        db.create_task(
+            make_task("t3", "g3", TaskState::Completed).with_metrics(TaskMetrics::new(40, 4, 0)),
+        )
+        .unwrap();
+
+        let root = db.compute_goal_metrics("g1");
+        assert_eq!(root.task_count(), 3);
+        assert_eq!(root.tasks_completed(), 2);
+        assert_eq!(root.tasks_failed(), 1);
+        assert_eq!(root.total_tokens(), 70);
+        assert_eq!(root.elapsed_ms(), 7);
+
+        let middle = db.compute_goal_metrics("g2");
+        assert_eq!(middle.task_count(), 2);
+        assert_eq!(middle.total_tokens(), 60);
+    }
+
+    // -- sub-goals --
+
+    // list_child_goals should return only direct children, and goal_subtree
+    // should list the goal itself followed by every descendant.
+    #[rstest]
+    fn sub_goal_lookups(db: (TempDir, Database)) {
+        let (_dir, mut db) = db;
+        db.create_goal(make_goal("g1")).unwrap();
+        db.create_goal(make_sub_goal("g2", "g1")).unwrap();
+        db.create_goal(make_sub_goal("g3", "g2")).unwrap();
+        db.create_goal(make_goal("g4")).unwrap();
+
+        let children: Vec<&str> = db.list_child_goals("g1").iter().map(|g| g.id()).collect();
+        assert_eq!(children, vec!["g2"]);
+        assert!(db.list_child_goals("g3").is_empty());
+
+        assert_eq!(db.goal_subtree("g1"), vec!["g1", "g2", "g3"]);
+        assert_eq!(db.goal_subtree("g4"), vec!["g4"]);
+    }
+
+    // -- stored goal metrics --
+
+    // Creating and completing tasks should keep the metrics stored in
+    // goal.toml in sync for the goal and its ancestors.
+    #[rstest]
+    fn stored_goal_metrics_stay_in_sync(db: (TempDir, Database)) {
+        let (dir, mut db) = db;
+        db.create_goal(make_goal("g1")).unwrap();
+        db.create_goal(make_sub_goal("g2", "g1")).unwrap();
+        db.create_task(make_task("t1", "g2", TaskState::InProgress))
+            .unwrap();
+
+        let reloaded = Database::open(dir.path()).unwrap();
+        assert_eq!(reloaded.get_goal("g2").unwrap().metrics().task_count(), 1);
+        assert_eq!(reloaded.get_goal("g1").unwrap().metrics().task_count(), 1);
+
+        db.update_task("t1", |t| {
+            Ok(t.complete(
+                Outcome::new("done".to_string(), Vec::new()),
+                TaskMetrics::new(50, 10, 0).with_token_split(30, 20),
+            ))
+        })
+        .unwrap();
+
+        let reloaded = Database::open(dir.path()).unwrap();
+        for gid in ["g1", "g2"] {
+            let stored = reloaded.get_goal(gid).unwrap().metrics();
+            assert_eq!(stored, &reloaded.compute_goal_metrics(gid));
+            assert_eq!(stored.tasks_completed(), 1);
+            assert_eq!(stored.total_tokens(), 50);
+            assert_eq!(stored.prompt_tokens(), 30);
+            assert_eq!(stored.completion_tokens(), 20);
+        }
+    }
+
+    // A process working from a stale load must not write metrics that drop
+    // another process's task changes.
+    #[rstest]
+    fn stored_goal_metrics_include_concurrent_writes(db: (TempDir, Database)) {
+        let (dir, mut db) = db;
+        db.create_goal(make_goal("g1")).unwrap();
+        db.create_task(make_task("t1", "g1", TaskState::InProgress))
+            .unwrap();
+        db.create_task(make_task("t2", "g1", TaskState::InProgress))
+            .unwrap();
+        let mut stale = Database::open(dir.path()).unwrap();
+
+        for (db, id, tokens) in [(&mut db, "t1", 10), (&mut stale, "t2", 5)] {
+            db.update_task(id, |t| {
+                Ok(t.complete(
+                    Outcome::new("done".to_string(), Vec::new()),
+                    TaskMetrics::new(tokens, 0, 0),
+                ))
+            })
+            .unwrap();
+        }
+
+        let reloaded = Database::open(dir.path()).unwrap();
+        let stored = reloaded.get_goal("g1").unwrap().metrics();
+        assert_eq!(stored.tasks_completed(), 2);
+        assert_eq!(stored.total_tokens(), 15);
+    }
+
     // A nonexistent goal should produce zeroed metrics, not an error.
     #[rstest]
     fn compute_goal_metrics_empty(db: (TempDir, Database)) {
@@ -0,0 +1,383 @@
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+
+use serde::Serialize;
+
+use crate::db::Database;
+use crate::models::{Task, TaskState};
+
+/// A problem in a goal's dependency graph.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Issue {
+    /// The tasks block each other in a loop, so none of them can ever start.
+    Cycle { task_ids: Vec<String> },
+    /// A blocker ID that no longer names any task (e.g. its file was deleted).
+    DanglingBlocker { task_id: String, blocker_id: String },
+    /// A blocker that belongs to a different goal.
+    CrossGoalBlocker {
+        task_id: String,
+        blocker_id: String,
+        blocker_goal_id: String,
+    },
+    /// A task that can never become ready because something it transitively
+    /// depends on is part of a cycle or has a bad blocker.
+    Unreachable { task_id: String, via: String },
+}
+
+impl fmt::Display for Issue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Cycle { task_ids } => {
+                write!(
+                    f,
+                    "Dependency cycle: {} -> {}",
+                    task_ids.join(" -> "),
+                    task_ids[0]
+                )
+            }
+            Self::DanglingBlocker {
+                task_id,
+                blocker_id,
+            } => write!(f, "Task {task_id} is blocked by missing task {blocker_id}"),
+            Self::CrossGoalBlocker {
+                task_id,
+                blocker_id,
+                blocker_goal_id,
+            } => write!(
+                f,
+                "Task {task_id} is blocked by {blocker_id}, which belongs to goal {blocker_goal_id}"
+            ),
+            Self::Unreachable { task_id, via } => {
+                write!(f, "Task {task_id} can never start: it depends on {via}")
+            }
+        }
+    }
+}
+
+/// Validate the dependency graph of one goal.
+pub fn check(goal_id: &str, db: &Database) -> Vec<Issue> {
+    let tasks = db.list_tasks(goal_id);
+    let by_id: HashMap<&str, &Task> = tasks.iter().map(|t| (t.id(), *t)).collect();
+
+    let mut issues = Vec::new();
+    let mut broken: HashSet<&str> = HashSet::new();
+
+    for task in &tasks {
+        for blocker_id in task.blocked_by() {
+            if by_id.contains_key(blocker_id.as_str()) {
+                continue;
+            }
+            broken.insert(task.id());
+            issues.push(match db.get_task(blocker_id) {
+                Some(blocker) => Issue::CrossGoalBlocker {
+                    task_id: task.id().to_owned(),
+                    blocker_id: blocker_id.clone(),
+                    blocker_goal_id: blocker.goal_id().to_owned(),
+                },
+                None => Issue::DanglingBlocker {
+                    task_id: task.id().to_owned(),
+                    blocker_id: blocker_id.clone(),
+                },
+            });
+        }
+    }
+
+    for cycle in find_cycles(&tasks, &by_id) {
+        broken.extend(cycle.iter().copied());
+        issues.push(Issue::Cycle {
+            task_ids: cycle.into_iter().map(str::to_owned).collect(),
+        });
+    }
+
+    for task in &tasks {
+        if broken.contains(task.id()) || task.state() == TaskState::Completed {
+            continue;
+        }
+        if let Some(via) = first_broken_dependency(task.id(), &by_id, &broken) {
+            issues.push(Issue::Unreachable {
+                task_id: task.id().to_owned(),
+                via: via.to_owned(),
+            });
+        }
+    }
+
+    issues
+}
+
+/// How many unfinished tasks of a goal wait on `task_id`, directly or through
+/// other tasks.
+pub fn downstream_count(goal_id: &str, task_id: &str, db: &Database) -> usize {
+    let tasks = db.list_tasks(goal_id);
+    let mut dependents: HashMap<&str, Vec<&str>> = HashMap::new();
+    for task in &tasks {
+        if task.state() == TaskState::Completed {
+            continue;
+        }
+        for blocker_id in task.blocked_by() {
+            dependents
+                .entry(blocker_id.as_str())
+                .or_default()
+                .push(task.id());
+        }
+    }
+
+    let mut seen: HashSet<&str> = HashSet::new();
+    let mut stack = vec![task_id];
+    while let Some(id) = stack.pop() {
+        for dependent in dependents.get(id).into_iter().flatten() {
+            if *dependent != task_id && seen.insert(dependent) {
+                stack.push(dependent);
+            }
+        }
+    }
+    seen.len()
+}
+
+/// Find a chain of `blocked_by` edges from `from` to `to` within a goal,
+/// returned as the task IDs along the way (both ends included).
+///
+/// Making `to` blocked by `from` would close a cycle exactly when such a
+/// chain exists.
+pub fn find_path(goal_id: &str, from: &str, to: &str, db: &Database) -> Option<Vec<String>> {
+    let tasks = db.list_tasks(goal_id);
+    let by_id: HashMap<&str, &Task> = tasks.iter().map(|t| (t.id(), *t)).collect();
+
+    let mut visited = HashSet::new();
+    let mut path = Vec::new();
+    if walk_to(from, to, &by_id, &mut visited, &mut path) {
+        Some(path.into_iter().map(str::to_owned).collect())
+    } else {
+        None
+    }
+}
+
+fn walk_to<'a>(
+    current: &'a str,
+    target: &str,
+    by_id: &HashMap<&'a str, &'a Task>,
+    visited: &mut HashSet<&'a str>,
+    path: &mut Vec<&'a str>,
+) -> bool {
+    path.push(current);
+    if current == target {
+        return true;
+    }
+    if visited.insert(current)
+        && let Some(task) = by_id.get(current)
+    {
+        for blocker_id in task.blocked_by() {
+            if walk_to(blocker_id, target, by_id, visited, path) {
+                return true;
+            }
+        }
+    }
+    path.pop();
+    false
+}
+
+/// Cycles closed by back edges in a depth-first search over `blocked_by`.
+/// Each is rotated to start at its smallest task ID so repeats are dropped.
+fn find_cycles<'a>(tasks: &[&'a Task], by_id: &HashMap<&'a str, &'a Task>) -> Vec<Vec<&'a str>> {
+    fn visit<'a>(
+        id: &'a str,
+        by_id: &HashMap<&'a str, &'a Task>,
+        done: &mut HashSet<&'a str>,
+        stack: &mut Vec<&'a str>,
+        cycles: &mut Vec<Vec<&'a str>>,
+    ) {
+        if done.contains(id) {
+            return;
+        }
+        if let Some(pos) = stack.iter().position(|&s| s == id) {
+            let mut cycle = stack[pos..].to_vec();
+            let min = (0..cycle.len()).min_by_key(|&i| cycle[i]).unwrap_or(0);
+            cycle.rotate_left(min);
+            if !cycles.contains(&cycle) {
+                cycles.push(cycle);
+            }
+            return;
+        }
+        let Some(task) = by_id.get(id) else {
+            return;
+        };
+        stack.push(id);
+        for blocker_id in task.blocked_by() {
+            if let Some((&key, _)) = by_id.get_key_value(blocker_id.as_str()) {
+                visit(key, by_id, done, stack, cycles);
+            }
+        }
+        stack.pop();
+        done.insert(id);
+    }
+
+    let mut done = HashSet::new();
+    let mut cycles = Vec::new();
+    for task in tasks {
+        visit(task.id(), by_id, &mut done, &mut Vec::new(), &mut cycles);
+    }
+    cycles
+}
+
+fn first_broken_dependency<'a>(
+    id: &'a str,
+    by_id: &HashMap<&'a str, &'a Task>,
+    broken: &HashSet<&'a str>,
+) -> Option<&'a str> {
+    let mut visited = HashSet::new();
+    let mut queue = vec![id];
+    while let Some(current) = queue.pop() {
+        if !visited.insert(current) {
+            continue;
+        }
+        let Some(task) = by_id.get(current) else {
+            continue;
+        };
+        for blocker_id in task.blocked_by() {
+            if let Some((&key, _)) = by_id.get_key_value(blocker_id.as_str()) {
+                if broken.contains(key) {
+                    return Some(key);
+                }
+                queue.push(key);
+            }
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{Goal, GoalState, Metrics};
+    use jiff::Timestamp;
+    use rstest::{fixture, rstest};
+    use tempfile::TempDir;
+
+    fn make_task(id: &str, goal_id: &str, blocked_by: &[&str]) -> Task {
+        let now = Timestamp::now();
+        Task::new(
+            id.to_string(),
+            goal_id.to_string(),
+            "test task".to_string(),
+            None,
+            TaskState::Blocked,
+            blocked_by.iter().map(|b| (*b).to_string()).collect(),
+            now,
+            now,
+        )
+    }
+
+    /// A Database with two empty goals, "g1" and "g2".
+    #[fixture]
+    fn db() -> (TempDir, Database) {
+        let dir = TempDir::new().unwrap();
+        let mut db = Database::open(dir.path()).unwrap();
+        for id in ["g1", "g2"] {
+            let now = Timestamp::now();
+            db.create_goal(Goal::new(
+                id.to_string(),
+                None,
+                "test goal".to_string(),
+                GoalState::Pending,
+                now,
+                now,
+                None,
+                Metrics::default(),
+            ))
+            .unwrap();
+        }
+        (dir, db)
+    }
+
+    fn add(db: &mut Database, id: &str, goal_id: &str, blocked_by: &[&str]) {
+        db.create_task(make_task(id, goal_id, blocked_by)).unwrap();
+    }
+
+    // A well-formed chain has no issues.
+    #[rstest]
+    fn check_clean_graph(db: (TempDir, Database)) {
+        let (_dir, mut db) = db;
+        add(&mut db, "a", "g1", &[]);
+        add(&mut db, "b", "g1", &["a"]);
+        add(&mut db, "c", "g1", &["a", "b"]);
+        assert!(check("g1", &db).is_empty());
+    }
+
+    // downstream_count follows dependents transitively, counting shared ones
+    // once and skipping completed ones.
+    #[rstest]
+    fn downstream_count_counts_transitive_dependents(db: (TempDir, Database)) {
+        let (_dir, mut db) = db;
+        add(&mut db, "a", "g1", &[]);
+        add(&mut db, "b", "g1", &["a"]);
+        add(&mut db, "c", "g1", &["a", "b"]);
+        add(&mut db, "d", "g1", &["c"]);
+        add(&mut db, "e", "g1", &[]);
+
+        assert_eq!(downstream_count("g1", "a", &db), 3);
+        assert_eq!(downstream_count("g1", "c", &db), 1);
+        assert_eq!(downstream_count("g1", "e", &db), 0);
+    }
+
+    // A loop is reported once, starting from its smallest ID, and tasks
+    // downstream of it are reported as unreachable.
+    #[rstest]
+    fn check_detects_cycle_and_unreachable(db: (TempDir, Database)) {
+        let (_dir, mut db) = db;
+        add(&mut db, "b", "g1", &["a"]);
+        add(&mut db, "a", "g1", &["c"]);
+        add(&mut db, "c", "g1", &["b"]);
+        add(&mut db, "d", "g1", &["c"]);
+
+        let issues = check("g1", &db);
+        assert_eq!(
+            issues,
+            vec![
+                Issue::Cycle {
+                    task_ids: vec!["a".to_string(), "c".to_string(), "b".to_string()],
+                },
+                Issue::Unreachable {
+                    task_id: "d".to_string(),
+                    via: "c".to_string(),
+                },
+            ]
+        );
+    }
+
+    // Missing blockers and blockers from another goal are reported
+    // distinctly.
+    #[rstest]
+    fn check_detects_bad_blockers(db: (TempDir, Database)) {
+        let (_dir, mut db) = db;
+        add(&mut db, "x", "g2", &[]);
+        add(&mut db, "a", "g1", &["gone"]);
+        add(&mut db, "b", "g1", &["x"]);
+
+        let issues = check("g1", &db);
+        assert!(issues.contains(&Issue::DanglingBlocker {
+            task_id: "a".to_string(),
+            blocker_id: "gone".to_string(),
+        }));
+        assert!(issues.contains(&Issue::CrossGoalBlocker {
+            task_id: "b".to_string(),
+            blocker_id: "x".to_string(),
+            blocker_goal_id: "g2".to_string(),
+        }));
+    }
+
+    // find_path follows blocked_by edges and returns the chain, or None
+    // when the target is not upstream.
+    #[rstest]
+    fn find_path_follows_blockers(db: (TempDir, Database)) {
+        let (_dir, mut db) = db;
+        add(&mut db, "a", "g1", &[]);
+        add(&mut db, "b", "g1", &["a"]);
+        add(&mut db, "c", "g1", &["b"]);
+
+        assert_eq!(
+            find_path("g1", "c", "a", &db),
+            Some(vec!["c".to_string(), "b".to_string(), "a".to_string()])
+        );
+        assert_eq!(find_path("g1", "a", "c", &db), None);
+    }
+}
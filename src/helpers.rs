@@ -1,15 +1,36 @@
+use anyhow::anyhow;
 use strsim::levenshtein;
 
-/// Find the most similar ID from a list of candidates
-pub fn find_similar_id<'a>(target: &str, candidates: &[&'a str]) -> Option<&'a str> {
+use crate::db::Database;
+use crate::id::IdKind;
+
+/// Find the most similar ID from a list of candidates, at most `max_distance`
+/// edits away
+pub fn find_similar_id<'a>(
+    target: &str,
+    candidates: &[&'a str],
+    max_distance: usize,
+) -> Option<&'a str> {
     candidates
         .iter()
         .map(|&candidate| (candidate, levenshtein(target, candidate)))
-        .filter(|(_, distance)| *distance <= 2)
+        .filter(|(_, distance)| *distance <= max_distance)
         .min_by_key(|(_, distance)| *distance)
         .map(|(id, _)| id)
 }
 
+/// If `id` names something other than the `expected` kind, explain what it is.
+pub fn wrong_kind_err(id: &str, expected: IdKind, db: &Database) -> Option<anyhow::Error> {
+    let actual = db.id_kind(id)?;
+    (actual != expected).then(|| {
+        anyhow!(
+            "{id} is a {} ID, not a {} ID",
+            actual.noun(),
+            expected.noun()
+        )
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -18,11 +39,17 @@ mod tests {
     fn test_find_similar_id() {
         let candidates = vec!["t8zwaROl", "xYz9Kp2m", "V1StGXR8"];
 
-        assert_eq!(find_similar_id("t8zwaRO1", &candidates), Some("t8zwaROl"));
+        assert_eq!(
+            find_similar_id("t8zwaRO1", &candidates, 2),
+            Some("t8zwaROl")
+        );
 
-        assert_eq!(find_similar_id("xYz9Kp2n", &candidates), Some("xYz9Kp2m"));
+        assert_eq!(
+            find_similar_id("xYz9Kp2n", &candidates, 2),
+            Some("xYz9Kp2m")
+        );
 
         // Very different ID should return None
-        assert_eq!(find_similar_id("zzzzz", &candidates), None);
+        assert_eq!(find_similar_id("zzzzz", &candidates, 2), None);
     }
 }
@@ -1,15 +1,53 @@
-/// Generate a safe 8-character ID
-/// Uses alphanumeric characters only (no dashes or underscores)
-/// to avoid conflicts with CLI flag parsing
-pub fn generate_id() -> String {
-    const ALPHABET: [char; 62] = [
-        '0', '1', '2', '3', '4', '5', '6', '7', '8', '9', 'A', 'B', 'C', 'D', 'E', 'F', 'G', 'H',
-        'I', 'J', 'K', 'L', 'M', 'N', 'O', 'P', 'Q', 'R', 'S', 'T', 'U', 'V', 'W', 'X', 'Y', 'Z',
-        'a', 'b', 'c', 'd', 'e', 'f', 'g', 'h', 'i', 'j', 'k', 'l', 'm', 'n', 'o', 'p', 'q', 'r',
-        's', 't', 'u', 'v', 'w', 'x', 'y', 'z',
-    ];
-
-    nanoid::nanoid!(8, &ALPHABET)
+/// Characters new IDs are drawn from unless `id.alphabet` says otherwise.
+pub const ALPHABET: &str = "0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz";
+
+/// Fewest characters, after the kind prefix, that a partial ID must have before
+/// it is matched as a prefix. Shorter ones would silently pick whichever single
+/// goal or task happens to exist.
+pub const MIN_PREFIX_LEN: usize = 3;
+
+/// What an ID names. New IDs carry a prefix per kind so that a goal ID can't be
+/// mistaken for a task ID. IDs created before prefixes existed have none.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IdKind {
+    Goal,
+    Task,
+    Comment,
+}
+
+impl IdKind {
+    pub fn default_prefix(self) -> &'static str {
+        match self {
+            Self::Goal => "g-",
+            Self::Task => "t-",
+            Self::Comment => "c-",
+        }
+    }
+
+    /// Capitalized noun, for the start of a message.
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Goal => "Goal",
+            Self::Task => "Task",
+            Self::Comment => "Comment",
+        }
+    }
+
+    pub fn noun(self) -> &'static str {
+        match self {
+            Self::Goal => "goal",
+            Self::Task => "task",
+            Self::Comment => "comment",
+        }
+    }
+}
+
+/// Generate an ID of `length` random characters from `alphabet` after `prefix`.
+/// The prefix must not start with a dash, and the alphabet needs at least two
+/// characters.
+pub fn generate_id(prefix: &str, length: usize, alphabet: &str) -> String {
+    let alphabet: Vec<char> = alphabet.chars().collect();
+    format!("{prefix}{}", nanoid::nanoid!(length, &alphabet))
 }
 
 #[cfg(test)]
@@ -19,12 +57,10 @@ mod tests {
     #[test]
     fn test_generate_id() {
         for _ in 0..100 {
-            let id = generate_id();
-            assert_eq!(id.len(), 8);
-            assert!(!id.starts_with('-'));
-            assert!(!id.starts_with('_'));
-            assert!(!id.contains('-'));
-            assert!(!id.contains('_'));
+            let id = generate_id("t-", 8, ALPHABET);
+            let random = id.strip_prefix("t-").unwrap();
+            assert_eq!(random.len(), 8);
+            assert!(random.chars().all(|c| c.is_ascii_alphanumeric()));
         }
     }
 }
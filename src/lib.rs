@@ -2,23 +2,45 @@
 #![allow(clippy::missing_panics_doc)]
 #![allow(clippy::must_use_candidate)]
 
+pub mod api;
 pub mod cli;
 pub mod commands;
+pub mod config;
 pub mod db;
+pub mod graph;
 pub mod helpers;
 pub mod id;
+pub mod mcp;
 pub mod models;
 pub mod output;
+pub mod pricing;
+pub mod runner;
+pub mod serve;
 
 use anyhow::{Context, Result, anyhow};
 use std::path::PathBuf;
+use std::time::Duration;
 
-use cli::{Cli, Commands, GoalCommands, TaskCommands};
+use cli::{Cli, Commands, ConfigCommands, GoalCommands, TaskCommands};
 use db::Database;
+use models::Budget;
 
 pub const RADIAL_DIR: &str = ".radial";
 pub const REDIRECT_FILE: &str = "redirect";
 
+/// Ends the process with a specific exit code once a command has printed its
+/// output, for outcomes that aren't errors but that scripts need to tell apart.
+#[derive(Debug)]
+pub struct Exit(pub u8);
+
+impl std::fmt::Display for Exit {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "exit code {}", self.0)
+    }
+}
+
+impl std::error::Error for Exit {}
+
 /// Finds the `.radial/` directory by walking up from the current directory.
 /// Returns `None` if no `.radial/` directory is found.
 pub fn find_radial_dir() -> Option<PathBuf> {
@@ -59,6 +81,13 @@ pub fn resolve_radial_dir() -> Option<PathBuf> {
     Some(radial_dir)
 }
 
+/// The directory containing the local `.radial/`, where verify commands run.
+fn project_root() -> Result<PathBuf> {
+    find_radial_dir()
+        .and_then(|dir| dir.parent().map(PathBuf::from))
+        .ok_or_else(|| anyhow!("Radial not initialized. Run 'radial init' first."))
+}
+
 fn get_radial_path() -> Option<PathBuf> {
     resolve_radial_dir()
 }
@@ -70,15 +99,56 @@ fn ensure_initialized() -> Result<Database> {
     Database::open(&radial_dir).context("Failed to open database")
 }
 
+/// Fall back to the configured default agent when none was given.
+fn default_agent(agent: Option<String>, db: &Database) -> Option<String> {
+    agent.or_else(|| db.config().default_agent().map(str::to_owned))
+}
+
+fn run_config(config_cmd: ConfigCommands, db: &mut Database) -> Result<()> {
+    match config_cmd {
+        ConfigCommands::Get { key } => {
+            let value = commands::config::get(&key, db)?;
+            output::config_value(&value)
+        }
+        ConfigCommands::Set { key, value } => {
+            commands::config::set(&key, &value, db)?;
+            output::config_set(&key, &value)
+        }
+        ConfigCommands::List { json } => {
+            let entries = commands::config::list(db);
+            output::config_list(&entries, json || db.config().default_json())
+        }
+    }
+}
+
 fn run_goal(goal_cmd: GoalCommands, db: &mut Database) -> Result<()> {
     match goal_cmd {
-        GoalCommands::Create { description, json } => {
-            let goal = commands::goal::create(description, db)?;
-            output::goal_created(&goal, json)
+        GoalCommands::Create {
+            description,
+            parent,
+            max_tokens,
+            max_elapsed,
+            max_retries,
+            json,
+        } => {
+            let budget = Budget::new(max_tokens, max_elapsed, max_retries);
+            let goal = commands::goal::create(description, parent, budget, db)?;
+            output::goal_created(&goal, json || db.config().default_json())
         }
         GoalCommands::List { json } => {
             let goals = commands::goal::list(db);
-            output::goal_list(&goals, json)
+            output::goal_list(&goals, json || db.config().default_json())
+        }
+        GoalCommands::Budget {
+            goal_id,
+            max_tokens,
+            max_elapsed,
+            max_retries,
+            json,
+        } => {
+            let changes = Budget::new(max_tokens, max_elapsed, max_retries);
+            let goal = commands::goal::set_budget(&goal_id, changes, db)?;
+            output::goal_budget(&goal, json || db.config().default_json())
         }
     }
 }
@@ -91,7 +161,9 @@ fn run_task(task_cmd: TaskCommands, db: &mut Database) -> Result<()> {
             receives,
             produces,
             verify,
+            verify_cmd,
             blocked_by,
+            priority,
             json,
         } => {
             let task = commands::task::create(
@@ -100,48 +172,185 @@ fn run_task(task_cmd: TaskCommands, db: &mut Database) -> Result<()> {
                 receives,
                 produces,
                 verify,
+                verify_cmd,
                 blocked_by,
+                priority,
                 db,
             )?;
-            output::task_created(&task, json)
+            output::task_created(&task, json || db.config().default_json())
         }
         TaskCommands::List {
             goal_id,
             json,
             verbose,
+            agent,
+        } => {
+            let tasks = commands::task::list(&goal_id, agent.as_deref(), db)?;
+            let goal = db.resolve_goal(&goal_id)?;
+            output::task_list(&tasks, goal, verbose, json || db.config().default_json())
+        }
+        TaskCommands::Update {
+            task_id,
+            description,
+            receives,
+            produces,
+            verify,
+            verify_cmd,
+            add_blocked_by,
+            remove_blocked_by,
+            priority,
+            force,
+            json,
         } => {
-            let tasks = commands::task::list(&goal_id, db)?;
-            let goal = db
-                .get_goal(&goal_id)
-                .ok_or_else(|| anyhow!("Goal not found: {goal_id}"))?;
-            output::task_list(&tasks, goal, verbose, json)
+            let changes = commands::task::TaskUpdate {
+                description,
+                receives,
+                produces,
+                verify,
+                verify_cmd,
+                add_blocked_by,
+                remove_blocked_by,
+                priority,
+            };
+            let task = commands::task::update(&task_id, changes, force, db)?;
+            output::task_updated(&task, json || db.config().default_json())
         }
-        TaskCommands::Start { task_id } => {
-            let task = commands::task::start(&task_id, db)?;
-            output::task_started(&task)
+        TaskCommands::Contract {
+            task_id,
+            receives,
+            produces,
+            verify,
+            verify_cmd,
+            force,
+            json,
+        } => {
+            let changes = commands::task::TaskUpdate {
+                receives,
+                produces,
+                verify,
+                verify_cmd,
+                ..Default::default()
+            };
+            let task = commands::task::update(&task_id, changes, force, db)?;
+            output::task_updated(&task, json || db.config().default_json())
+        }
+        TaskCommands::Start {
+            task_id,
+            agent,
+            lease_secs,
+            json,
+        } => {
+            let task = commands::task::start(&task_id, default_agent(agent, db), lease_secs, db)?;
+            let context = commands::handoff::context(&task, db);
+            output::task_started(&task, &context, json || db.config().default_json())
+        }
+        TaskCommands::Heartbeat {
+            task_id,
+            agent,
+            json,
+        } => {
+            let task = commands::task::heartbeat(&task_id, default_agent(agent, db), db)?;
+            output::task_heartbeat(&task, json || db.config().default_json())
         }
         TaskCommands::Complete {
             task_id,
             result,
             artifacts,
             tokens,
+            prompt_tokens,
+            completion_tokens,
+            model,
+            elapsed,
+            agent,
+            json,
+        } => {
+            let usage =
+                commands::task::usage(tokens, prompt_tokens, completion_tokens, elapsed, model);
+            let complete_result = commands::task::complete(
+                &task_id,
+                result,
+                artifacts,
+                usage,
+                default_agent(agent, db),
+                db,
+            )?;
+            output::task_completed(&complete_result, json || db.config().default_json())
+        }
+        TaskCommands::Submit {
+            task_id,
+            result,
+            artifacts,
+            tokens,
+            prompt_tokens,
+            completion_tokens,
+            model,
             elapsed,
+            agent,
+            json,
         } => {
-            let complete_result =
-                commands::task::complete(&task_id, result, artifacts, tokens, elapsed, db)?;
-            output::task_completed(&complete_result)
+            let usage =
+                commands::task::usage(tokens, prompt_tokens, completion_tokens, elapsed, model);
+            let task = commands::task::submit(
+                &task_id,
+                result,
+                artifacts,
+                usage,
+                default_agent(agent, db),
+                db,
+            )?;
+            output::task_submitted(&task, json || db.config().default_json())
         }
-        TaskCommands::Fail { task_id } => {
-            let task = commands::task::fail(&task_id, db)?;
-            output::task_failed(&task)
+        TaskCommands::Verify {
+            task_id,
+            pass,
+            fail: _,
+            run,
+            timeout,
+            note,
+            agent,
+            json,
+        } => {
+            let verify_result = if run {
+                let root = project_root()?;
+                let timeout = Duration::from_secs(timeout);
+                commands::task::run_verify(&task_id, default_agent(agent, db), &root, timeout, db)?
+            } else {
+                commands::task::verify(&task_id, pass, note, default_agent(agent, db), db)?
+            };
+            output::task_verified(&verify_result, json || db.config().default_json())
         }
-        TaskCommands::Retry { task_id } => {
-            let task = commands::task::retry(&task_id, db)?;
-            output::task_retry(&task)
+        TaskCommands::Fail {
+            task_id,
+            tokens,
+            prompt_tokens,
+            completion_tokens,
+            model,
+            elapsed,
+            agent,
+            json,
+        } => {
+            let usage =
+                commands::task::usage(tokens, prompt_tokens, completion_tokens, elapsed, model);
+            let task = commands::task::fail(&task_id, usage, default_agent(agent, db), db)?;
+            output::task_failed(&task, json || db.config().default_json())
         }
-        TaskCommands::Comment { task_id, text } => {
-            let task = commands::task::comment(&task_id, text, db)?;
-            output::task_commented(&task, false)
+        TaskCommands::Retry {
+            task_id,
+            agent,
+            lease_secs,
+            json,
+        } => {
+            let task = commands::task::retry(&task_id, default_agent(agent, db), lease_secs, db)?;
+            output::task_retry(&task, json || db.config().default_json())
+        }
+        TaskCommands::Comment {
+            task_id,
+            text,
+            agent,
+            json,
+        } => {
+            let task = commands::task::comment(&task_id, text, default_agent(agent, db), db)?;
+            output::task_commented(&task, json || db.config().default_json())
         }
     }
 }
@@ -157,6 +366,10 @@ pub fn run(cli: Cli) -> Result<()> {
             let mut db = ensure_initialized()?;
             run_task(task_cmd, &mut db)
         }
+        Commands::Config(config_cmd) => {
+            let mut db = ensure_initialized()?;
+            run_config(config_cmd, &mut db)
+        }
         Commands::Status {
             goal,
             task,
@@ -165,19 +378,116 @@ pub fn run(cli: Cli) -> Result<()> {
         } => {
             let db = ensure_initialized()?;
             let result = commands::status::run(goal, task, &db)?;
-            output::status(&result, json, concise)
+            output::status(
+                &result,
+                json || db.config().default_json(),
+                concise || db.config().default_concise(),
+            )
         }
-        Commands::Ready { goal_id, json } => {
+        Commands::Ready {
+            goal_id,
+            limit,
+            json,
+        } => {
+            let mut db = ensure_initialized()?;
+            commands::reap::run(goal_id.as_deref(), &mut db)?;
+            let json = json || db.config().default_json();
+            match goal_id {
+                Some(goal_id) => {
+                    let tasks = commands::ready::run(&goal_id, limit, &db)?;
+                    let goal = db.resolve_goal(&goal_id)?;
+                    output::ready_tasks(&tasks, goal, json)
+                }
+                None => {
+                    let tasks = commands::ready::run_all(limit, &db);
+                    output::ready_tasks_all_goals(&tasks, json)
+                }
+            }
+        }
+        Commands::Next {
+            goal_id,
+            agent,
+            json,
+        } => {
+            let mut db = ensure_initialized()?;
+            commands::reap::run(Some(&goal_id), &mut db)?;
+            let agent = default_agent(agent, &db);
+            let next = commands::next::run(&goal_id, agent, &mut db)?;
+            output::next(&next, json || db.config().default_json())?;
+
+            match next.exit_code() {
+                0 => Ok(()),
+                code => Err(Exit(code).into()),
+            }
+        }
+        Commands::Why { task_id, json } => {
             let db = ensure_initialized()?;
-            let tasks = commands::ready::run(&goal_id, &db)?;
-            let goal = db
-                .get_goal(&goal_id)
-                .ok_or_else(|| anyhow!("Goal not found: {goal_id}"))?;
-            output::ready_tasks(&tasks, goal, json)
+            let why = commands::why::run(&task_id, &db)?;
+            output::why(&why, json || db.config().default_json())
+        }
+        Commands::Check { goal_id, json } => {
+            let db = ensure_initialized()?;
+            let checks = commands::check::run(goal_id, &db)?;
+            output::check(&checks, json || db.config().default_json())?;
+
+            let issue_count: usize = checks.iter().map(|c| c.issues().len()).sum();
+            if issue_count > 0 {
+                return Err(anyhow!("Found {issue_count} dependency problem(s)"));
+            }
+            Ok(())
+        }
+        Commands::Log { goal, task, json } => {
+            let db = ensure_initialized()?;
+            let events = commands::log::run(goal, task, &db)?;
+            output::events(&events, json || db.config().default_json())
+        }
+        Commands::Watch { goal, json } => {
+            let db = ensure_initialized()?;
+            let json = json || db.config().default_json();
+            commands::watch::run(db.base_path(), goal.as_deref(), |change| {
+                output::change(change, json)
+            })
+        }
+        Commands::Reap { goal_id, json } => {
+            let mut db = ensure_initialized()?;
+            let tasks = commands::reap::run(goal_id.as_deref(), &mut db)?;
+            output::reaped_tasks(&tasks, json || db.config().default_json())
         }
         Commands::Prep => {
             let text = commands::prep::run();
             output::prep(text)
         }
+        Commands::Prompt {
+            task_id,
+            template,
+            agent,
+        } => {
+            let db = ensure_initialized()?;
+            let agent = default_agent(agent, &db);
+            let text = commands::prompt::run(&task_id, template.as_deref(), agent.as_deref(), &db)?;
+            output::prompt(&text)
+        }
+        Commands::Mcp { agent } => {
+            let radial_dir = get_radial_path()
+                .ok_or_else(|| anyhow!("Radial not initialized. Run 'radial init' first."))?;
+            let stdin = std::io::stdin().lock();
+            mcp::serve(
+                &radial_dir,
+                agent.as_deref(),
+                stdin,
+                std::io::stdout().lock(),
+            )
+        }
+        Commands::Serve {
+            bind,
+            agent,
+            allow_remote,
+        } => {
+            let radial_dir = get_radial_path()
+                .ok_or_else(|| anyhow!("Radial not initialized. Run 'radial init' first."))?;
+            let listener = serve::listen(&bind, allow_remote)?;
+            output::serving(listener.local_addr()?)?;
+            serve::run(&listener, &radial_dir, agent.as_deref(), allow_remote)
+        }
     }
 }
@@ -1,8 +1,19 @@
-use anyhow::Result;
+use std::process::ExitCode;
+
 use clap::Parser;
+use radial::Exit;
 use radial::cli::Cli;
 
-fn main() -> Result<()> {
+fn main() -> ExitCode {
     let cli = Cli::parse();
-    radial::run(cli)
+    match radial::run(cli) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => {
+            if let Some(Exit(code)) = err.downcast_ref::<Exit>() {
+                return ExitCode::from(*code);
+            }
+            eprintln!("Error: {err:?}");
+            ExitCode::FAILURE
+        }
+    }
 }
@@ -0,0 +1,207 @@
+//! A Model Context Protocol server on stdin/stdout, so MCP-capable agents can
+//! call radial's operations as tools instead of shelling out to `rd`.
+//!
+//! Messages are JSON-RPC 2.0, one per line. The database is reopened for every
+//! tool call so the server sees changes made by other agents in the meantime.
+
+use std::io::{BufRead, Write};
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use serde_json::{Value, json};
+
+use crate::api;
+use crate::db::Database;
+
+/// Protocol version offered when the client asks for one this server doesn't
+/// speak, or for none.
+pub const PROTOCOL_VERSION: &str = "2024-11-05";
+
+/// Protocol versions a client can get by asking for them.
+const SUPPORTED_VERSIONS: &[&str] = &[PROTOCOL_VERSION, "2025-03-26", "2025-06-18"];
+
+const PARSE_ERROR: i64 = -32700;
+const INVALID_REQUEST: i64 = -32600;
+const METHOD_NOT_FOUND: i64 = -32601;
+const INVALID_PARAMS: i64 = -32602;
+
+/// Answer requests from `input` until it closes. `agent` is used for calls that
+/// don't name one.
+pub fn serve(
+    radial_dir: &Path,
+    agent: Option<&str>,
+    input: impl BufRead,
+    mut output: impl Write,
+) -> Result<()> {
+    for line in input.lines() {
+        let line = line.context("Failed to read request")?;
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let response = match serde_json::from_str::<Value>(&line) {
+            Ok(request) => handle(&request, radial_dir, agent),
+            Err(err) => Some(error(Value::Null, PARSE_ERROR, &err.to_string())),
+        };
+        if let Some(response) = response {
+            writeln!(output, "{response}").context("Failed to write response")?;
+            output.flush()?;
+        }
+    }
+    Ok(())
+}
+
+/// The response to one message, or `None` for notifications.
+fn handle(request: &Value, radial_dir: &Path, agent: Option<&str>) -> Option<Value> {
+    let Some(method) = request.get("method").and_then(Value::as_str) else {
+        let id = request.get("id").cloned().unwrap_or(Value::Null);
+        return Some(error(id, INVALID_REQUEST, "Missing method"));
+    };
+    // Requests without an id are notifications and get no reply.
+    let id = request.get("id")?.clone();
+    let params = request.get("params").cloned().unwrap_or(Value::Null);
+
+    Some(match method {
+        "initialize" => {
+            let version = params
+                .get("protocolVersion")
+                .and_then(Value::as_str)
+                .filter(|v| SUPPORTED_VERSIONS.contains(v))
+                .unwrap_or(PROTOCOL_VERSION);
+            result(
+                id,
+                json!({
+                    "protocolVersion": version,
+                    "capabilities": { "tools": {} },
+                    "serverInfo": { "name": "radial", "version": env!("CARGO_PKG_VERSION") },
+                }),
+            )
+        }
+        "ping" => result(id, json!({})),
+        "tools/list" => {
+            let tools: Vec<Value> = api::OPERATIONS
+                .iter()
+                .map(|op| {
+                    json!({
+                        "name": op.name,
+                        "description": op.description,
+                        "inputSchema": (op.input_schema)(),
+                    })
+                })
+                .collect();
+            result(id, json!({ "tools": tools }))
+        }
+        "tools/call" => {
+            let Some(name) = params.get("name").and_then(Value::as_str) else {
+                return Some(error(id, INVALID_PARAMS, "Missing tool name"));
+            };
+            if !api::OPERATIONS.iter().any(|op| op.name == name) {
+                return Some(error(id, INVALID_PARAMS, &format!("Unknown tool: {name}")));
+            }
+            let args = params.get("arguments").cloned().unwrap_or(json!({}));
+
+            let outcome = Database::open(radial_dir)
+                .context("Failed to open database")
+                .and_then(|mut db| api::call(name, &args, agent, &mut db));
+            let (text, is_error) = match outcome {
+                Ok(value) => (value.to_string(), false),
+                Err(err) => (format!("{err:#}"), true),
+            };
+            result(
+                id,
+                json!({
+                    "content": [{ "type": "text", "text": text }],
+                    "isError": is_error,
+                }),
+            )
+        }
+        _ => error(id, METHOD_NOT_FOUND, &format!("Unknown method: {method}")),
+    })
+}
+
+fn result(id: Value, result: Value) -> Value {
+    json!({ "jsonrpc": "2.0", "id": id, "result": result })
+}
+
+fn error(id: Value, code: i64, message: &str) -> Value {
+    json!({ "jsonrpc": "2.0", "id": id, "error": { "code": code, "message": message } })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+    use tempfile::TempDir;
+
+    fn exchange(dir: &Path, requests: &[Value]) -> Vec<Value> {
+        let input: String = requests.iter().map(|r| format!("{r}\n")).collect();
+        let mut output = Vec::new();
+        serve(dir, None, input.as_bytes(), &mut output).unwrap();
+        String::from_utf8(output)
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect()
+    }
+
+    // -- serve --
+
+    // Notifications get no reply; every request gets one with its id.
+    #[rstest]
+    fn serve_replies_to_requests_only() {
+        let dir = TempDir::new().unwrap();
+        let responses = exchange(
+            dir.path(),
+            &[
+                json!({ "jsonrpc": "2.0", "id": 1, "method": "initialize", "params": {} }),
+                json!({ "jsonrpc": "2.0", "method": "notifications/initialized" }),
+                json!({ "jsonrpc": "2.0", "id": 2, "method": "tools/list" }),
+            ],
+        );
+
+        assert_eq!(responses.len(), 2);
+        assert_eq!(responses[0]["result"]["protocolVersion"], PROTOCOL_VERSION);
+        assert_eq!(responses[1]["id"], 2);
+        assert_eq!(
+            responses[1]["result"]["tools"].as_array().unwrap().len(),
+            api::OPERATIONS.len()
+        );
+    }
+
+    // A supported version is echoed back; any other is answered with ours.
+    #[rstest]
+    #[case::supported("2025-06-18", "2025-06-18")]
+    #[case::unsupported("1999-01-01", PROTOCOL_VERSION)]
+    fn initialize_negotiates_version(#[case] requested: &str, #[case] expected: &str) {
+        let dir = TempDir::new().unwrap();
+        let responses = exchange(
+            dir.path(),
+            &[json!({ "jsonrpc": "2.0", "id": 1, "method": "initialize",
+                      "params": { "protocolVersion": requested } })],
+        );
+        assert_eq!(responses[0]["result"]["protocolVersion"], expected);
+    }
+
+    // A failing tool reports isError in its result; unknown methods and tools
+    // are protocol errors.
+    #[rstest]
+    #[case::tool_failure(json!({ "name": "goal_create", "arguments": {} }), "tools/call", None)]
+    #[case::unknown_tool(json!({ "name": "nope" }), "tools/call", Some(INVALID_PARAMS))]
+    #[case::unknown_method(json!({}), "resources/list", Some(METHOD_NOT_FOUND))]
+    fn serve_reports_errors(
+        #[case] params: Value,
+        #[case] method: &str,
+        #[case] code: Option<i64>,
+    ) {
+        let dir = TempDir::new().unwrap();
+        let responses = exchange(
+            dir.path(),
+            &[json!({ "jsonrpc": "2.0", "id": 7, "method": method, "params": params })],
+        );
+
+        match code {
+            Some(code) => assert_eq!(responses[0]["error"]["code"], code),
+            None => assert_eq!(responses[0]["result"]["isError"], true),
+        }
+    }
+}
@@ -0,0 +1,187 @@
+use serde::{Deserialize, Serialize};
+
+use super::Metrics;
+
+/// Spending limits for a goal and everything under it.
+///
+/// Each limit is optional. Once a goal has used up its token or time budget, no
+/// new work may start under it; a task may only be retried `max_retries` times.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct Budget {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    max_tokens: Option<i64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    max_elapsed_ms: Option<i64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    max_retries: Option<i64>,
+}
+
+impl Budget {
+    /// Share of a limit at which `rd status` starts warning, as a percentage.
+    pub const WARN_PERCENT: i64 = 80;
+
+    pub fn new(
+        max_tokens: Option<i64>,
+        max_elapsed_ms: Option<i64>,
+        max_retries: Option<i64>,
+    ) -> Self {
+        Self {
+            max_tokens,
+            max_elapsed_ms,
+            max_retries,
+        }
+    }
+
+    pub fn max_tokens(&self) -> Option<i64> {
+        self.max_tokens
+    }
+
+    pub fn max_elapsed_ms(&self) -> Option<i64> {
+        self.max_elapsed_ms
+    }
+
+    pub fn max_retries(&self) -> Option<i64> {
+        self.max_retries
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.max_tokens.is_none() && self.max_elapsed_ms.is_none() && self.max_retries.is_none()
+    }
+
+    /// Overlay the limits given in `changes`. A limit of 0 removes it.
+    pub fn merge(&mut self, changes: &Budget) {
+        fn apply(current: &mut Option<i64>, change: Option<i64>) {
+            if let Some(value) = change {
+                *current = (value > 0).then_some(value);
+            }
+        }
+        apply(&mut self.max_tokens, changes.max_tokens);
+        apply(&mut self.max_elapsed_ms, changes.max_elapsed_ms);
+        apply(&mut self.max_retries, changes.max_retries);
+    }
+
+    /// Describe each exhausted token or time limit.
+    pub fn exceeded(&self, metrics: &Metrics) -> Vec<String> {
+        self.usage(metrics)
+            .into_iter()
+            .filter(|(_, used, max)| used >= max)
+            .map(|(what, used, max)| format!("{what} budget exhausted: used {used} of {max}"))
+            .collect()
+    }
+
+    /// Describe each token or time limit that is at least `WARN_PERCENT` used.
+    pub fn warnings(&self, metrics: &Metrics) -> Vec<String> {
+        // Limits go up to i64::MAX, so the percentages are worked out in i128.
+        self.usage(metrics)
+            .into_iter()
+            .filter(|(_, used, max)| {
+                i128::from(*used) * 100 >= i128::from(*max) * i128::from(Self::WARN_PERCENT)
+            })
+            .map(|(what, used, max)| {
+                format!(
+                    "{what} budget at {}%: used {used} of {max}",
+                    i128::from(used) * 100 / i128::from(max)
+                )
+            })
+            .collect()
+    }
+
+    fn usage(&self, metrics: &Metrics) -> Vec<(&'static str, i64, i64)> {
+        let mut usage = Vec::new();
+        if let Some(max) = self.max_tokens {
+            usage.push(("Token", metrics.total_tokens(), max));
+        }
+        if let Some(max) = self.max_elapsed_ms {
+            usage.push(("Time (ms)", metrics.elapsed_ms(), max));
+        }
+        usage
+    }
+}
+
+impl std::fmt::Display for Budget {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut parts = Vec::new();
+        if let Some(max) = self.max_tokens {
+            parts.push(format!("{max} tokens"));
+        }
+        if let Some(max) = self.max_elapsed_ms {
+            parts.push(format!("{max}ms"));
+        }
+        if let Some(max) = self.max_retries {
+            let noun = if max == 1 { "retry" } else { "retries" };
+            parts.push(format!("{max} {noun} per task"));
+        }
+        if parts.is_empty() {
+            write!(f, "none")
+        } else {
+            write!(f, "{}", parts.join(", "))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    fn metrics(tokens: i64, elapsed_ms: i64) -> Metrics {
+        Metrics::new(tokens, 0, 0, elapsed_ms, 0, 0, 0)
+    }
+
+    // -- merge --
+
+    // Limits that are given replace the current ones, 0 removes a limit, and
+    // limits that aren't given are left alone.
+    #[rstest]
+    fn merge_overlays_changes() {
+        let mut budget = Budget::new(Some(1000), Some(5000), None);
+        budget.merge(&Budget::new(Some(2000), Some(0), Some(3)));
+        assert_eq!(budget, Budget::new(Some(2000), None, Some(3)));
+
+        budget.merge(&Budget::default());
+        assert_eq!(budget, Budget::new(Some(2000), None, Some(3)));
+    }
+
+    // -- exceeded / warnings --
+
+    // Warnings start at WARN_PERCENT of a limit; a limit only counts as
+    // exceeded once usage reaches it.
+    #[rstest]
+    #[case::well_under(700, 0, 0)]
+    #[case::near_limit(800, 1, 0)]
+    #[case::at_limit(1000, 1, 1)]
+    fn usage_against_token_limit(
+        #[case] tokens: i64,
+        #[case] warnings: usize,
+        #[case] exceeded: usize,
+    ) {
+        let budget = Budget::new(Some(1000), None, Some(1));
+        assert_eq!(budget.warnings(&metrics(tokens, 0)).len(), warnings);
+        assert_eq!(budget.exceeded(&metrics(tokens, 0)).len(), exceeded);
+    }
+
+    // The largest limit a goal accepts is compared without overflowing.
+    #[rstest]
+    #[case::barely_used(5, 0, 0)]
+    #[case::near_limit(i64::MAX - 1, 1, 0)]
+    #[case::at_limit(i64::MAX, 1, 1)]
+    fn usage_against_largest_limit(
+        #[case] tokens: i64,
+        #[case] warnings: usize,
+        #[case] exceeded: usize,
+    ) {
+        let budget = Budget::new(Some(i64::MAX), Some(i64::MAX), None);
+        assert_eq!(budget.warnings(&metrics(tokens, 0)).len(), warnings);
+        assert_eq!(budget.exceeded(&metrics(tokens, 0)).len(), exceeded);
+    }
+
+    // Time is checked independently of tokens, and retries never count as
+    // exceeded since they are enforced per task.
+    #[rstest]
+    fn time_limit_checked_separately() {
+        let budget = Budget::new(Some(1000), Some(60_000), Some(0));
+        let exceeded = budget.exceeded(&metrics(10, 60_000));
+        assert_eq!(exceeded.len(), 1);
+        assert!(exceeded[0].starts_with("Time"));
+    }
+}
@@ -6,6 +6,8 @@ pub struct Comment {
     id: String,
     text: String,
     created_at: Timestamp,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    agent: Option<String>,
 }
 
 impl Comment {
@@ -14,9 +16,16 @@ impl Comment {
             id,
             text,
             created_at,
+            agent: None,
         }
     }
 
+    #[must_use]
+    pub fn with_agent(mut self, agent: Option<String>) -> Self {
+        self.agent = agent;
+        self
+    }
+
     pub fn id(&self) -> &str {
         &self.id
     }
@@ -28,4 +37,8 @@ impl Comment {
     pub fn created_at(&self) -> Timestamp {
         self.created_at
     }
+
+    pub fn agent(&self) -> Option<&str> {
+        self.agent.as_deref()
+    }
 }
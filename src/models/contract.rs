@@ -5,6 +5,8 @@ pub struct Contract {
     receives: String,
     produces: String,
     verify: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    verify_cmd: Option<String>,
 }
 
 impl Contract {
@@ -13,9 +15,17 @@ impl Contract {
             receives,
             produces,
             verify,
+            verify_cmd: None,
         }
     }
 
+    /// Attach a shell command that checks the contract mechanically.
+    #[must_use]
+    pub fn with_verify_cmd(mut self, verify_cmd: Option<String>) -> Self {
+        self.verify_cmd = verify_cmd;
+        self
+    }
+
     pub fn receives(&self) -> &str {
         &self.receives
     }
@@ -27,4 +37,8 @@ impl Contract {
     pub fn verify(&self) -> &str {
         &self.verify
     }
+
+    pub fn verify_cmd(&self) -> Option<&str> {
+        self.verify_cmd.as_deref()
+    }
 }
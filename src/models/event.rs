@@ -0,0 +1,151 @@
+use jiff::Timestamp;
+use serde::{Deserialize, Serialize};
+use strum::AsRefStr;
+
+use super::{Goal, Lease, Task, TaskState};
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, AsRefStr)]
+#[serde(rename_all = "snake_case")]
+#[strum(serialize_all = "snake_case")]
+pub enum EventAction {
+    Created,
+    Transition,
+    Commented,
+    Updated,
+}
+
+/// One entry in a goal's append-only journal.
+///
+/// States are recorded as their display strings so goal and task events share
+/// one shape.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Event {
+    timestamp: Timestamp,
+    goal_id: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    task_id: Option<String>,
+    action: EventAction,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    from: Option<String>,
+    to: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    agent: Option<String>,
+}
+
+impl Event {
+    pub fn goal_created(goal: &Goal) -> Self {
+        Self {
+            timestamp: goal.created_at(),
+            goal_id: goal.id().to_owned(),
+            task_id: None,
+            action: EventAction::Created,
+            from: None,
+            to: goal.state().as_ref().to_owned(),
+            agent: None,
+        }
+    }
+
+    /// An event for a goal write: a transition when its state changed, an
+    /// update when its description, parent or budget did, and `None` for writes
+    /// that only refresh its metrics or timestamp.
+    pub fn goal_changed(before: &Goal, after: &Goal) -> Option<Self> {
+        let action = if before.state() != after.state() {
+            EventAction::Transition
+        } else if before.description() != after.description()
+            || before.parent_id() != after.parent_id()
+            || before.budget() != after.budget()
+        {
+            EventAction::Updated
+        } else {
+            return None;
+        };
+        Some(Self {
+            timestamp: after.updated_at(),
+            goal_id: after.id().to_owned(),
+            task_id: None,
+            action,
+            from: Some(before.state().as_ref().to_owned()),
+            to: after.state().as_ref().to_owned(),
+            agent: None,
+        })
+    }
+
+    pub fn task_created(task: &Task) -> Self {
+        Self {
+            timestamp: task.created_at(),
+            goal_id: task.goal_id().to_owned(),
+            task_id: Some(task.id().to_owned()),
+            action: EventAction::Created,
+            from: None,
+            to: task.state().as_ref().to_owned(),
+            agent: None,
+        }
+    }
+
+    /// An event describing what changed between two versions of a task.
+    ///
+    /// The acting agent is taken from whichever field the change recorded it
+    /// in: the new comment, the review, the outcome, `failed_by`, or `claimed_by`.
+    /// Other updates name the holder only when they renewed its lease; edits
+    /// such as `rd task update` record no agent.
+    pub fn task_changed(before: &Task, after: &Task) -> Self {
+        let (action, agent) = if before.state() != after.state() {
+            let agent = match after.state() {
+                TaskState::InProgress => after.claimed_by(),
+                TaskState::Verifying => after.result().and_then(|r| r.agent()),
+                TaskState::Completed => after
+                    .review()
+                    .and_then(|r| r.agent())
+                    .or_else(|| after.result().and_then(|r| r.agent())),
+                TaskState::Failed => after.failed_by(),
+                _ => None,
+            };
+            (EventAction::Transition, agent)
+        } else if after.comments().len() > before.comments().len() {
+            let agent = after.comments().last().and_then(|c| c.agent());
+            (EventAction::Commented, agent)
+        } else {
+            let renewed =
+                before.lease().map(Lease::expires_at) != after.lease().map(Lease::expires_at);
+            (EventAction::Updated, after.claimed_by().filter(|_| renewed))
+        };
+
+        Self {
+            timestamp: after.updated_at(),
+            goal_id: after.goal_id().to_owned(),
+            task_id: Some(after.id().to_owned()),
+            action,
+            from: Some(before.state().as_ref().to_owned()),
+            to: after.state().as_ref().to_owned(),
+            agent: agent.map(str::to_owned),
+        }
+    }
+
+    pub fn timestamp(&self) -> Timestamp {
+        self.timestamp
+    }
+
+    pub fn goal_id(&self) -> &str {
+        &self.goal_id
+    }
+
+    pub fn task_id(&self) -> Option<&str> {
+        self.task_id.as_deref()
+    }
+
+    pub fn action(&self) -> EventAction {
+        self.action
+    }
+
+    pub fn from(&self) -> Option<&str> {
+        self.from.as_deref()
+    }
+
+    pub fn to(&self) -> &str {
+        &self.to
+    }
+
+    pub fn agent(&self) -> Option<&str> {
+        self.agent.as_deref()
+    }
+}
@@ -7,6 +7,7 @@ use jiff::Timestamp;
 use serde::{Deserialize, Serialize};
 use strum::{AsRefStr, EnumString};
 
+use super::Budget;
 use crate::db::atomic_write;
 use crate::output::{Render, write_field};
 
@@ -20,7 +21,7 @@ pub enum GoalState {
     Failed,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
 pub struct Metrics {
     total_tokens: i64,
     prompt_tokens: i64,
@@ -29,6 +30,8 @@ pub struct Metrics {
     task_count: i64,
     tasks_completed: i64,
     tasks_failed: i64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    estimated_cost: Option<f64>,
 }
 
 impl Metrics {
@@ -49,9 +52,16 @@ impl Metrics {
             task_count,
             tasks_completed,
             tasks_failed,
+            estimated_cost: None,
         }
     }
 
+    #[must_use]
+    pub fn with_estimated_cost(mut self, estimated_cost: Option<f64>) -> Self {
+        self.estimated_cost = estimated_cost;
+        self
+    }
+
     pub fn total_tokens(&self) -> i64 {
         self.total_tokens
     }
@@ -79,6 +89,11 @@ impl Metrics {
     pub fn tasks_failed(&self) -> i64 {
         self.tasks_failed
     }
+
+    /// Estimated cost in US dollars of the tasks that reported a priced model.
+    pub fn estimated_cost(&self) -> Option<f64> {
+        self.estimated_cost
+    }
 }
 
 impl Render for Metrics {
@@ -88,7 +103,14 @@ impl Render for Metrics {
             "  Tasks: {} total, {} completed, {} failed",
             self.task_count, self.tasks_completed, self.tasks_failed
         )?;
-        writeln!(w, "  Tokens: {}", self.total_tokens)?;
+        writeln!(
+            w,
+            "  Tokens: {} ({} prompt, {} completion)",
+            self.total_tokens, self.prompt_tokens, self.completion_tokens
+        )?;
+        if let Some(cost) = self.estimated_cost {
+            writeln!(w, "  Estimated cost: ${cost:.4}")?;
+        }
         writeln!(w, "  Elapsed: {}ms", self.elapsed_ms)?;
         Ok(())
     }
@@ -106,6 +128,8 @@ pub struct Goal {
     #[serde(skip_serializing_if = "Option::is_none")]
     completed_at: Option<Timestamp>,
     metrics: Metrics,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    budget: Option<Budget>,
 }
 
 impl Goal {
@@ -129,9 +153,16 @@ impl Goal {
             updated_at,
             completed_at,
             metrics,
+            budget: None,
         }
     }
 
+    #[must_use]
+    pub fn with_budget(mut self, budget: Budget) -> Self {
+        self.budget = (!budget.is_empty()).then_some(budget);
+        self
+    }
+
     pub fn id(&self) -> &str {
         &self.id
     }
@@ -164,6 +195,34 @@ impl Goal {
         &self.metrics
     }
 
+    pub fn budget(&self) -> Option<&Budget> {
+        self.budget.as_ref()
+    }
+
+    /// Replace the stored metrics. Returns false if they are unchanged.
+    pub fn set_metrics(&mut self, metrics: Metrics) -> bool {
+        if self.metrics == metrics {
+            return false;
+        }
+        self.metrics = metrics;
+        self.updated_at = Timestamp::now();
+        true
+    }
+
+    /// Apply budget changes, where a limit of 0 removes it. Returns false if
+    /// nothing changed.
+    pub fn update_budget(&mut self, changes: &Budget) -> bool {
+        let mut budget = self.budget.clone().unwrap_or_default();
+        budget.merge(changes);
+        let budget = (!budget.is_empty()).then_some(budget);
+        if budget == self.budget {
+            return false;
+        }
+        self.budget = budget;
+        self.updated_at = Timestamp::now();
+        true
+    }
+
     pub fn touch(&mut self) {
         self.updated_at = Timestamp::now();
     }
@@ -171,6 +230,7 @@ impl Goal {
     pub fn mark_in_progress(&mut self) {
         self.state = GoalState::InProgress;
         self.updated_at = Timestamp::now();
+        self.completed_at = None;
     }
 
     pub fn mark_completed(&mut self) {
@@ -205,6 +265,9 @@ impl Render for Goal {
             style(self.state.as_ref()).yellow()
         )?;
         write_field(w, "  ", "Description", &self.description)?;
+        if let Some(budget) = &self.budget {
+            writeln!(w, "  Budget: {budget}")?;
+        }
         Ok(())
     }
 }
@@ -0,0 +1,45 @@
+use jiff::{SignedDuration, Timestamp};
+use serde::{Deserialize, Serialize};
+
+/// A time-limited claim on an in-progress task.
+///
+/// Holders extend the lease with heartbeats; once it expires the task can be
+/// reaped back to pending so another agent can pick it up.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Lease {
+    duration_secs: i64,
+    expires_at: Timestamp,
+}
+
+impl Lease {
+    pub const DEFAULT_DURATION_SECS: i64 = 30 * 60;
+
+    pub fn new(duration_secs: i64, now: Timestamp) -> Self {
+        Self {
+            duration_secs,
+            expires_at: expiry(now, duration_secs),
+        }
+    }
+
+    pub fn duration_secs(&self) -> i64 {
+        self.duration_secs
+    }
+
+    pub fn expires_at(&self) -> Timestamp {
+        self.expires_at
+    }
+
+    pub fn is_expired(&self, now: Timestamp) -> bool {
+        now >= self.expires_at
+    }
+
+    /// Push the expiry out by the lease duration, measured from `now`.
+    pub fn renew(&mut self, now: Timestamp) {
+        self.expires_at = expiry(now, self.duration_secs);
+    }
+}
+
+fn expiry(now: Timestamp, duration_secs: i64) -> Timestamp {
+    now.checked_add(SignedDuration::from_secs(duration_secs))
+        .unwrap_or(Timestamp::MAX)
+}
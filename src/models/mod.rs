@@ -1,11 +1,21 @@
+mod budget;
 mod comment;
 mod contract;
+mod event;
 mod goal;
+mod lease;
 mod outcome;
+mod review;
 mod task;
+mod verify_run;
 
+pub use budget::Budget;
 pub use comment::Comment;
 pub use contract::Contract;
+pub use event::{Event, EventAction};
 pub use goal::{Goal, GoalState, Metrics};
+pub use lease::Lease;
 pub use outcome::Outcome;
+pub use review::Review;
 pub use task::{Task, TaskMetrics, TaskState};
+pub use verify_run::VerifyRun;
@@ -4,11 +4,23 @@ use serde::{Deserialize, Serialize};
 pub struct Outcome {
     summary: String,
     artifacts: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    agent: Option<String>,
 }
 
 impl Outcome {
     pub fn new(summary: String, artifacts: Vec<String>) -> Self {
-        Self { summary, artifacts }
+        Self {
+            summary,
+            artifacts,
+            agent: None,
+        }
+    }
+
+    #[must_use]
+    pub fn with_agent(mut self, agent: Option<String>) -> Self {
+        self.agent = agent;
+        self
     }
 
     pub fn summary(&self) -> &str {
@@ -18,4 +30,8 @@ impl Outcome {
     pub fn artifacts(&self) -> &[String] {
         &self.artifacts
     }
+
+    pub fn agent(&self) -> Option<&str> {
+        self.agent.as_deref()
+    }
 }
@@ -0,0 +1,45 @@
+use jiff::Timestamp;
+use serde::{Deserialize, Serialize};
+
+/// A reviewer's verdict on a submitted task.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Review {
+    passed: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    note: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    agent: Option<String>,
+    reviewed_at: Timestamp,
+}
+
+impl Review {
+    pub fn new(
+        passed: bool,
+        note: Option<String>,
+        agent: Option<String>,
+        reviewed_at: Timestamp,
+    ) -> Self {
+        Self {
+            passed,
+            note,
+            agent,
+            reviewed_at,
+        }
+    }
+
+    pub fn passed(&self) -> bool {
+        self.passed
+    }
+
+    pub fn note(&self) -> Option<&str> {
+        self.note.as_deref()
+    }
+
+    pub fn agent(&self) -> Option<&str> {
+        self.agent.as_deref()
+    }
+
+    pub fn reviewed_at(&self) -> Timestamp {
+        self.reviewed_at
+    }
+}
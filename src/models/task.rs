@@ -7,7 +7,7 @@ use jiff::Timestamp;
 use serde::{Deserialize, Serialize};
 use strum::{AsRefStr, EnumString};
 
-use super::{Comment, Contract, Outcome};
+use super::{Comment, Contract, Lease, Outcome, Review, VerifyRun};
 use crate::db::atomic_write;
 use crate::output::{Render, write_field};
 
@@ -26,23 +26,62 @@ pub enum TaskState {
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct TaskMetrics {
     tokens: i64,
+    #[serde(default)]
+    prompt_tokens: i64,
+    #[serde(default)]
+    completion_tokens: i64,
     elapsed_ms: i64,
     retry_count: i64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    model: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    estimated_cost: Option<f64>,
 }
 
 impl TaskMetrics {
     pub fn new(tokens: i64, elapsed_ms: i64, retry_count: i64) -> Self {
         Self {
             tokens,
+            prompt_tokens: 0,
+            completion_tokens: 0,
             elapsed_ms,
             retry_count,
+            model: None,
+            estimated_cost: None,
         }
     }
 
+    #[must_use]
+    pub fn with_token_split(mut self, prompt_tokens: i64, completion_tokens: i64) -> Self {
+        self.prompt_tokens = prompt_tokens;
+        self.completion_tokens = completion_tokens;
+        self
+    }
+
+    #[must_use]
+    pub fn with_model(mut self, model: Option<String>) -> Self {
+        self.model = model;
+        self
+    }
+
+    #[must_use]
+    pub fn with_estimated_cost(mut self, estimated_cost: Option<f64>) -> Self {
+        self.estimated_cost = estimated_cost;
+        self
+    }
+
     pub fn tokens(&self) -> i64 {
         self.tokens
     }
 
+    pub fn prompt_tokens(&self) -> i64 {
+        self.prompt_tokens
+    }
+
+    pub fn completion_tokens(&self) -> i64 {
+        self.completion_tokens
+    }
+
     pub fn elapsed_ms(&self) -> i64 {
         self.elapsed_ms
     }
@@ -50,6 +89,36 @@ impl TaskMetrics {
     pub fn retry_count(&self) -> i64 {
         self.retry_count
     }
+
+    /// The model most recently reported for this task.
+    pub fn model(&self) -> Option<&str> {
+        self.model.as_deref()
+    }
+
+    /// Estimated cost in US dollars, priced per attempt from the local price table.
+    pub fn estimated_cost(&self) -> Option<f64> {
+        self.estimated_cost
+    }
+
+    /// Add another attempt's usage to these totals. The model is replaced only
+    /// when the attempt reports one. The retry count is the task's own and is
+    /// left as it is.
+    pub fn add(&mut self, other: TaskMetrics) {
+        self.tokens += other.tokens;
+        self.prompt_tokens += other.prompt_tokens;
+        self.completion_tokens += other.completion_tokens;
+        self.elapsed_ms += other.elapsed_ms;
+        if let Some(cost) = other.estimated_cost {
+            self.estimated_cost = Some(self.estimated_cost.unwrap_or(0.0) + cost);
+        }
+        if other.model.is_some() {
+            self.model = other.model;
+        }
+    }
+}
+
+fn default_priority() -> u8 {
+    Task::DEFAULT_PRIORITY
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -60,10 +129,23 @@ pub struct Task {
     #[serde(skip_serializing_if = "Option::is_none")]
     contract: Option<Contract>,
     state: TaskState,
+    /// 0 is the most urgent, `LOWEST_PRIORITY` the least.
+    #[serde(default = "default_priority")]
+    priority: u8,
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
     blocked_by: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    claimed_by: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    lease: Option<Lease>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    failed_by: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     result: Option<Outcome>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    review: Option<Review>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    verify_run: Option<VerifyRun>,
     created_at: Timestamp,
     updated_at: Timestamp,
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -74,6 +156,11 @@ pub struct Task {
 }
 
 impl Task {
+    /// Priority of tasks created without one.
+    pub const DEFAULT_PRIORITY: u8 = 2;
+    /// The least urgent priority, p3.
+    pub const LOWEST_PRIORITY: u8 = 3;
+
     #[allow(clippy::too_many_arguments)]
     pub fn new(
         id: String,
@@ -91,8 +178,14 @@ impl Task {
             description,
             contract,
             state,
+            priority: Self::DEFAULT_PRIORITY,
             blocked_by,
+            claimed_by: None,
+            lease: None,
+            failed_by: None,
             result: None,
+            review: None,
+            verify_run: None,
             created_at,
             updated_at,
             completed_at: None,
@@ -101,6 +194,12 @@ impl Task {
         }
     }
 
+    #[must_use]
+    pub fn with_priority(mut self, priority: u8) -> Self {
+        self.priority = priority;
+        self
+    }
+
     #[must_use]
     pub fn with_metrics(mut self, metrics: TaskMetrics) -> Self {
         self.metrics = metrics;
@@ -127,14 +226,41 @@ impl Task {
         self.state
     }
 
+    pub fn priority(&self) -> u8 {
+        self.priority
+    }
+
     pub fn blocked_by(&self) -> &[String] {
         &self.blocked_by
     }
 
+    /// The agent that most recently claimed this task.
+    pub fn claimed_by(&self) -> Option<&str> {
+        self.claimed_by.as_deref()
+    }
+
+    pub fn lease(&self) -> Option<&Lease> {
+        self.lease.as_ref()
+    }
+
+    pub fn failed_by(&self) -> Option<&str> {
+        self.failed_by.as_deref()
+    }
+
     pub fn result(&self) -> Option<&Outcome> {
         self.result.as_ref()
     }
 
+    /// The most recent verdict from `rd task verify`, if any.
+    pub fn review(&self) -> Option<&Review> {
+        self.review.as_ref()
+    }
+
+    /// The most recent run of the contract's verify command, if any.
+    pub fn verify_run(&self) -> Option<&VerifyRun> {
+        self.verify_run.as_ref()
+    }
+
     pub fn created_at(&self) -> Timestamp {
         self.created_at
     }
@@ -178,29 +304,174 @@ impl Task {
         if !from.contains(&self.state) {
             return false;
         }
+        if to != TaskState::InProgress {
+            self.lease = None;
+        }
         self.state = to;
         self.updated_at = Timestamp::now();
         true
     }
 
-    pub fn complete(&mut self, outcome: Outcome, metrics: TaskMetrics) -> bool {
+    /// Finish an in-progress task. `usage` is added to the task's metrics so that
+    /// spend from earlier failed attempts is kept.
+    pub fn complete(&mut self, outcome: Outcome, usage: TaskMetrics) -> bool {
         if self.state != TaskState::InProgress {
             return false;
         }
         self.state = TaskState::Completed;
+        self.lease = None;
         self.result = Some(outcome);
-        self.metrics = metrics;
+        self.review = None;
+        self.metrics.add(usage);
         let now = Timestamp::now();
         self.updated_at = now;
         self.completed_at = Some(now);
         true
     }
 
-    pub fn retry(&mut self) -> bool {
+    /// Hand an in-progress task to a reviewer, recording the claimed outcome and
+    /// adding `usage` to the task's metrics.
+    pub fn submit(&mut self, outcome: Outcome, usage: TaskMetrics) -> bool {
+        if self.state != TaskState::InProgress {
+            return false;
+        }
+        self.state = TaskState::Verifying;
+        self.lease = None;
+        self.result = Some(outcome);
+        self.review = None;
+        self.metrics.add(usage);
+        self.updated_at = Timestamp::now();
+        true
+    }
+
+    pub fn set_verify_run(&mut self, run: VerifyRun) {
+        self.verify_run = Some(run);
+        self.updated_at = Timestamp::now();
+    }
+
+    /// Record a reviewer's verdict on a verifying task, moving it to
+    /// Completed if it passed or Failed if not.
+    pub fn verify(&mut self, review: Review) -> bool {
+        if self.state != TaskState::Verifying {
+            return false;
+        }
+        let now = review.reviewed_at();
+        if review.passed() {
+            self.state = TaskState::Completed;
+            self.completed_at = Some(now);
+        } else {
+            self.state = TaskState::Failed;
+            self.failed_by = review.agent().map(str::to_owned);
+        }
+        self.review = Some(review);
+        self.updated_at = now;
+        true
+    }
+
+    pub fn set_description(&mut self, description: String) {
+        self.description = description;
+        self.updated_at = Timestamp::now();
+    }
+
+    pub fn set_priority(&mut self, priority: u8) {
+        self.priority = priority;
+        self.updated_at = Timestamp::now();
+    }
+
+    pub fn set_contract(&mut self, contract: Contract) {
+        self.contract = Some(contract);
+        self.updated_at = Timestamp::now();
+    }
+
+    /// Replace the blocker list. Tasks that have not been started move between
+    /// Pending and Blocked according to `blocked`; other states are kept.
+    pub fn set_blocked_by(&mut self, blocked_by: Vec<String>, blocked: bool) {
+        self.blocked_by = blocked_by;
+        if matches!(self.state, TaskState::Pending | TaskState::Blocked) {
+            self.state = if blocked {
+                TaskState::Blocked
+            } else {
+                TaskState::Pending
+            };
+        }
+        self.updated_at = Timestamp::now();
+    }
+
+    /// Move a pending task to in-progress under the given lease.
+    pub fn claim(&mut self, agent: Option<String>, lease: Lease) -> bool {
+        if !self.transition(TaskState::Pending, TaskState::InProgress) {
+            return false;
+        }
+        self.claimed_by = agent;
+        self.lease = Some(lease);
+        true
+    }
+
+    /// Mark an in-progress or verifying task as failed by `agent`, adding the
+    /// failed attempt's `usage` to the task's metrics.
+    pub fn fail(&mut self, agent: Option<String>, usage: TaskMetrics) -> bool {
+        if !self.transition_from_any(
+            &[TaskState::InProgress, TaskState::Verifying],
+            TaskState::Failed,
+        ) {
+            return false;
+        }
+        self.failed_by = agent;
+        self.metrics.add(usage);
+        true
+    }
+
+    /// Whether `agent` claimed, completed, failed, or commented on this task.
+    pub fn touched_by(&self, agent: &str) -> bool {
+        self.claimed_by.as_deref() == Some(agent)
+            || self.failed_by.as_deref() == Some(agent)
+            || self.result.as_ref().and_then(Outcome::agent) == Some(agent)
+            || self.comments.iter().any(|c| c.agent() == Some(agent))
+    }
+
+    /// Extend the lease of an in-progress task. Tasks claimed without a lease
+    /// get a default-length one.
+    pub fn heartbeat(&mut self, now: Timestamp) -> bool {
+        if self.state != TaskState::InProgress {
+            return false;
+        }
+        match self.lease {
+            Some(ref mut lease) => lease.renew(now),
+            None => self.lease = Some(Lease::new(Lease::DEFAULT_DURATION_SECS, now)),
+        }
+        self.updated_at = now;
+        true
+    }
+
+    /// Return an in-progress task with an expired lease to pending, unclaimed,
+    /// counting the abandoned attempt as a retry.
+    pub fn expire_lease(&mut self, now: Timestamp) -> bool {
+        if self.state != TaskState::InProgress
+            || !self.lease.as_ref().is_some_and(|l| l.is_expired(now))
+        {
+            return false;
+        }
+        self.state = TaskState::Pending;
+        self.claimed_by = None;
+        self.lease = None;
+        self.metrics.retry_count += 1;
+        self.updated_at = now;
+        true
+    }
+
+    /// Move a failed task back to in-progress under a new lease, so a retry
+    /// that is abandoned can be reaped like any other claim. The previous
+    /// attempt's outcome and review are dropped; the journal keeps them.
+    pub fn retry(&mut self, agent: Option<String>, lease: Lease) -> bool {
         if self.state != TaskState::Failed {
             return false;
         }
         self.state = TaskState::InProgress;
+        self.claimed_by = agent;
+        self.lease = Some(lease);
+        self.result = None;
+        self.review = None;
+        self.verify_run = None;
         self.metrics.retry_count += 1;
         self.updated_at = Timestamp::now();
         true
@@ -226,6 +497,7 @@ impl Render for Task {
             style(self.state.as_ref()).yellow()
         )?;
         write_field(w, "  ", "Description", &self.description)?;
+        writeln!(w, "  Priority: p{}", self.priority)?;
 
         match self.contract {
             Some(ref contract) => {
@@ -233,6 +505,9 @@ impl Render for Task {
                 write_field(w, "    ", "Receives", contract.receives())?;
                 write_field(w, "    ", "Produces", contract.produces())?;
                 write_field(w, "    ", "Verify", contract.verify())?;
+                if let Some(cmd) = contract.verify_cmd() {
+                    write_field(w, "    ", "Verify command", cmd)?;
+                }
             }
             None => {
                 writeln!(w, "  Contract: {}", style("(not set)").dim())?;
@@ -243,12 +518,43 @@ impl Render for Task {
             writeln!(w, "  Blocked by: {}", self.blocked_by.join(", "))?;
         }
 
+        if let Some(agent) = &self.claimed_by {
+            writeln!(w, "  Claimed by: {agent}")?;
+        }
+
+        if let Some(lease) = &self.lease {
+            writeln!(w, "  Lease expires: {}", lease.expires_at())?;
+        }
+
+        if let Some(agent) = &self.failed_by {
+            writeln!(w, "  Failed by: {agent}")?;
+        }
+
         if let Some(result) = &self.result {
             write_field(w, "  ", "Result", result.summary())?;
+            if let Some(agent) = result.agent() {
+                let label = if self.state == TaskState::Verifying {
+                    "Submitted by"
+                } else {
+                    "Completed by"
+                };
+                writeln!(w, "  {label}: {agent}")?;
+            }
             if !result.artifacts().is_empty() {
                 writeln!(w, "  Artifacts: {}", result.artifacts().join(", "))?;
             }
         }
+
+        if let Some(review) = &self.review {
+            let verdict = if review.passed() { "passed" } else { "failed" };
+            match review.agent() {
+                Some(agent) => writeln!(w, "  Review: {verdict} by {agent}")?,
+                None => writeln!(w, "  Review: {verdict}")?,
+            }
+            if let Some(note) = review.note() {
+                write_field(w, "    ", "Note", note)?;
+            }
+        }
         Ok(())
     }
 }
@@ -268,8 +574,14 @@ mod tests {
             description: "test task".to_string(),
             contract: None,
             state: TaskState::Pending,
+            priority: Task::DEFAULT_PRIORITY,
             blocked_by: Vec::new(),
+            claimed_by: None,
+            lease: None,
+            failed_by: None,
             result: None,
+            review: None,
+            verify_run: None,
             created_at: now,
             updated_at: now,
             completed_at: None,
@@ -366,7 +678,33 @@ mod tests {
         assert!(task.completed_at.is_some());
         assert_eq!(task.result.as_ref().unwrap().summary(), "done");
         assert_eq!(task.metrics.tokens, 100);
-        assert_eq!(task.metrics.retry_count, 1);
+        // An attempt's usage never changes how many times the task was retried.
+        assert_eq!(task.metrics.retry_count, 0);
+    }
+
+    // Usage from an earlier attempt should be kept when the task is retried
+    // and completed again, with the latest reported model winning.
+    #[rstest]
+    fn complete_accumulates_usage_across_attempts(mut task: Task) {
+        task.state = TaskState::InProgress;
+        let first = TaskMetrics::new(30, 100, 0)
+            .with_token_split(20, 10)
+            .with_model(Some("model-a".to_string()));
+        assert!(task.submit(Outcome::new("try".to_string(), Vec::new()), first));
+
+        task.state = TaskState::Failed;
+        assert!(task.retry(None, Lease::new(60, Timestamp::now())));
+        let second = TaskMetrics::new(50, 200, 0)
+            .with_token_split(40, 10)
+            .with_model(Some("model-b".to_string()));
+        assert!(task.complete(Outcome::new("done".to_string(), Vec::new()), second));
+
+        assert_eq!(task.metrics.tokens(), 80);
+        assert_eq!(task.metrics.prompt_tokens(), 60);
+        assert_eq!(task.metrics.completion_tokens(), 20);
+        assert_eq!(task.metrics.elapsed_ms(), 300);
+        assert_eq!(task.metrics.retry_count(), 1);
+        assert_eq!(task.metrics.model(), Some("model-b"));
     }
 
     // complete() is only valid from InProgress. Every other state should
@@ -384,17 +722,103 @@ mod tests {
         assert!(task.completed_at.is_none());
     }
 
+    // -- submit / verify --
+
+    // Submitting moves InProgress to Verifying with the outcome recorded,
+    // but does not complete the task.
+    #[rstest]
+    fn submit_moves_to_verifying(mut task: Task) {
+        task.state = TaskState::InProgress;
+        let outcome = Outcome::new("done".to_string(), Vec::new());
+        assert!(task.submit(outcome, TaskMetrics::default()));
+        assert_eq!(task.state, TaskState::Verifying);
+        assert!(task.completed_at.is_none());
+        assert_eq!(task.result.as_ref().unwrap().summary(), "done");
+    }
+
+    // submit() is only valid from InProgress.
+    #[rstest]
+    #[case::from_pending(TaskState::Pending)]
+    #[case::from_verifying(TaskState::Verifying)]
+    fn submit_rejects_non_in_progress(mut task: Task, #[case] state: TaskState) {
+        task.state = state;
+        let outcome = Outcome::new("done".to_string(), Vec::new());
+        assert!(!task.submit(outcome, TaskMetrics::default()));
+        assert_eq!(task.state, state);
+    }
+
+    // A passing review completes the task; a failing one fails it and
+    // records the reviewer as the failing agent.
+    #[rstest]
+    #[case::pass(true, TaskState::Completed)]
+    #[case::fail(false, TaskState::Failed)]
+    fn verify_applies_verdict(mut task: Task, #[case] passed: bool, #[case] expected: TaskState) {
+        task.state = TaskState::Verifying;
+        let review = Review::new(
+            passed,
+            Some("looked fine".to_string()),
+            Some("checker".to_string()),
+            Timestamp::now(),
+        );
+        assert!(task.verify(review));
+        assert_eq!(task.state, expected);
+        assert_eq!(task.completed_at.is_some(), passed);
+        assert_eq!(task.failed_by.is_some(), !passed);
+        assert_eq!(task.review.as_ref().unwrap().note(), Some("looked fine"));
+    }
+
+    // verify() is only valid from Verifying.
+    #[rstest]
+    fn verify_rejects_non_verifying(mut task: Task) {
+        task.state = TaskState::InProgress;
+        assert!(!task.verify(Review::new(true, None, None, Timestamp::now())));
+        assert_eq!(task.state, TaskState::InProgress);
+    }
+
     // -- retry --
 
-    // Retrying a failed task should move it back to InProgress and
-    // bump the retry counter.
+    // Retrying a failed task should move it back to InProgress under a
+    // lease and bump the retry counter.
     #[rstest]
     fn retry_increments_and_transitions(mut task: Task) {
         task.state = TaskState::Failed;
         task.metrics.retry_count = 2;
-        assert!(task.retry());
+        let now = Timestamp::now();
+        assert!(task.retry(Some("agent-2".to_string()), Lease::new(60, now)));
         assert_eq!(task.state, TaskState::InProgress);
         assert_eq!(task.metrics.retry_count, 3);
+        assert_eq!(task.claimed_by.as_deref(), Some("agent-2"));
+        assert!(task.lease.is_some());
+    }
+
+    // A retry starts a fresh attempt, so the rejected submission and its
+    // review don't show on the task any more.
+    #[rstest]
+    fn retry_clears_previous_attempt(mut task: Task) {
+        task.state = TaskState::InProgress;
+        let outcome =
+            Outcome::new("done".to_string(), Vec::new()).with_agent(Some("a1".to_string()));
+        assert!(task.submit(outcome, TaskMetrics::default()));
+        let review = Review::new(false, None, Some("a2".to_string()), Timestamp::now());
+        assert!(task.verify(review));
+
+        assert!(task.retry(Some("a1".to_string()), Lease::new(60, Timestamp::now())));
+        assert!(task.result.is_none());
+        assert!(task.review.is_none());
+        assert!(task.verify_run.is_none());
+    }
+
+    // A retried task whose agent goes quiet expires like any other claim.
+    #[rstest]
+    fn retried_task_lease_expires(mut task: Task) {
+        task.state = TaskState::Failed;
+        let now = Timestamp::now();
+        assert!(task.retry(None, Lease::new(60, now)));
+        let later = now
+            .checked_add(jiff::SignedDuration::from_secs(61))
+            .unwrap();
+        assert!(task.expire_lease(later));
+        assert_eq!(task.state, TaskState::Pending);
     }
 
     // retry() is only valid from Failed. Every other state should be rejected.
@@ -405,10 +829,152 @@ mod tests {
     #[case::from_blocked(TaskState::Blocked)]
     fn retry_rejects_non_failed(mut task: Task, #[case] state: TaskState) {
         task.state = state;
-        assert!(!task.retry());
+        assert!(!task.retry(None, Lease::new(60, Timestamp::now())));
         assert_eq!(task.state, state);
     }
 
+    // -- set_blocked_by --
+
+    // Changing blockers recomputes Pending/Blocked for unstarted tasks but
+    // never moves a task that is already underway.
+    #[rstest]
+    #[case::pending_to_blocked(TaskState::Pending, true, TaskState::Blocked)]
+    #[case::blocked_to_pending(TaskState::Blocked, false, TaskState::Pending)]
+    #[case::in_progress_kept(TaskState::InProgress, true, TaskState::InProgress)]
+    #[case::completed_kept(TaskState::Completed, false, TaskState::Completed)]
+    fn set_blocked_by_recomputes_state(
+        mut task: Task,
+        #[case] initial: TaskState,
+        #[case] blocked: bool,
+        #[case] expected: TaskState,
+    ) {
+        task.state = initial;
+        task.set_blocked_by(vec!["t_other".to_string()], blocked);
+        assert_eq!(task.state, expected);
+        assert_eq!(task.blocked_by, vec!["t_other".to_string()]);
+    }
+
+    // -- claim / heartbeat / expire_lease --
+
+    // Claiming a pending task moves it to InProgress and records the lease;
+    // any other state is rejected without touching the lease.
+    #[rstest]
+    #[case::from_pending(TaskState::Pending, true)]
+    #[case::from_blocked(TaskState::Blocked, false)]
+    #[case::from_in_progress(TaskState::InProgress, false)]
+    fn claim_only_from_pending(mut task: Task, #[case] state: TaskState, #[case] expected: bool) {
+        task.state = state;
+        let lease = Lease::new(60, Timestamp::now());
+        assert_eq!(task.claim(Some("agent-1".to_string()), lease), expected);
+        assert_eq!(task.lease.is_some(), expected);
+        assert_eq!(task.claimed_by.is_some(), expected);
+    }
+
+    // A heartbeat pushes the expiry forward by the lease duration.
+    #[rstest]
+    fn heartbeat_extends_lease(mut task: Task) {
+        let start = Timestamp::from_second(1_000_000).unwrap();
+        task.claim(None, Lease::new(60, start));
+
+        let later = Timestamp::from_second(1_000_050).unwrap();
+        assert!(task.heartbeat(later));
+        assert_eq!(
+            task.lease.as_ref().unwrap().expires_at(),
+            Timestamp::from_second(1_000_110).unwrap()
+        );
+    }
+
+    // Heartbeats are only meaningful while the task is in progress.
+    #[rstest]
+    fn heartbeat_rejects_non_in_progress(mut task: Task) {
+        assert!(!task.heartbeat(Timestamp::now()));
+        assert!(task.lease.is_none());
+    }
+
+    // An expired lease returns the task to Pending and counts as a retry;
+    // a live lease or a task without a lease is left alone.
+    #[rstest]
+    #[case::expired(60, true)]
+    #[case::live(59, false)]
+    fn expire_lease_checks_expiry(mut task: Task, #[case] elapsed: i64, #[case] expected: bool) {
+        let start = Timestamp::from_second(1_000_000).unwrap();
+        task.claim(Some("agent-1".to_string()), Lease::new(60, start));
+
+        let now = Timestamp::from_second(1_000_000 + elapsed).unwrap();
+        assert_eq!(task.expire_lease(now), expected);
+        if expected {
+            assert_eq!(task.state, TaskState::Pending);
+            assert!(task.lease.is_none());
+            assert!(task.claimed_by.is_none());
+            assert_eq!(task.metrics.retry_count, 1);
+        } else {
+            assert_eq!(task.state, TaskState::InProgress);
+            assert_eq!(task.metrics.retry_count, 0);
+        }
+    }
+
+    // Tasks claimed before leases existed have no expiry and are never reaped.
+    #[rstest]
+    fn expire_lease_ignores_unleased_tasks(mut task: Task) {
+        task.state = TaskState::InProgress;
+        assert!(!task.expire_lease(Timestamp::MAX));
+        assert_eq!(task.state, TaskState::InProgress);
+    }
+
+    // -- fail / touched_by --
+
+    // fail() records the failing agent and the attempt's usage alongside the
+    // Failed transition.
+    #[rstest]
+    fn fail_records_agent_and_usage(mut task: Task) {
+        task.state = TaskState::InProgress;
+        let usage = TaskMetrics::new(40, 900, 0).with_token_split(30, 10);
+        assert!(task.fail(Some("agent-1".to_string()), usage));
+        assert_eq!(task.state, TaskState::Failed);
+        assert_eq!(task.failed_by.as_deref(), Some("agent-1"));
+        assert_eq!(task.metrics.tokens(), 40);
+        assert_eq!(task.metrics.prompt_tokens(), 30);
+        assert_eq!(task.metrics.elapsed_ms(), 900);
+    }
+
+    // touched_by matches an agent on any recorded state change or comment.
+    #[rstest]
+    fn touched_by_checks_every_agent_field(mut task: Task) {
+        assert!(!task.touched_by("agent-1"));
+
+        task.add_comment(
+            Comment::new("c_1".to_string(), "hi".to_string(), Timestamp::now())
+                .with_agent(Some("agent-1".to_string())),
+        );
+        assert!(task.touched_by("agent-1"));
+
+        task.result = Some(
+            Outcome::new("done".to_string(), Vec::new()).with_agent(Some("agent-2".to_string())),
+        );
+        assert!(task.touched_by("agent-2"));
+        assert!(!task.touched_by("agent-3"));
+    }
+
+    // The summary render names the agents involved.
+    #[rstest]
+    fn render_includes_agents(mut task: Task) {
+        task.claimed_by = Some("maker".to_string());
+        task.state = TaskState::Completed;
+        task.result = Some(
+            Outcome::new("done".to_string(), Vec::new()).with_agent(Some("maker".to_string())),
+        );
+        let output = render_to_string(&task);
+        assert!(output.contains("Claimed by: maker"));
+        assert!(output.contains("Completed by: maker"));
+    }
+
+    // The summary render shows the priority in its p0-p3 form.
+    #[rstest]
+    fn render_includes_priority(task: Task) {
+        let output = render_to_string(&task.with_priority(0));
+        assert!(output.contains("Priority: p0"));
+    }
+
     // -- unblock --
 
     // Unblocking sets the task to Pending unconditionally and bumps updated_at.
@@ -467,6 +1033,13 @@ mod tests {
         assert!(output.contains("input data"));
         assert!(output.contains("output data"));
         assert!(output.contains("check output"));
+        assert!(!output.contains("Verify command"));
+
+        task.contract = task
+            .contract
+            .map(|c| c.with_verify_cmd(Some("cargo test".to_string())));
+        let output = render_to_string(&task);
+        assert!(output.contains("Verify command: cargo test"));
     }
 
     // Blocked tasks should show which task IDs they're waiting on.
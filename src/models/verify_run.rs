@@ -0,0 +1,84 @@
+use jiff::Timestamp;
+use serde::{Deserialize, Serialize};
+
+/// The captured result of running a contract's verify command.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VerifyRun {
+    command: String,
+    /// `None` when the process was killed (by timeout or a signal).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    exit_code: Option<i32>,
+    #[serde(default)]
+    timed_out: bool,
+    stdout: String,
+    stderr: String,
+    duration_ms: i64,
+    ran_at: Timestamp,
+}
+
+impl VerifyRun {
+    pub fn new(
+        command: String,
+        exit_code: Option<i32>,
+        timed_out: bool,
+        stdout: String,
+        stderr: String,
+        duration_ms: i64,
+        ran_at: Timestamp,
+    ) -> Self {
+        Self {
+            command,
+            exit_code,
+            timed_out,
+            stdout,
+            stderr,
+            duration_ms,
+            ran_at,
+        }
+    }
+
+    pub fn command(&self) -> &str {
+        &self.command
+    }
+
+    pub fn exit_code(&self) -> Option<i32> {
+        self.exit_code
+    }
+
+    pub fn timed_out(&self) -> bool {
+        self.timed_out
+    }
+
+    pub fn stdout(&self) -> &str {
+        &self.stdout
+    }
+
+    pub fn stderr(&self) -> &str {
+        &self.stderr
+    }
+
+    pub fn duration_ms(&self) -> i64 {
+        self.duration_ms
+    }
+
+    pub fn ran_at(&self) -> Timestamp {
+        self.ran_at
+    }
+
+    pub fn passed(&self) -> bool {
+        !self.timed_out && self.exit_code == Some(0)
+    }
+
+    /// One-line description of how the run ended.
+    pub fn summary(&self) -> String {
+        if self.timed_out {
+            format!("verify command timed out after {}ms", self.duration_ms)
+        } else {
+            match self.exit_code {
+                Some(0) => "verify command passed".to_owned(),
+                Some(code) => format!("verify command failed with exit code {code}"),
+                None => "verify command was terminated by a signal".to_owned(),
+            }
+        }
+    }
+}
@@ -1,13 +1,20 @@
 use std::io::{self, Write};
+use std::net::SocketAddr;
 
 use anyhow::Result;
 use console::{Term, style};
 use serde::Serialize;
 use textwrap::wrap;
 
+use crate::commands::check::GoalCheck;
+use crate::commands::handoff::Context;
+use crate::commands::next::Next;
+use crate::commands::ready::ReadyTask;
 use crate::commands::status::{GoalStatus, GoalSummary, StatusResult};
 use crate::commands::task::CompleteResult;
-use crate::models::{Goal, Task};
+use crate::commands::watch::{Change, ChangeKind};
+use crate::commands::why::Why;
+use crate::models::{Comment, Event, Goal, Task};
 
 /// Trait for types that can render themselves as human-readable CLI output.
 pub trait Render {
@@ -77,6 +84,74 @@ pub fn write_field_with_width(
     Ok(())
 }
 
+/// Write the `[timestamp] agent` line that precedes a comment's text.
+fn write_comment_header(w: &mut dyn Write, prefix: &str, comment: &Comment) -> Result<()> {
+    write!(w, "{prefix}[{}]", style(comment.created_at()).dim())?;
+    if let Some(agent) = comment.agent() {
+        write!(w, " {}", style(agent).magenta())?;
+    }
+    writeln!(w)?;
+    Ok(())
+}
+
+/// Order goals depth-first so every sub-goal follows its parent, paired with its depth.
+///
+/// Goals whose parent is not in `items` are shown as roots. Siblings keep their order.
+fn goal_tree<T>(items: &[T], goal: impl Fn(&T) -> &Goal) -> Vec<(usize, &T)> {
+    fn visit<'a, T>(
+        items: &'a [T],
+        goal: &impl Fn(&T) -> &Goal,
+        index: usize,
+        depth: usize,
+        seen: &mut [bool],
+        out: &mut Vec<(usize, &'a T)>,
+    ) {
+        if seen[index] {
+            return;
+        }
+        seen[index] = true;
+        out.push((depth, &items[index]));
+
+        let id = goal(&items[index]).id();
+        for (child, item) in items.iter().enumerate() {
+            if goal(item).parent_id() == Some(id) {
+                visit(items, goal, child, depth + 1, seen, out);
+            }
+        }
+    }
+
+    let mut seen = vec![false; items.len()];
+    let mut out = Vec::with_capacity(items.len());
+    for (index, item) in items.iter().enumerate() {
+        let is_root = goal(item)
+            .parent_id()
+            .is_none_or(|pid| !items.iter().any(|other| goal(other).id() == pid));
+        if is_root {
+            visit(items, &goal, index, 0, &mut seen, &mut out);
+        }
+    }
+    out
+}
+
+/// Run `render` and indent every non-empty line it writes by `depth` levels.
+fn write_indented(
+    w: &mut dyn Write,
+    depth: usize,
+    render: impl FnOnce(&mut dyn Write) -> Result<()>,
+) -> Result<()> {
+    let mut buf = Vec::new();
+    render(&mut buf)?;
+    let indent = "    ".repeat(depth);
+    for line in String::from_utf8_lossy(&buf).lines() {
+        if line.is_empty() {
+            writeln!(w)?;
+        } else {
+            writeln!(w, "{indent}{line}")?;
+        }
+    }
+    Ok(())
+}
+
 // -- Goal outputs --
 
 pub fn goal_created(goal: &Goal, json: bool) -> Result<()> {
@@ -87,11 +162,41 @@ pub fn goal_created(goal: &Goal, json: bool) -> Result<()> {
             style("Created goal:").green(),
             style(goal.id()).cyan().bold()
         )?;
+        if let Some(parent_id) = goal.parent_id() {
+            writeln!(w, "  Parent: {}", style(parent_id).cyan())?;
+        }
         write_field(w, "  ", "Description", goal.description())?;
+        if let Some(budget) = goal.budget() {
+            writeln!(w, "  Budget: {budget}")?;
+        }
         Ok(())
     })
 }
 
+pub fn goal_budget(goal: &Goal, json: bool) -> Result<()> {
+    json_or(goal, json, |w| {
+        writeln!(
+            w,
+            "{} {}",
+            style("Updated budget for goal:").green(),
+            style(goal.id()).cyan().bold()
+        )?;
+        match goal.budget() {
+            Some(budget) => writeln!(w, "  Budget: {budget}")?,
+            None => writeln!(w, "  Budget: none")?,
+        }
+        Ok(())
+    })
+}
+
+/// Write one warning line per budget limit that is nearly used up.
+fn write_budget_warnings(w: &mut dyn Write, prefix: &str, warnings: &[String]) -> Result<()> {
+    for warning in warnings {
+        writeln!(w, "{prefix}{} {warning}", style("Warning:").red().bold())?;
+    }
+    Ok(())
+}
+
 pub fn goal_list(goals: &[Goal], json: bool) -> Result<()> {
     json_or(goals, json, |w| {
         if goals.is_empty() {
@@ -99,8 +204,8 @@ pub fn goal_list(goals: &[Goal], json: bool) -> Result<()> {
             return Ok(());
         }
 
-        for goal in goals {
-            goal.render(w)?;
+        for (depth, goal) in goal_tree(goals, |g| g) {
+            write_indented(w, depth, |w| goal.render(w))?;
             writeln!(w)?;
         }
         Ok(())
@@ -151,7 +256,7 @@ pub fn task_list(tasks: &[Task], goal: &Goal, verbose: bool, json: bool) -> Resu
             if verbose && !task.comments().is_empty() {
                 writeln!(w, "  Comments: ({})", task.comments().len())?;
                 for comment in task.comments() {
-                    writeln!(w, "    [{}]", style(comment.created_at()).dim())?;
+                    write_comment_header(w, "    ", comment)?;
                     write_field(w, "    ", "", comment.text())?;
                 }
             }
@@ -161,20 +266,94 @@ pub fn task_list(tasks: &[Task], goal: &Goal, verbose: bool, json: bool) -> Resu
     })
 }
 
-pub fn task_started(task: &Task) -> Result<()> {
-    let mut w = io::stdout().lock();
-    writeln!(
-        w,
-        "{} {}",
-        style("Started task:").green(),
-        style(task.id()).cyan().bold()
-    )?;
-    write_field(&mut w, "  ", "Description", task.description())?;
+pub fn task_updated(task: &Task, json: bool) -> Result<()> {
+    json_or(task, json, |w| {
+        writeln!(w, "{}", style("Updated task:").green())?;
+        task.render(w)?;
+        Ok(())
+    })
+}
+
+/// `rd task start --json` prints the task with its handoff context alongside.
+#[derive(Serialize)]
+struct Started<'a> {
+    #[serde(flatten)]
+    task: &'a Task,
+    context: &'a Context,
+}
+
+pub fn task_started(task: &Task, context: &Context, json: bool) -> Result<()> {
+    json_or(&Started { task, context }, json, |w| {
+        writeln!(
+            w,
+            "{} {}",
+            style("Started task:").green(),
+            style(task.id()).cyan().bold()
+        )?;
+        write_field(w, "  ", "Description", task.description())?;
+        if let Some(lease) = task.lease() {
+            writeln!(w, "  Lease expires: {}", lease.expires_at())?;
+        }
+        write_context(w, context)
+    })
+}
+
+/// Write what a task's completed blockers handed on, if anything.
+fn write_context(w: &mut dyn Write, context: &Context) -> Result<()> {
+    if context.is_empty() {
+        return Ok(());
+    }
+
+    writeln!(w)?;
+    writeln!(w, "{}", style("Context from completed blockers:").bold())?;
+    for handoff in context.upstream() {
+        writeln!(
+            w,
+            "  {} {}",
+            style(handoff.task_id()).cyan(),
+            handoff.description()
+        )?;
+        if let Some(produces) = handoff.produces() {
+            write_field(w, "    ", "Produces", produces)?;
+        }
+        write_field(w, "    ", "Result", handoff.summary())?;
+        if let Some(agent) = handoff.agent() {
+            writeln!(w, "    Completed by: {agent}")?;
+        }
+        if !handoff.artifacts().is_empty() {
+            writeln!(w, "    Artifacts: {}", handoff.artifacts().join(", "))?;
+        }
+        for comment in handoff.comments() {
+            write_comment_header(w, "    ", comment)?;
+            write_field(w, "    ", "", comment.text())?;
+        }
+    }
+    for warning in context.warnings() {
+        writeln!(w, "  {} {warning}", style("Warning:").yellow().bold())?;
+    }
     Ok(())
 }
 
-pub fn task_completed(result: &CompleteResult) -> Result<()> {
-    let mut w = io::stdout().lock();
+pub fn task_heartbeat(task: &Task, json: bool) -> Result<()> {
+    json_or(task, json, |w| {
+        writeln!(
+            w,
+            "{} {}",
+            style("Extended lease on task:").green(),
+            style(task.id()).cyan().bold()
+        )?;
+        if let Some(lease) = task.lease() {
+            writeln!(w, "  Lease expires: {}", lease.expires_at())?;
+        }
+        Ok(())
+    })
+}
+
+pub fn task_completed(result: &CompleteResult, json: bool) -> Result<()> {
+    json_or(result, json, |w| write_completed(w, result))
+}
+
+fn write_completed(w: &mut dyn Write, result: &CompleteResult) -> Result<()> {
     writeln!(
         w,
         "{} {}",
@@ -182,7 +361,7 @@ pub fn task_completed(result: &CompleteResult) -> Result<()> {
         style(result.task.id()).cyan().bold()
     )?;
     if let Some(res) = result.task.result() {
-        write_field(&mut w, "  ", "Result", res.summary())?;
+        write_field(w, "  ", "Result", res.summary())?;
     }
 
     if !result.unblocked_task_ids.is_empty() {
@@ -195,29 +374,77 @@ pub fn task_completed(result: &CompleteResult) -> Result<()> {
     Ok(())
 }
 
-pub fn task_failed(task: &Task) -> Result<()> {
-    let mut w = io::stdout().lock();
-    writeln!(
-        w,
-        "{} {}",
-        style("Failed task:").red(),
-        style(task.id()).cyan().bold()
-    )?;
-    write_field(&mut w, "  ", "Description", task.description())?;
-    Ok(())
+pub fn task_submitted(task: &Task, json: bool) -> Result<()> {
+    json_or(task, json, |w| {
+        writeln!(
+            w,
+            "{} {}",
+            style("Submitted task for review:").green(),
+            style(task.id()).cyan().bold()
+        )?;
+        if let Some(res) = task.result() {
+            write_field(w, "  ", "Result", res.summary())?;
+        }
+        if let Some(contract) = task.contract() {
+            write_field(w, "  ", "Verify", contract.verify())?;
+            if let Some(cmd) = contract.verify_cmd() {
+                write_field(w, "  ", "Verify command", cmd)?;
+            }
+        }
+        Ok(())
+    })
 }
 
-pub fn task_retry(task: &Task) -> Result<()> {
-    let mut w = io::stdout().lock();
-    writeln!(
-        w,
-        "{} {}",
-        style("Retrying task:").yellow(),
-        style(task.id()).cyan().bold()
-    )?;
-    write_field(&mut w, "  ", "Description", task.description())?;
-    writeln!(w, "  Retry count: {}", task.metrics().retry_count())?;
-    Ok(())
+pub fn task_verified(result: &CompleteResult, json: bool) -> Result<()> {
+    json_or(result, json, |w| {
+        let passed = result.task.review().is_some_and(|r| r.passed());
+        if passed {
+            return write_completed(w, result);
+        }
+
+        writeln!(
+            w,
+            "{} {}",
+            style("Verification failed:").red(),
+            style(result.task.id()).cyan().bold()
+        )?;
+        if let Some(note) = result.task.review().and_then(|r| r.note()) {
+            write_field(w, "  ", "Note", note)?;
+        }
+        if let Some(run) = result.task.verify_run()
+            && !run.stderr().trim().is_empty()
+        {
+            write_field(w, "  ", "Stderr", run.stderr().trim_end())?;
+        }
+        Ok(())
+    })
+}
+
+pub fn task_failed(task: &Task, json: bool) -> Result<()> {
+    json_or(task, json, |w| {
+        writeln!(
+            w,
+            "{} {}",
+            style("Failed task:").red(),
+            style(task.id()).cyan().bold()
+        )?;
+        write_field(w, "  ", "Description", task.description())?;
+        Ok(())
+    })
+}
+
+pub fn task_retry(task: &Task, json: bool) -> Result<()> {
+    json_or(task, json, |w| {
+        writeln!(
+            w,
+            "{} {}",
+            style("Retrying task:").yellow(),
+            style(task.id()).cyan().bold()
+        )?;
+        write_field(w, "  ", "Description", task.description())?;
+        writeln!(w, "  Retry count: {}", task.metrics().retry_count())?;
+        Ok(())
+    })
 }
 
 pub fn task_commented(task: &Task, json: bool) -> Result<()> {
@@ -266,6 +493,9 @@ fn status_task(task: &Task, json: bool, concise: bool) -> Result<()> {
                 write_field(w, "  ", "Receives", contract.receives())?;
                 write_field(w, "  ", "Produces", contract.produces())?;
                 write_field(w, "  ", "Verify", contract.verify())?;
+                if let Some(cmd) = contract.verify_cmd() {
+                    write_field(w, "  ", "Verify command", cmd)?;
+                }
             }
             None => {
                 writeln!(w, "Contract: {}", style("(not set)").dim())?;
@@ -277,10 +507,29 @@ fn status_task(task: &Task, json: bool, concise: bool) -> Result<()> {
             writeln!(w, "Blocked by: {}", task.blocked_by().join(", "))?;
         }
 
+        if task.claimed_by().is_some() || task.lease().is_some() {
+            writeln!(w)?;
+            writeln!(w, "{}", style("Claim:").bold())?;
+            if let Some(agent) = task.claimed_by() {
+                writeln!(w, "  Agent: {agent}")?;
+            }
+            if let Some(lease) = task.lease() {
+                writeln!(w, "  Lease expires: {}", lease.expires_at())?;
+            }
+        }
+
+        if let Some(agent) = task.failed_by() {
+            writeln!(w)?;
+            writeln!(w, "Failed by: {agent}")?;
+        }
+
         if let Some(result) = task.result() {
             writeln!(w)?;
             writeln!(w, "{}", style("Result:").bold())?;
             write_field(w, "  ", "Summary", result.summary())?;
+            if let Some(agent) = result.agent() {
+                writeln!(w, "  Agent: {agent}")?;
+            }
             if !result.artifacts().is_empty() {
                 writeln!(w, "  Artifacts:")?;
                 for artifact in result.artifacts() {
@@ -289,9 +538,51 @@ fn status_task(task: &Task, json: bool, concise: bool) -> Result<()> {
             }
         }
 
+        if let Some(review) = task.review() {
+            writeln!(w)?;
+            writeln!(w, "{}", style("Review:").bold())?;
+            let verdict = if review.passed() { "passed" } else { "failed" };
+            writeln!(w, "  Verdict: {verdict}")?;
+            if let Some(agent) = review.agent() {
+                writeln!(w, "  Reviewer: {agent}")?;
+            }
+            if let Some(note) = review.note() {
+                write_field(w, "  ", "Note", note)?;
+            }
+            writeln!(w, "  Reviewed: {}", review.reviewed_at())?;
+        }
+
+        if let Some(run) = task.verify_run() {
+            writeln!(w)?;
+            writeln!(w, "{}", style("Verify run:").bold())?;
+            write_field(w, "  ", "Command", run.command())?;
+            writeln!(w, "  Outcome: {}", run.summary())?;
+            writeln!(w, "  Ran: {} ({}ms)", run.ran_at(), run.duration_ms())?;
+            if !concise {
+                if !run.stdout().trim().is_empty() {
+                    write_field(w, "  ", "Stdout", run.stdout().trim_end())?;
+                }
+                if !run.stderr().trim().is_empty() {
+                    write_field(w, "  ", "Stderr", run.stderr().trim_end())?;
+                }
+            }
+        }
+
         writeln!(w)?;
         writeln!(w, "{}", style("Metrics:").bold())?;
-        writeln!(w, "  Tokens: {}", task.metrics().tokens())?;
+        writeln!(
+            w,
+            "  Tokens: {} ({} prompt, {} completion)",
+            task.metrics().tokens(),
+            task.metrics().prompt_tokens(),
+            task.metrics().completion_tokens()
+        )?;
+        if let Some(model) = task.metrics().model() {
+            writeln!(w, "  Model: {model}")?;
+        }
+        if let Some(cost) = task.metrics().estimated_cost() {
+            writeln!(w, "  Estimated cost: ${cost:.4}")?;
+        }
         writeln!(w, "  Elapsed: {}ms", task.metrics().elapsed_ms())?;
         writeln!(w, "  Retries: {}", task.metrics().retry_count())?;
 
@@ -299,7 +590,7 @@ fn status_task(task: &Task, json: bool, concise: bool) -> Result<()> {
             writeln!(w)?;
             writeln!(w, "{}", style("Comments:").bold())?;
             for comment in task.comments() {
-                writeln!(w, "  [{}]", style(comment.created_at()).dim())?;
+                write_comment_header(w, "  ", comment)?;
                 write_field(w, "  ", "", comment.text())?;
             }
         }
@@ -319,7 +610,14 @@ fn status_goal(goal_status: &GoalStatus, json: bool) -> Result<()> {
             style(goal.id()).cyan().bold(),
             style(goal.state().as_ref()).yellow()
         )?;
+        if let Some(parent_id) = goal.parent_id() {
+            writeln!(w, "  Parent: {}", style(parent_id).cyan())?;
+        }
         write_field(w, "  ", "Description", goal.description())?;
+        if let Some(budget) = goal.budget() {
+            writeln!(w, "  Budget: {budget}")?;
+        }
+        write_budget_warnings(w, "  ", goal_status.budget_warnings())?;
         writeln!(w, "  Created: {}", goal.created_at())?;
         writeln!(w, "  Updated: {}", goal.updated_at())?;
         if let Some(completed_at) = &goal.completed_at() {
@@ -330,6 +628,20 @@ fn status_goal(goal_status: &GoalStatus, json: bool) -> Result<()> {
         writeln!(w, "{}", style("Metrics:").bold())?;
         metrics.render(w)?;
 
+        if !goal_status.sub_goals().is_empty() {
+            writeln!(w)?;
+            writeln!(w, "{}", style("Sub-goals:").bold())?;
+            for sub_goal in goal_status.sub_goals() {
+                writeln!(
+                    w,
+                    "  {} [{}] - {}",
+                    style(sub_goal.id()).cyan(),
+                    style(sub_goal.state().as_ref()).yellow(),
+                    sub_goal.description()
+                )?;
+            }
+        }
+
         if !goal_status.tasks().is_empty() {
             writeln!(w)?;
             writeln!(w, "{}", style("Tasks:").bold())?;
@@ -356,18 +668,24 @@ fn status_all_goals(summaries: &[GoalSummary], json: bool) -> Result<()> {
 
         writeln!(w, "{}\n", style("All Goals:").bold())?;
 
-        for summary in summaries {
+        for (depth, summary) in goal_tree(summaries, GoalSummary::goal) {
             let goal = &summary.goal();
             let metrics = &summary.computed_metrics();
 
-            writeln!(
-                w,
-                "{} [{}]",
-                style(goal.id()).cyan().bold(),
-                style(goal.state().as_ref()).yellow()
-            )?;
-            write_field(w, "  ", "Description", goal.description())?;
-            metrics.render(w)?;
+            write_indented(w, depth, |w| {
+                writeln!(
+                    w,
+                    "{} [{}]",
+                    style(goal.id()).cyan().bold(),
+                    style(goal.state().as_ref()).yellow()
+                )?;
+                write_field(w, "  ", "Description", goal.description())?;
+                if let Some(budget) = goal.budget() {
+                    writeln!(w, "  Budget: {budget}")?;
+                }
+                write_budget_warnings(w, "  ", summary.budget_warnings())?;
+                metrics.render(w)
+            })?;
             writeln!(w)?;
         }
         Ok(())
@@ -408,6 +726,265 @@ pub fn ready_tasks(tasks: &[Task], goal: &Goal, json: bool) -> Result<()> {
     })
 }
 
+pub fn ready_tasks_all_goals(tasks: &[ReadyTask], json: bool) -> Result<()> {
+    json_or(tasks, json, |w| {
+        if tasks.is_empty() {
+            writeln!(w, "No tasks ready to start in any goal.")?;
+            return Ok(());
+        }
+
+        writeln!(w, "{} task(s) ready:\n", style(tasks.len()).green().bold())?;
+
+        for ready in tasks {
+            let task = ready.task();
+            writeln!(w, "{}", style(task.id()).cyan().bold())?;
+            write_field(
+                w,
+                "  ",
+                "Goal",
+                &format!("{} ({})", task.goal_id(), ready.goal_description()),
+            )?;
+            write_field(w, "  ", "Description", task.description())?;
+            if let Some(contract) = task.contract() {
+                write_field(w, "  ", "Receives", contract.receives())?;
+                write_field(w, "  ", "Produces", contract.produces())?;
+                write_field(w, "  ", "Verify", contract.verify())?;
+            }
+            writeln!(w)?;
+        }
+        Ok(())
+    })
+}
+
+// -- Next --
+
+pub fn next(next: &Next, json: bool) -> Result<()> {
+    json_or(next, json, |w| match next {
+        Next::Claimed { task, context } => {
+            writeln!(w, "{}", style("Claimed task:").green())?;
+            task.render(w)?;
+            write_context(w, context)
+        }
+        Next::NothingReady { goal_id } => {
+            writeln!(w, "No tasks ready in goal {goal_id}. Try again later.")?;
+            Ok(())
+        }
+        Next::GoalDone { goal_id } => {
+            writeln!(w, "Goal {goal_id} is completed. Nothing left to do.")?;
+            Ok(())
+        }
+    })
+}
+
+// -- Why --
+
+pub fn why(why: &Why, json: bool) -> Result<()> {
+    json_or(why, json, |w| {
+        writeln!(
+            w,
+            "Task: {} [{}]",
+            style(why.task_id()).cyan().bold(),
+            style(why.state().as_ref()).yellow()
+        )?;
+
+        if why.ready() {
+            writeln!(w, "{}", style("Ready to start.").green())?;
+            return Ok(());
+        }
+
+        writeln!(w)?;
+        writeln!(w, "{}", style("Not ready because:").bold())?;
+        for reason in why.reasons() {
+            let mut lines = reason.lines();
+            writeln!(w, "  - {}", lines.next().unwrap_or_default())?;
+            for line in lines {
+                writeln!(w, "    {line}")?;
+            }
+        }
+
+        if !why.blockers().is_empty() {
+            writeln!(w)?;
+            writeln!(w, "{}", style("Blocking chain:").bold())?;
+            for blocker in why.blockers() {
+                write_indented(w, blocker.depth(), |w| {
+                    let state = blocker.state();
+                    writeln!(
+                        w,
+                        "{} [{}]",
+                        style(blocker.task_id()).cyan(),
+                        style(state.as_ref().map_or("missing", AsRef::as_ref)).yellow()
+                    )?;
+                    if let Some(description) = blocker.description() {
+                        write_field(w, "  ", "Description", description)?;
+                    }
+                    if let Some(agent) = blocker.claimed_by() {
+                        writeln!(w, "  Held by: {agent}")?;
+                    }
+                    Ok(())
+                })?;
+            }
+        }
+        Ok(())
+    })
+}
+
+// -- Check --
+
+pub fn check(checks: &[GoalCheck], json: bool) -> Result<()> {
+    json_or(checks, json, |w| {
+        if checks.is_empty() {
+            writeln!(w, "No goals found.")?;
+            return Ok(());
+        }
+
+        for check in checks {
+            if check.issues().is_empty() {
+                writeln!(
+                    w,
+                    "{} {}",
+                    style(check.goal_id()).cyan().bold(),
+                    style("ok").green()
+                )?;
+                continue;
+            }
+
+            writeln!(
+                w,
+                "{} {}",
+                style(check.goal_id()).cyan().bold(),
+                style(format!("{} problem(s)", check.issues().len())).red()
+            )?;
+            for issue in check.issues() {
+                write_field(w, "  ", "-", &issue.to_string())?;
+            }
+        }
+        Ok(())
+    })
+}
+
+// -- Log --
+
+pub fn events(events: &[Event], json: bool) -> Result<()> {
+    json_or(events, json, |w| {
+        if events.is_empty() {
+            writeln!(w, "No events found.")?;
+            return Ok(());
+        }
+
+        for event in events {
+            let subject = match event.task_id() {
+                Some(task_id) => format!("task {task_id}"),
+                None => format!("goal {}", event.goal_id()),
+            };
+            write!(
+                w,
+                "[{}] {} {}",
+                style(event.timestamp()).dim(),
+                style(subject).cyan(),
+                event.action().as_ref()
+            )?;
+            match event.from() {
+                Some(from) if from != event.to() => {
+                    write!(w, " {} -> {}", from, style(event.to()).yellow())?;
+                }
+                _ => write!(w, " [{}]", style(event.to()).yellow())?,
+            }
+            if let Some(agent) = event.agent() {
+                write!(w, " by {}", style(agent).magenta())?;
+            }
+            writeln!(w)?;
+        }
+        Ok(())
+    })
+}
+
+// -- Watch --
+
+/// Print one change as a line of its own, flushed so consumers see it at once.
+pub fn change(change: &Change, json: bool) -> Result<()> {
+    let mut w = io::stdout().lock();
+    if json {
+        serde_json::to_writer(&mut w, change)?;
+    } else {
+        let kind = change.kind();
+        let kind = match kind {
+            ChangeKind::Completed | ChangeKind::Unblocked => style(kind.as_ref()).green(),
+            ChangeKind::Failed => style(kind.as_ref()).red(),
+            _ => style(kind.as_ref()).yellow(),
+        };
+        write!(
+            w,
+            "[{}] {} {kind}: {}",
+            style(change.timestamp()).dim(),
+            style(format!("task {}", change.task_id())).cyan(),
+            change.description()
+        )?;
+        if let Some(agent) = change.agent() {
+            write!(w, " by {}", style(agent).magenta())?;
+        }
+        if let Some(comment) = change.comment() {
+            write!(w, "\n    {comment}")?;
+        }
+    }
+    writeln!(w)?;
+    w.flush()?;
+    Ok(())
+}
+
+// -- Reap --
+
+pub fn reaped_tasks(tasks: &[Task], json: bool) -> Result<()> {
+    json_or(tasks, json, |w| {
+        if tasks.is_empty() {
+            writeln!(w, "No expired leases.")?;
+            return Ok(());
+        }
+
+        writeln!(
+            w,
+            "{} task(s) returned to pending:\n",
+            style(tasks.len()).yellow().bold()
+        )?;
+        for task in tasks {
+            writeln!(
+                w,
+                "{} (retry {})",
+                style(task.id()).cyan().bold(),
+                task.metrics().retry_count()
+            )?;
+            write_field(w, "  ", "Description", task.description())?;
+        }
+        Ok(())
+    })
+}
+
+// -- Config --
+
+pub fn config_value(value: &str) -> Result<()> {
+    let mut w = io::stdout().lock();
+    writeln!(w, "{value}")?;
+    Ok(())
+}
+
+pub fn config_set(key: &str, value: &str) -> Result<()> {
+    let mut w = io::stdout().lock();
+    writeln!(w, "{} {key} = {value}", style("Set").green())?;
+    Ok(())
+}
+
+pub fn config_list(entries: &[(&str, String)], json: bool) -> Result<()> {
+    let map: serde_json::Map<String, serde_json::Value> = entries
+        .iter()
+        .map(|(key, value)| ((*key).to_owned(), serde_json::Value::from(value.as_str())))
+        .collect();
+    json_or(&map, json, |w| {
+        for (key, value) in entries {
+            writeln!(w, "{} = {value}", style(key).cyan())?;
+        }
+        Ok(())
+    })
+}
+
 // -- Prep --
 
 pub fn prep(text: &str) -> Result<()> {
@@ -416,6 +993,19 @@ pub fn prep(text: &str) -> Result<()> {
     Ok(())
 }
 
+pub fn serving(addr: SocketAddr) -> Result<()> {
+    let mut w = io::stdout().lock();
+    writeln!(w, "{} http://{addr}", style("Listening on").green())?;
+    w.flush()?;
+    Ok(())
+}
+
+pub fn prompt(text: &str) -> Result<()> {
+    let mut w = io::stdout().lock();
+    write!(w, "{text}")?;
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -519,4 +1109,50 @@ mod tests {
         assert!(out.starts_with("X:\n"));
         assert!(out.contains("a very long string"));
     }
+
+    // -- goal_tree --
+
+    fn goal(id: &str, parent_id: Option<&str>) -> Goal {
+        let now = jiff::Timestamp::now();
+        Goal::new(
+            id.to_string(),
+            parent_id.map(str::to_string),
+            "goal".to_string(),
+            crate::models::GoalState::Pending,
+            now,
+            now,
+            None,
+            crate::models::Metrics::default(),
+        )
+    }
+
+    // Sub-goals should follow their parent one level deeper, siblings should
+    // keep their input order, and goals whose parent is missing become roots.
+    #[rstest]
+    fn goal_tree_nests_sub_goals() {
+        let goals = vec![
+            goal("b1", Some("a")),
+            goal("a", None),
+            goal("c", None),
+            goal("b2", Some("a")),
+            goal("d", Some("b1")),
+            goal("orphan", Some("gone")),
+        ];
+
+        let tree: Vec<(usize, &str)> = goal_tree(&goals, |g| g)
+            .into_iter()
+            .map(|(depth, g)| (depth, g.id()))
+            .collect();
+        assert_eq!(
+            tree,
+            vec![
+                (0, "a"),
+                (1, "b1"),
+                (2, "d"),
+                (1, "b2"),
+                (0, "c"),
+                (0, "orphan"),
+            ]
+        );
+    }
 }
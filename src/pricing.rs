@@ -0,0 +1,138 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::models::TaskMetrics;
+
+pub const PRICING_FILE: &str = "pricing.toml";
+
+/// Price of one model, in US dollars per million tokens.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ModelPrice {
+    #[serde(default)]
+    prompt_per_million: f64,
+    #[serde(default)]
+    completion_per_million: f64,
+}
+
+impl ModelPrice {
+    pub fn new(prompt_per_million: f64, completion_per_million: f64) -> Self {
+        Self {
+            prompt_per_million,
+            completion_per_million,
+        }
+    }
+
+    pub fn prompt_per_million(&self) -> f64 {
+        self.prompt_per_million
+    }
+
+    pub fn completion_per_million(&self) -> f64 {
+        self.completion_per_million
+    }
+}
+
+/// The local price table in `.radial/pricing.toml`:
+///
+/// ```toml
+/// [pricing."model-name"]
+/// prompt_per_million = 3.0
+/// completion_per_million = 15.0
+/// ```
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Pricing {
+    #[serde(default)]
+    pricing: HashMap<String, ModelPrice>,
+}
+
+impl Pricing {
+    /// Read the price table from a `.radial/` directory. A missing file is an
+    /// empty table.
+    pub fn load(dir: &Path) -> Result<Self> {
+        let path = dir.join(PRICING_FILE);
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+
+        let content = fs::read_to_string(&path)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        toml::from_str(&content).with_context(|| format!("Failed to parse {}", path.display()))
+    }
+
+    pub fn price(&self, model: &str) -> Option<&ModelPrice> {
+        self.pricing.get(model)
+    }
+
+    /// Estimate the cost in US dollars of one attempt's usage. Returns `None`
+    /// when the usage names no model, the model has no price, or the usage has
+    /// no prompt/completion split to price.
+    #[allow(clippy::cast_precision_loss)]
+    pub fn estimate(&self, usage: &TaskMetrics) -> Option<f64> {
+        let price = self.price(usage.model()?)?;
+        if usage.prompt_tokens() == 0 && usage.completion_tokens() == 0 {
+            return None;
+        }
+        let cost = usage.prompt_tokens() as f64 * price.prompt_per_million
+            + usage.completion_tokens() as f64 * price.completion_per_million;
+        Some(cost / 1_000_000.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+    use tempfile::TempDir;
+
+    // -- load --
+
+    // A missing price table is empty; a present one is parsed by model name.
+    #[rstest]
+    fn load_reads_price_table() {
+        let dir = TempDir::new().unwrap();
+        assert!(Pricing::load(dir.path()).unwrap().price("m").is_none());
+
+        fs::write(
+            dir.path().join(PRICING_FILE),
+            "[pricing.\"big-model\"]\nprompt_per_million = 3.0\ncompletion_per_million = 15.0\n",
+        )
+        .unwrap();
+        let pricing = Pricing::load(dir.path()).unwrap();
+        assert_eq!(
+            pricing.price("big-model"),
+            Some(&ModelPrice::new(3.0, 15.0))
+        );
+    }
+
+    // -- estimate --
+
+    // Prompt and completion tokens are priced separately; usage without a
+    // known model or without the split has no estimate.
+    #[rstest]
+    #[case::priced(Some("big-model"), (1000, 100), Some(0.0045))]
+    #[case::unknown_model(Some("other"), (1000, 100), None)]
+    #[case::no_model(None, (1000, 100), None)]
+    #[case::no_split(Some("big-model"), (0, 0), None)]
+    fn estimate_prices_usage(
+        #[case] model: Option<&str>,
+        #[case] split: (i64, i64),
+        #[case] expected: Option<f64>,
+    ) {
+        let mut pricing = Pricing::default();
+        pricing
+            .pricing
+            .insert("big-model".to_string(), ModelPrice::new(3.0, 15.0));
+
+        let usage = TaskMetrics::new(1000, 0, 0)
+            .with_token_split(split.0, split.1)
+            .with_model(model.map(str::to_string));
+        let cost = pricing.estimate(&usage);
+        match (cost, expected) {
+            (Some(cost), Some(expected)) => assert!((cost - expected).abs() < 1e-12),
+            (cost, expected) => assert_eq!(cost, expected),
+        }
+    }
+}
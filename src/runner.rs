@@ -0,0 +1,224 @@
+use std::collections::VecDeque;
+use std::io::Read;
+use std::path::Path;
+use std::process::{Child, Command, Stdio};
+use std::sync::mpsc::{self, Receiver};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use anyhow::{Context, Result};
+use jiff::Timestamp;
+
+use crate::models::VerifyRun;
+
+/// Captured output beyond this many bytes is truncated from the front, keeping
+/// the tail where test failures are usually reported.
+const MAX_CAPTURE_BYTES: usize = 64 * 1024;
+
+const POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+/// How long to wait for the output pipes to close once the command has exited.
+/// A background process it left behind can hold them open indefinitely.
+const DRAIN_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Run a shell command in `dir`, killing it if it outlives `timeout`.
+pub fn run_verify_command(command: &str, dir: &Path, timeout: Duration) -> Result<VerifyRun> {
+    let ran_at = Timestamp::now();
+    let started = Instant::now();
+
+    let mut child = shell(command)
+        .current_dir(dir)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .with_context(|| format!("Failed to run verify command: {command}"))?;
+
+    // Drain both pipes on their own threads so a chatty command can't block
+    // on a full pipe while we wait for it.
+    let stdout = child.stdout.take().map(capture);
+    let stderr = child.stderr.take().map(capture);
+
+    let mut timed_out = false;
+    let status = loop {
+        if let Some(status) = child
+            .try_wait()
+            .context("Failed to wait on verify command")?
+        {
+            break status;
+        }
+        if started.elapsed() >= timeout {
+            timed_out = true;
+            kill_tree(&mut child);
+            break child.wait().context("Failed to wait on verify command")?;
+        }
+        thread::sleep(POLL_INTERVAL);
+    };
+
+    let duration_ms = i64::try_from(started.elapsed().as_millis()).unwrap_or(i64::MAX);
+    let drain_deadline = Instant::now() + DRAIN_TIMEOUT;
+    let join = |capture: Option<Capture>| {
+        capture
+            .map(|c| c.finish(drain_deadline))
+            .unwrap_or_default()
+    };
+
+    Ok(VerifyRun::new(
+        command.to_owned(),
+        if timed_out { None } else { status.code() },
+        timed_out,
+        join(stdout),
+        join(stderr),
+        duration_ms,
+        ran_at,
+    ))
+}
+
+#[cfg(windows)]
+fn shell(command: &str) -> Command {
+    let mut cmd = Command::new("cmd");
+    cmd.args(["/C", command]);
+    cmd
+}
+
+#[cfg(windows)]
+fn kill_tree(child: &mut Child) {
+    // The process may exit between try_wait and kill; either way wait() reaps it.
+    let _ = child.kill();
+}
+
+/// Runs the command in its own process group so a timeout can kill everything
+/// it started, not just the shell.
+#[cfg(not(windows))]
+fn shell(command: &str) -> Command {
+    use std::os::unix::process::CommandExt;
+
+    let mut cmd = Command::new("sh");
+    cmd.args(["-c", command]).process_group(0);
+    cmd
+}
+
+#[cfg(not(windows))]
+fn kill_tree(child: &mut Child) {
+    let group = format!("-{}", child.id());
+    let killed_group = Command::new("kill")
+        .args(["-KILL", "--", &group])
+        .stderr(Stdio::null())
+        .status()
+        .is_ok_and(|s| s.success());
+    if !killed_group {
+        let _ = child.kill();
+    }
+}
+
+/// Output read from one pipe so far, and a signal for when the pipe closed.
+struct Capture {
+    tail: Arc<Mutex<VecDeque<u8>>>,
+    closed: Receiver<()>,
+}
+
+impl Capture {
+    /// Wait until the pipe closes or `deadline` passes, then take whatever was
+    /// read. The reader thread is left behind if the pipe is still open.
+    fn finish(self, deadline: Instant) -> String {
+        let _ = self
+            .closed
+            .recv_timeout(deadline.saturating_duration_since(Instant::now()));
+        let tail = self.tail.lock().map(|t| t.clone()).unwrap_or_default();
+        String::from_utf8_lossy(&Vec::from(tail)).into_owned()
+    }
+}
+
+/// Read `pipe` on its own thread, keeping only the last `MAX_CAPTURE_BYTES`.
+fn capture(mut pipe: impl Read + Send + 'static) -> Capture {
+    let tail = Arc::new(Mutex::new(VecDeque::new()));
+    let (done, closed) = mpsc::channel();
+    let shared = Arc::clone(&tail);
+    thread::spawn(move || {
+        let mut chunk = [0; 8192];
+        while let Ok(n) = pipe.read(&mut chunk) {
+            if n == 0 {
+                break;
+            }
+            let Ok(mut tail) = shared.lock() else { break };
+            tail.extend(&chunk[..n]);
+            let excess = tail.len().saturating_sub(MAX_CAPTURE_BYTES);
+            tail.drain(..excess);
+        }
+        let _ = done.send(());
+    });
+    Capture { tail, closed }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+    use tempfile::TempDir;
+
+    // Exit status and both output streams are captured, and only a zero exit
+    // counts as a pass.
+    #[rstest]
+    #[case::passes("echo out; echo err >&2", Some(0), true)]
+    #[case::fails("echo out; echo err >&2; exit 3", Some(3), false)]
+    fn captures_exit_code_and_output(
+        #[case] command: &str,
+        #[case] exit_code: Option<i32>,
+        #[case] passed: bool,
+    ) {
+        let dir = TempDir::new().unwrap();
+        let run = run_verify_command(command, dir.path(), Duration::from_secs(10)).unwrap();
+        assert_eq!(run.exit_code(), exit_code);
+        assert_eq!(run.passed(), passed);
+        assert_eq!(run.stdout(), "out\n");
+        assert_eq!(run.stderr(), "err\n");
+    }
+
+    // The command runs in the given directory.
+    #[rstest]
+    fn runs_in_directory() {
+        let dir = TempDir::new().unwrap();
+        std::fs::write(dir.path().join("marker"), "").unwrap();
+        let run =
+            run_verify_command("test -f marker", dir.path(), Duration::from_secs(10)).unwrap();
+        assert!(run.passed());
+    }
+
+    // A command that outlives its timeout is killed and reported as failed.
+    #[rstest]
+    fn kills_on_timeout() {
+        let dir = TempDir::new().unwrap();
+        let run = run_verify_command("sleep 5", dir.path(), Duration::from_millis(200)).unwrap();
+        assert!(run.timed_out());
+        assert!(!run.passed());
+        assert!(run.duration_ms() < 5000);
+    }
+
+    // Only the tail of a long output is kept.
+    #[rstest]
+    fn keeps_tail_of_long_output() {
+        let dir = TempDir::new().unwrap();
+        let run = run_verify_command(
+            "head -c 200000 /dev/zero | tr '\\0' a; echo end",
+            dir.path(),
+            Duration::from_secs(10),
+        )
+        .unwrap();
+        assert_eq!(run.stdout().len(), MAX_CAPTURE_BYTES);
+        assert!(run.stdout().ends_with("aend\n"));
+    }
+
+    // A background process that keeps the output pipe open doesn't hold up the
+    // result once the command itself has exited.
+    #[rstest]
+    fn does_not_wait_for_background_processes() {
+        let dir = TempDir::new().unwrap();
+        let started = Instant::now();
+        let run = run_verify_command("sleep 10 & echo done", dir.path(), Duration::from_secs(30))
+            .unwrap();
+        assert!(run.passed());
+        assert_eq!(run.stdout(), "done\n");
+        assert!(started.elapsed() < Duration::from_secs(8));
+    }
+}
@@ -0,0 +1,522 @@
+//! A local HTTP/JSON API, for orchestrators that want a long-lived endpoint
+//! instead of spawning `rd` for every call.
+//!
+//! Requests are handled one at a time on the accepting thread, so the server is
+//! the single writer for everything that goes through it. Each route maps to an
+//! `api` operation: path segments and query parameters are merged into the JSON
+//! body to form its arguments, and the result is returned as the response body.
+//!
+//! There is no authentication, so by default the server only listens on a
+//! loopback address and only answers requests whose `Host` is this machine,
+//! which keeps web pages from reaching it through DNS rebinding. Requests that
+//! change anything must be sent as `application/json`, which a page can't do
+//! without a CORS preflight the server never answers.
+
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::net::{IpAddr, TcpListener, TcpStream, ToSocketAddrs};
+use std::path::Path;
+use std::time::Duration;
+
+use anyhow::{Context, Result, anyhow, bail};
+use serde_json::{Map, Value, json};
+
+use crate::api;
+use crate::db::{Database, NotFound};
+
+/// How long a client may take to send its request.
+const READ_TIMEOUT: Duration = Duration::from_secs(5);
+/// Largest request body accepted, in bytes.
+const MAX_BODY: usize = 1024 * 1024;
+/// Longest request line or header line accepted, in bytes.
+const MAX_LINE: usize = 8 * 1024;
+/// Most header lines accepted in one request.
+const MAX_HEADERS: usize = 100;
+
+/// The error `read_request` returns when a header line is too long or there are
+/// too many of them.
+#[derive(Debug)]
+pub struct HeadersTooLarge;
+
+impl std::fmt::Display for HeadersTooLarge {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Request headers are larger than {MAX_HEADERS} lines of {MAX_LINE} bytes"
+        )
+    }
+}
+
+impl std::error::Error for HeadersTooLarge {}
+
+/// An HTTP request, reduced to what routing needs.
+#[derive(Debug)]
+pub struct Request {
+    method: String,
+    path: String,
+    query: Vec<(String, String)>,
+    host: Option<String>,
+    content_type: Option<String>,
+    body: Vec<u8>,
+}
+
+/// A JSON response and its status code.
+#[derive(Debug)]
+pub struct Response {
+    status: u16,
+    body: Value,
+}
+
+impl Response {
+    fn ok(body: Value) -> Self {
+        Self { status: 200, body }
+    }
+
+    fn error(status: u16, message: &str) -> Self {
+        Self {
+            status,
+            body: json!({ "error": message }),
+        }
+    }
+
+    pub fn status(&self) -> u16 {
+        self.status
+    }
+
+    pub fn body(&self) -> &Value {
+        &self.body
+    }
+}
+
+/// Listen on `bind`, which must be a loopback address unless `allow_remote`.
+pub fn listen(bind: &str, allow_remote: bool) -> Result<TcpListener> {
+    let addrs: Vec<_> = bind
+        .to_socket_addrs()
+        .with_context(|| format!("Invalid address: {bind}"))?
+        .collect();
+    if !allow_remote && addrs.iter().any(|addr| !addr.ip().is_loopback()) {
+        bail!(
+            "Refusing to listen on {bind}: the API has no authentication, so it only listens on loopback addresses.\nPass --allow-remote to accept connections from other machines."
+        );
+    }
+    TcpListener::bind(addrs.as_slice()).with_context(|| format!("Failed to listen on {bind}"))
+}
+
+/// Accept connections on `listener` until the process is stopped. `agent` is
+/// used for calls that don't name one; `allow_remote` accepts requests for any
+/// `Host`.
+pub fn run(
+    listener: &TcpListener,
+    radial_dir: &Path,
+    agent: Option<&str>,
+    allow_remote: bool,
+) -> Result<()> {
+    for stream in listener.incoming() {
+        let Ok(stream) = stream else { continue };
+        // A client that hangs up or sends garbage only affects its own request.
+        let _ = serve_connection(stream, radial_dir, agent, allow_remote);
+    }
+    Ok(())
+}
+
+fn serve_connection(
+    mut stream: TcpStream,
+    radial_dir: &Path,
+    agent: Option<&str>,
+    allow_remote: bool,
+) -> Result<()> {
+    stream.set_read_timeout(Some(READ_TIMEOUT))?;
+    let response = match read_request(BufReader::new(&stream)) {
+        Ok(request) => handle(&request, radial_dir, agent, allow_remote),
+        Err(err) if err.is::<HeadersTooLarge>() => Response::error(431, &err.to_string()),
+        Err(err) => Response::error(400, &format!("{err:#}")),
+    };
+    write_response(&mut stream, &response)
+}
+
+/// Parse a request line, the headers routing needs and the `Content-Length` body.
+pub fn read_request(mut reader: impl BufRead) -> Result<Request> {
+    let mut line = String::new();
+    if !read_line(&mut reader, &mut line)? {
+        return Err(anyhow!("Request line is longer than {MAX_LINE} bytes"));
+    }
+    let mut parts = line.split_whitespace();
+    let (Some(method), Some(target)) = (parts.next(), parts.next()) else {
+        return Err(anyhow!("Malformed request line"));
+    };
+    let method = method.to_owned();
+    let (path, query) = target.split_once('?').unwrap_or((target, ""));
+    let path = path.to_owned();
+    let query = query
+        .split('&')
+        .filter(|pair| !pair.is_empty())
+        .map(|pair| {
+            let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+            (percent_decode(key), percent_decode(value))
+        })
+        .collect();
+
+    let mut content_length = 0;
+    let mut host = None;
+    let mut content_type = None;
+    for count in 0.. {
+        if count > MAX_HEADERS || !read_line(&mut reader, &mut line)? {
+            return Err(HeadersTooLarge.into());
+        }
+        let header = line.trim_end();
+        if header.is_empty() {
+            break;
+        }
+        let Some((name, value)) = header.split_once(':') else {
+            continue;
+        };
+        let value = value.trim();
+        if name.eq_ignore_ascii_case("content-length") {
+            content_length = value.parse().context("Invalid Content-Length")?;
+        } else if name.eq_ignore_ascii_case("host") {
+            host = Some(value.to_owned());
+        } else if name.eq_ignore_ascii_case("content-type") {
+            content_type = Some(value.to_owned());
+        }
+    }
+    if content_length > MAX_BODY {
+        return Err(anyhow!("Request body is larger than {MAX_BODY} bytes"));
+    }
+
+    let mut body = vec![0; content_length];
+    reader.read_exact(&mut body)?;
+    Ok(Request {
+        method,
+        path,
+        query,
+        host,
+        content_type,
+        body,
+    })
+}
+
+/// Read one line into `line`, replacing its contents. Returns false if the
+/// line is longer than `MAX_LINE`, without reading the rest of it.
+fn read_line(reader: &mut impl BufRead, line: &mut String) -> Result<bool> {
+    line.clear();
+    let read = reader.take(MAX_LINE as u64).read_line(line)?;
+    Ok(read < MAX_LINE || line.ends_with('\n'))
+}
+
+/// Route a request to its operation and run it against a freshly opened
+/// database, so changes made outside the server are seen.
+pub fn handle(
+    request: &Request,
+    radial_dir: &Path,
+    agent: Option<&str>,
+    allow_remote: bool,
+) -> Response {
+    if !allow_remote && !request.host.as_deref().is_some_and(is_loopback_host) {
+        return Response::error(403, "Host must be a loopback address");
+    }
+
+    let segments: Vec<&str> = request.path.split('/').filter(|s| !s.is_empty()).collect();
+    let method = request.method.as_str();
+
+    let (operation, path_args) = match (method, segments.as_slice()) {
+        ("GET", ["goals"]) => ("goal_list", vec![]),
+        ("POST", ["goals"]) => ("goal_create", vec![]),
+        ("GET", ["goals", id]) => ("status", vec![("goal_id", *id)]),
+        ("GET", ["goals", id, "tasks"]) => ("task_list", vec![("goal_id", *id)]),
+        ("POST", ["goals", id, "tasks"]) => ("task_create", vec![("goal_id", *id)]),
+        ("GET", ["goals", id, "ready"]) => ("ready", vec![("goal_id", *id)]),
+        ("POST", ["goals", id, "next"]) => ("next", vec![("goal_id", *id)]),
+        ("GET", ["ready"]) => ("ready", vec![]),
+        ("GET", ["status"]) => ("status", vec![]),
+        ("GET", ["tasks", id]) => ("status", vec![("task_id", *id)]),
+        ("POST", ["tasks", id, "start"]) => ("task_start", vec![("task_id", *id)]),
+        ("POST", ["tasks", id, "heartbeat"]) => ("task_heartbeat", vec![("task_id", *id)]),
+        ("POST", ["tasks", id, "complete"]) => ("task_complete", vec![("task_id", *id)]),
+        ("POST", ["tasks", id, "submit"]) => ("task_submit", vec![("task_id", *id)]),
+        ("POST", ["tasks", id, "verify"]) => ("task_verify", vec![("task_id", *id)]),
+        ("POST", ["tasks", id, "fail"]) => ("task_fail", vec![("task_id", *id)]),
+        ("POST", ["tasks", id, "comments"]) => ("task_comment", vec![("task_id", *id)]),
+        _ => {
+            return Response::error(404, &format!("No route for {method} {}", request.path));
+        }
+    };
+
+    let is_json = request
+        .content_type
+        .as_deref()
+        .and_then(|value| value.split(';').next())
+        .is_some_and(|media| media.trim().eq_ignore_ascii_case("application/json"));
+    if method == "POST" && !is_json {
+        return Response::error(415, "Content-Type must be application/json");
+    }
+
+    let args = match arguments(request, &path_args) {
+        Ok(args) => args,
+        Err(err) => return Response::error(400, &format!("{err:#}")),
+    };
+    let mut db = match Database::open(radial_dir) {
+        Ok(db) => db,
+        Err(err) => return Response::error(500, &format!("Failed to open database: {err:#}")),
+    };
+    match api::call(operation, &args, agent, &mut db) {
+        Ok(value) => Response::ok(value),
+        Err(err) => Response::error(error_status(&err), &format!("{err:#}")),
+    }
+}
+
+/// Whether a `Host` header names this machine, with or without a port.
+fn is_loopback_host(host: &str) -> bool {
+    let name = match host.strip_prefix('[') {
+        Some(bracketed) => bracketed.split(']').next().unwrap_or_default(),
+        None => host.rsplit_once(':').map_or(host, |(name, _)| name),
+    };
+    name.eq_ignore_ascii_case("localhost")
+        || name.parse::<IpAddr>().is_ok_and(|ip| ip.is_loopback())
+}
+
+/// The status for a failed operation: 404 when an ID names nothing, 500 when
+/// the database couldn't be read or written, and 400 for anything else the
+/// request got wrong.
+fn error_status(err: &anyhow::Error) -> u16 {
+    if err.chain().any(|cause| cause.is::<NotFound>()) {
+        404
+    } else if err
+        .chain()
+        .any(|cause| cause.is::<io::Error>() || cause.is::<toml::de::Error>())
+    {
+        500
+    } else {
+        400
+    }
+}
+
+/// Merge the JSON body, query parameters and path segments, in increasing
+/// order of precedence, into one arguments object.
+fn arguments(request: &Request, path_args: &[(&str, &str)]) -> Result<Value> {
+    let mut args = if request.body.iter().all(u8::is_ascii_whitespace) {
+        Map::new()
+    } else {
+        match serde_json::from_slice(&request.body).context("Request body is not valid JSON")? {
+            Value::Object(map) => map,
+            _ => return Err(anyhow!("Request body must be a JSON object")),
+        }
+    };
+
+    for (key, value) in &request.query {
+        // Query strings are untyped; numeric parameters are sent as numbers.
+        let value = match value.parse::<i64>() {
+            Ok(n) if key == "limit" => Value::from(n),
+            _ => Value::from(value.as_str()),
+        };
+        args.insert(key.clone(), value);
+    }
+    for (key, value) in path_args {
+        args.insert((*key).to_owned(), Value::from(percent_decode(value)));
+    }
+    Ok(Value::Object(args))
+}
+
+fn write_response(stream: &mut impl Write, response: &Response) -> Result<()> {
+    let body = response.body.to_string();
+    let reason = match response.status {
+        200 => "OK",
+        400 => "Bad Request",
+        403 => "Forbidden",
+        404 => "Not Found",
+        415 => "Unsupported Media Type",
+        431 => "Request Header Fields Too Large",
+        500 => "Internal Server Error",
+        _ => "",
+    };
+    write!(
+        stream,
+        "HTTP/1.1 {} {reason}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+        response.status,
+        body.len()
+    )?;
+    stream.flush()?;
+    Ok(())
+}
+
+/// Decode `%XX` escapes and `+` as used in URLs. Invalid escapes are kept as
+/// they are.
+fn percent_decode(input: &str) -> String {
+    let bytes = input.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'%' if i + 2 < bytes.len()
+                && bytes[i + 1].is_ascii_hexdigit()
+                && bytes[i + 2].is_ascii_hexdigit() =>
+            {
+                let hex = std::str::from_utf8(&bytes[i + 1..i + 3]).unwrap_or_default();
+                out.push(u8::from_str_radix(hex, 16).unwrap_or_default());
+                i += 3;
+                continue;
+            }
+            b'+' => out.push(b' '),
+            byte => out.push(byte),
+        }
+        i += 1;
+    }
+    String::from_utf8_lossy(&out).into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+    use tempfile::TempDir;
+
+    fn request(raw: &str) -> Request {
+        read_request(raw.as_bytes()).unwrap()
+    }
+
+    // -- read_request --
+
+    // The query string is split and decoded, and the body is read up to
+    // Content-Length.
+    #[rstest]
+    fn read_request_parses_query_and_body() {
+        let req = request(
+            "POST /goals/g-1/tasks?agent=a%20b&limit=2 HTTP/1.1\r\nContent-Length: 2\r\n\r\n{}extra",
+        );
+        assert_eq!(req.method, "POST");
+        assert_eq!(req.path, "/goals/g-1/tasks");
+        assert_eq!(
+            req.query,
+            vec![
+                ("agent".to_string(), "a b".to_string()),
+                ("limit".to_string(), "2".to_string())
+            ]
+        );
+        assert_eq!(req.body, b"{}");
+    }
+
+    // Overlong lines and too many headers are refused instead of buffered.
+    #[rstest]
+    #[case::long_request_line(format!("GET /{} HTTP/1.1\r\n\r\n", "a".repeat(MAX_LINE)), false)]
+    #[case::long_header(format!("GET / HTTP/1.1\r\nX-Pad: {}\r\n\r\n", "a".repeat(MAX_LINE)), true)]
+    #[case::many_headers(format!("GET / HTTP/1.1\r\n{}\r\n", "X-Pad: a\r\n".repeat(MAX_HEADERS + 1)), true)]
+    fn read_request_limits_headers(#[case] raw: String, #[case] headers: bool) {
+        let err = read_request(raw.as_bytes()).unwrap_err();
+        assert_eq!(err.is::<HeadersTooLarge>(), headers);
+    }
+
+    // Exactly MAX_HEADERS headers are still accepted.
+    #[rstest]
+    fn read_request_accepts_header_limit() {
+        let raw = format!(
+            "GET / HTTP/1.1\r\n{}\r\n",
+            "X-Pad: a\r\n".repeat(MAX_HEADERS)
+        );
+        assert!(read_request(raw.as_bytes()).is_ok());
+    }
+
+    // -- percent_decode --
+
+    #[rstest]
+    #[case::plain("abc", "abc")]
+    #[case::escape("a%2Fb", "a/b")]
+    #[case::plus("a+b", "a b")]
+    #[case::invalid("100%", "100%")]
+    #[case::bad_hex("%zz", "%zz")]
+    fn percent_decode_handles_escapes(#[case] input: &str, #[case] expected: &str) {
+        assert_eq!(percent_decode(input), expected);
+    }
+
+    // -- handle --
+
+    // Path segments override body fields, and the result is the operation's JSON.
+    #[rstest]
+    fn handle_routes_to_operations() {
+        let dir = TempDir::new().unwrap();
+        let body = json!({ "description": "G" }).to_string();
+        let created = handle(
+            &request(&format!(
+                "POST /goals HTTP/1.1\r\nHost: 127.0.0.1:7420\r\nContent-Type: application/json\r\nContent-Length: {}\r\n\r\n{body}",
+                body.len()
+            )),
+            dir.path(),
+            None,
+            false,
+        );
+        assert_eq!(created.status(), 200);
+        let goal_id = created.body()["id"].as_str().unwrap();
+
+        let status = handle(
+            &request(&format!(
+                "GET /goals/{goal_id} HTTP/1.1\r\nHost: localhost\r\n\r\n"
+            )),
+            dir.path(),
+            None,
+            false,
+        );
+        assert_eq!(status.body()["id"], goal_id);
+    }
+
+    // Unknown routes and IDs are 404s, bad bodies are 400s, and requests a web
+    // page could forge are refused before anything runs.
+    #[rstest]
+    #[case::unknown_route("DELETE /goals HTTP/1.1\r\nHost: localhost\r\n\r\n", 404)]
+    #[case::missing_goal("GET /goals/g-nope HTTP/1.1\r\nHost: localhost\r\n\r\n", 404)]
+    #[case::bad_body(
+        "POST /goals HTTP/1.1\r\nHost: localhost\r\nContent-Type: application/json\r\nContent-Length: 3\r\n\r\n[1]",
+        400
+    )]
+    #[case::remote_host("GET /goals HTTP/1.1\r\nHost: evil.example:7420\r\n\r\n", 403)]
+    #[case::no_host("GET /goals HTTP/1.1\r\n\r\n", 403)]
+    #[case::not_json(
+        "POST /goals HTTP/1.1\r\nHost: localhost\r\nContent-Type: text/plain\r\nContent-Length: 2\r\n\r\n{}",
+        415
+    )]
+    fn handle_reports_errors(#[case] raw: &str, #[case] status: u16) {
+        let dir = TempDir::new().unwrap();
+        let response = handle(&request(raw), dir.path(), None, false);
+        assert_eq!(response.status(), status);
+        assert!(response.body()["error"].is_string());
+    }
+
+    // A database that can't be opened is the server's fault, not the request's.
+    #[rstest]
+    fn handle_reports_internal_failures() {
+        let dir = TempDir::new().unwrap();
+        let response = handle(
+            &request("GET /goals HTTP/1.1\r\nHost: localhost\r\n\r\n"),
+            &dir.path().join("missing"),
+            None,
+            false,
+        );
+        assert_eq!(response.status(), 500);
+    }
+
+    // With remote access allowed, any Host is answered.
+    #[rstest]
+    fn handle_allows_remote_hosts_when_asked() {
+        let dir = TempDir::new().unwrap();
+        let raw = "GET /goals HTTP/1.1\r\nHost: radial.internal:7420\r\n\r\n";
+        assert_eq!(handle(&request(raw), dir.path(), None, true).status(), 200);
+    }
+
+    // -- is_loopback_host --
+
+    #[rstest]
+    #[case::ipv4("127.0.0.1:7420", true)]
+    #[case::localhost("LOCALHOST", true)]
+    #[case::ipv6("[::1]:7420", true)]
+    #[case::lan("192.168.1.5:7420", false)]
+    #[case::name("localhost.evil.example", false)]
+    fn is_loopback_host_matches_this_machine(#[case] host: &str, #[case] expected: bool) {
+        assert_eq!(is_loopback_host(host), expected);
+    }
+
+    // -- listen --
+
+    // Binding beyond loopback needs an explicit opt-in.
+    #[rstest]
+    fn listen_refuses_remote_addresses() {
+        assert!(listen("0.0.0.0:0", false).is_err());
+        assert!(listen("0.0.0.0:0", true).is_ok());
+        assert!(listen("127.0.0.1:0", false).is_ok());
+    }
+}
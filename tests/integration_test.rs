@@ -1,6 +1,9 @@
 use serde_json::Value;
+use std::fs;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::TcpStream;
 use std::path::PathBuf;
-use std::process::Command;
+use std::process::{Command, Stdio};
 use tempfile::TempDir;
 
 /// Helper struct to manage test environment
@@ -43,6 +46,40 @@ impl TestEnv {
         }
     }
 
+    /// Run a radial command and return its exit code along with stdout
+    fn run_with_code(&self, args: &[&str]) -> (Option<i32>, String) {
+        let output = Command::new(&self.binary_path)
+            .args(args)
+            .current_dir(&self.work_dir)
+            .output()
+            .expect("Failed to execute radial command");
+
+        (
+            output.status.code(),
+            String::from_utf8_lossy(&output.stdout).to_string(),
+        )
+    }
+
+    /// Run a radial command with `input` on stdin and return stdout
+    fn run_with_stdin(&self, args: &[&str], input: &str) -> String {
+        let mut child = Command::new(&self.binary_path)
+            .args(args)
+            .current_dir(&self.work_dir)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn()
+            .expect("Failed to execute radial command");
+
+        child
+            .stdin
+            .take()
+            .unwrap()
+            .write_all(input.as_bytes())
+            .expect("Failed to write stdin");
+        let output = child.wait_with_output().expect("Failed to wait for radial");
+        String::from_utf8_lossy(&output.stdout).to_string()
+    }
+
     /// Check if .radial directory exists
     fn radial_dir_exists(&self) -> bool {
         self.work_dir.join(".radial").exists()
@@ -107,14 +144,22 @@ fn test_create_and_list_goals() {
         .expect("Create goal failed");
     assert!(output.contains("Created goal:"));
 
-    // Extract goal ID from output (format: "Created goal: XXXXXXXX")
+    // Extract goal ID from output (format: "Created goal: g-XXXXXXXX")
     let goal_id = output
         .lines()
         .find(|line| line.contains("Created goal:"))
         .and_then(|line| line.split_whitespace().nth(2))
         .expect("Could not extract goal ID");
 
-    assert_eq!(goal_id.len(), 8, "Goal ID should be 8 characters");
+    assert!(
+        goal_id.starts_with("g-"),
+        "Goal ID should have the goal prefix"
+    );
+    assert_eq!(
+        goal_id.len(),
+        10,
+        "Goal ID should be 8 characters after the prefix"
+    );
 
     // List goals should show the created goal
     let output = env.run(&["goal", "list"]).expect("List failed");
@@ -161,7 +206,15 @@ fn test_create_task_and_workflow() {
         .and_then(|line| line.split_whitespace().nth(2))
         .expect("Could not extract task ID");
 
-    assert_eq!(task_id.len(), 8, "Task ID should be 8 characters");
+    assert!(
+        task_id.starts_with("t-"),
+        "Task ID should have the task prefix"
+    );
+    assert_eq!(
+        task_id.len(),
+        10,
+        "Task ID should be 8 characters after the prefix"
+    );
 
     // List tasks
     let output = env
@@ -401,8 +454,18 @@ fn test_goal_completion() {
         .unwrap();
 
     env.run(&["task", "start", task_id]).expect("Start failed");
-    env.run(&["task", "complete", task_id, "--result", "All done"])
-        .expect("Complete failed");
+    env.run(&[
+        "task",
+        "complete",
+        task_id,
+        "--result",
+        "All done",
+        "--prompt-tokens",
+        "30",
+        "--completion-tokens",
+        "12",
+    ])
+    .expect("Complete failed");
 
     // Goal should now be completed
     let output = env
@@ -410,6 +473,67 @@ fn test_goal_completion() {
         .expect("Status failed");
     assert!(output.contains("[completed]"));
     assert!(output.contains("Tasks: 1 total, 1 completed, 0 failed"));
+
+    // The metrics stored in goal.toml match, for tools that read it directly
+    let goal_toml = env.work_dir.join(".radial").join(goal_id).join("goal.toml");
+    let goal: toml::Value = toml::from_str(&fs::read_to_string(goal_toml).unwrap()).unwrap();
+    let metrics = &goal["metrics"];
+    assert_eq!(metrics["task_count"].as_integer(), Some(1));
+    assert_eq!(metrics["tasks_completed"].as_integer(), Some(1));
+    assert_eq!(metrics["total_tokens"].as_integer(), Some(42));
+    assert_eq!(metrics["prompt_tokens"].as_integer(), Some(30));
+    assert_eq!(metrics["completion_tokens"].as_integer(), Some(12));
+}
+
+#[test]
+fn test_goal_follows_failure_and_retry() {
+    let env = TestEnv::new();
+    env.run(&["init"]).expect("Init failed");
+
+    let output = env
+        .run(&["goal", "create", "Retry test"])
+        .expect("Create goal failed");
+    let goal_id = output
+        .lines()
+        .find(|line| line.contains("Created goal:"))
+        .and_then(|line| line.split_whitespace().nth(2))
+        .unwrap();
+
+    let output = env
+        .run(&[
+            "task",
+            "create",
+            goal_id,
+            "Flaky task",
+            "--receives",
+            "Goal",
+            "--produces",
+            "Result",
+            "--verify",
+            "Done",
+        ])
+        .expect("Create task failed");
+    let task_id = output
+        .lines()
+        .find(|line| line.contains("Created task:"))
+        .and_then(|line| line.split_whitespace().nth(2))
+        .unwrap();
+
+    let goal_state = || {
+        let output = env
+            .run(&["status", "--goal", goal_id, "--json"])
+            .expect("Status failed");
+        let json: serde_json::Value = serde_json::from_str(&output).unwrap();
+        json["state"].as_str().unwrap().to_string()
+    };
+
+    // Failing the task fails the goal, and retrying it puts the goal back in progress
+    env.run(&["task", "start", task_id]).expect("Start failed");
+    env.run(&["task", "fail", task_id]).expect("Fail failed");
+    assert_eq!(goal_state(), "failed");
+
+    env.run(&["task", "retry", task_id]).expect("Retry failed");
+    assert_eq!(goal_state(), "inprogress");
 }
 
 #[test]
@@ -977,3 +1101,2187 @@ fn test_task_comments_json_output() {
     let comments = parsed["comments"].as_array().unwrap();
     assert_eq!(comments.len(), 2, "JSON should always include comments");
 }
+
+#[test]
+fn test_concurrent_start_has_single_winner() {
+    let env = TestEnv::new();
+    env.run(&["init"]).expect("Init failed");
+
+    let output = env
+        .run(&["goal", "create", "Race test"])
+        .expect("Create goal failed");
+    let goal_id = output
+        .lines()
+        .find(|line| line.contains("Created goal:"))
+        .and_then(|line| line.split_whitespace().nth(2))
+        .unwrap();
+
+    let output = env
+        .run(&[
+            "task",
+            "create",
+            goal_id,
+            "Contended task",
+            "--receives",
+            "Input",
+            "--produces",
+            "Output",
+            "--verify",
+            "Check",
+        ])
+        .expect("Create task failed");
+    let task_id = output
+        .lines()
+        .find(|line| line.contains("Created task:"))
+        .and_then(|line| line.split_whitespace().nth(2))
+        .unwrap();
+
+    // Spawn every claimant before waiting on any, so they all load the
+    // pending task and race on the write.
+    let children: Vec<_> = (0..16)
+        .map(|_| {
+            Command::new(&env.binary_path)
+                .args(["task", "start", task_id])
+                .current_dir(&env.work_dir)
+                .stdout(std::process::Stdio::null())
+                .stderr(std::process::Stdio::null())
+                .spawn()
+                .expect("Failed to spawn radial")
+        })
+        .collect();
+
+    let winners = children
+        .into_iter()
+        .map(|mut child| child.wait().expect("Failed to wait on radial"))
+        .filter(std::process::ExitStatus::success)
+        .count();
+
+    assert_eq!(winners, 1, "Exactly one process should claim the task");
+
+    let output = env
+        .run(&["status", "--task", task_id])
+        .expect("Status failed");
+    assert!(output.contains("[in_progress]"));
+}
+
+#[test]
+fn test_expired_lease_is_reaped() {
+    let env = TestEnv::new();
+    env.run(&["init"]).expect("Init failed");
+
+    let output = env
+        .run(&["goal", "create", "Lease test"])
+        .expect("Create goal failed");
+    let goal_id = output
+        .lines()
+        .find(|line| line.contains("Created goal:"))
+        .and_then(|line| line.split_whitespace().nth(2))
+        .unwrap();
+
+    let output = env
+        .run(&[
+            "task",
+            "create",
+            goal_id,
+            "Leased task",
+            "--receives",
+            "Input",
+            "--produces",
+            "Output",
+            "--verify",
+            "Check",
+        ])
+        .expect("Create task failed");
+    let task_id = output
+        .lines()
+        .find(|line| line.contains("Created task:"))
+        .and_then(|line| line.split_whitespace().nth(2))
+        .unwrap();
+
+    env.run(&[
+        "task",
+        "start",
+        task_id,
+        "--agent",
+        "worker-1",
+        "--lease-secs",
+        "1",
+    ])
+    .expect("Start failed");
+
+    // Only the holder may extend the lease
+    let result = env.run(&["task", "heartbeat", task_id, "--agent", "worker-2"]);
+    assert!(result.unwrap_err().contains("held by another agent"));
+    env.run(&["task", "heartbeat", task_id, "--agent", "worker-1"])
+        .expect("Heartbeat failed");
+
+    // A live lease is not reaped
+    let output = env.run(&["reap"]).expect("Reap failed");
+    assert!(output.contains("No expired leases"));
+
+    std::thread::sleep(std::time::Duration::from_millis(1100));
+
+    // `rd ready` reaps before listing, so the abandoned task is offered again
+    let output = env.run(&["ready", goal_id]).expect("Ready failed");
+    assert!(output.contains(task_id));
+
+    let output = env
+        .run(&["status", "--task", task_id, "--json"])
+        .expect("Status failed");
+    let parsed: Value = serde_json::from_str(&output).expect("Should be valid JSON");
+    assert_eq!(parsed["state"], "pending");
+    assert_eq!(parsed["metrics"]["retry_count"], 1);
+    assert!(parsed["lease"].is_null());
+}
+
+#[test]
+fn test_agent_identity_recorded() {
+    let env = TestEnv::new();
+    env.run(&["init"]).expect("Init failed");
+
+    let output = env
+        .run(&["goal", "create", "Agent test"])
+        .expect("Create goal failed");
+    let goal_id = output
+        .lines()
+        .find(|line| line.contains("Created goal:"))
+        .and_then(|line| line.split_whitespace().nth(2))
+        .unwrap();
+
+    let mut task_ids = Vec::new();
+    for description in ["Task for alice", "Task for bob"] {
+        let output = env
+            .run(&[
+                "task",
+                "create",
+                goal_id,
+                description,
+                "--receives",
+                "Input",
+                "--produces",
+                "Output",
+                "--verify",
+                "Check",
+            ])
+            .expect("Create task failed");
+        let task_id = output
+            .lines()
+            .find(|line| line.contains("Created task:"))
+            .and_then(|line| line.split_whitespace().nth(2))
+            .unwrap()
+            .to_string();
+        task_ids.push(task_id);
+    }
+
+    env.run(&["task", "start", &task_ids[0], "--agent", "alice"])
+        .expect("Start failed");
+    env.run(&[
+        "task",
+        "complete",
+        &task_ids[0],
+        "--result",
+        "Done",
+        "--agent",
+        "alice",
+    ])
+    .expect("Complete failed");
+
+    // RD_AGENT is used when --agent is not given
+    let output = Command::new(&env.binary_path)
+        .args(["task", "comment", &task_ids[1], "Looking into it"])
+        .env("RD_AGENT", "bob")
+        .current_dir(&env.work_dir)
+        .output()
+        .expect("Failed to execute radial");
+    assert!(output.status.success());
+
+    let output = env
+        .run(&["status", "--task", &task_ids[0], "--json"])
+        .expect("Status failed");
+    let parsed: Value = serde_json::from_str(&output).expect("Should be valid JSON");
+    assert_eq!(parsed["claimed_by"], "alice");
+    assert_eq!(parsed["result"]["agent"], "alice");
+
+    let output = env
+        .run(&["status", "--task", &task_ids[1]])
+        .expect("Status failed");
+    assert!(output.contains("bob"));
+
+    let output = env
+        .run(&["task", "list", goal_id, "--agent", "bob", "--json"])
+        .expect("List failed");
+    let parsed: Value = serde_json::from_str(&output).expect("Should be valid JSON");
+    let tasks = parsed.as_array().unwrap();
+    assert_eq!(tasks.len(), 1);
+    assert_eq!(tasks[0]["id"], task_ids[1].as_str());
+    assert_eq!(tasks[0]["comments"][0]["agent"], "bob");
+}
+
+#[test]
+fn test_event_log() {
+    let env = TestEnv::new();
+    env.run(&["init"]).expect("Init failed");
+
+    let output = env
+        .run(&["goal", "create", "Log test"])
+        .expect("Create goal failed");
+    let goal_id = output
+        .lines()
+        .find(|line| line.contains("Created goal:"))
+        .and_then(|line| line.split_whitespace().nth(2))
+        .unwrap();
+
+    let output = env
+        .run(&[
+            "task",
+            "create",
+            goal_id,
+            "Logged task",
+            "--receives",
+            "Input",
+            "--produces",
+            "Output",
+            "--verify",
+            "Check",
+        ])
+        .expect("Create task failed");
+    let task_id = output
+        .lines()
+        .find(|line| line.contains("Created task:"))
+        .and_then(|line| line.split_whitespace().nth(2))
+        .unwrap();
+
+    env.run(&["task", "start", task_id, "--agent", "alice"])
+        .expect("Start failed");
+    env.run(&["task", "fail", task_id, "--agent", "alice"])
+        .expect("Fail failed");
+    env.run(&["task", "retry", task_id, "--agent", "bob"])
+        .expect("Retry failed");
+
+    let output = env
+        .run(&["log", "--task", task_id, "--json"])
+        .expect("Log failed");
+    let parsed: Value = serde_json::from_str(&output).expect("Should be valid JSON");
+    let events = parsed.as_array().unwrap();
+    let transitions: Vec<(&str, &str, &str)> = events
+        .iter()
+        .filter(|e| e["action"] == "transition")
+        .map(|e| {
+            (
+                e["from"].as_str().unwrap(),
+                e["to"].as_str().unwrap(),
+                e["agent"].as_str().unwrap(),
+            )
+        })
+        .collect();
+    assert_eq!(events[0]["action"], "created");
+    assert_eq!(
+        transitions,
+        vec![
+            ("pending", "in_progress", "alice"),
+            ("in_progress", "failed", "alice"),
+            ("failed", "in_progress", "bob"),
+        ]
+    );
+    assert!(events.iter().all(|e| e["timestamp"].is_string()));
+
+    // The goal log also includes goal-level events
+    let output = env.run(&["log", "--goal", goal_id]).expect("Log failed");
+    assert!(output.contains(&format!("goal {goal_id} created")));
+    assert!(output.contains("pending -> in_progress"));
+    assert!(output.contains("by bob"));
+
+    // Goal edits are journaled, and edits nobody claimed name no agent
+    env.run(&["goal", "budget", goal_id, "--max-tokens", "1000"])
+        .expect("Budget failed");
+    env.run(&["task", "update", task_id, "--description", "Renamed"])
+        .expect("Update failed");
+    let output = env
+        .run(&["log", "--goal", goal_id, "--json"])
+        .expect("Log failed");
+    let parsed: Value = serde_json::from_str(&output).unwrap();
+    let updates: Vec<&Value> = parsed
+        .as_array()
+        .unwrap()
+        .iter()
+        .filter(|e| e["action"] == "updated")
+        .collect();
+    assert_eq!(updates.len(), 2);
+    assert!(updates[0]["task_id"].is_null());
+    assert_eq!(updates[1]["task_id"], task_id);
+    assert!(updates.iter().all(|e| e["agent"].is_null()));
+}
+
+#[test]
+fn test_task_update_and_contract() {
+    let env = TestEnv::new();
+    env.run(&["init"]).expect("Init failed");
+
+    let output = env
+        .run(&["goal", "create", "Update test"])
+        .expect("Create goal failed");
+    let goal_id = output
+        .lines()
+        .find(|line| line.contains("Created goal:"))
+        .and_then(|line| line.split_whitespace().nth(2))
+        .unwrap();
+
+    let output = env
+        .run(&["task", "create", goal_id, "Sketch"])
+        .expect("Create task failed");
+    let task_id = output
+        .lines()
+        .find(|line| line.contains("Created task:"))
+        .and_then(|line| line.split_whitespace().nth(2))
+        .unwrap()
+        .to_string();
+
+    let output = env
+        .run(&[
+            "task",
+            "create",
+            goal_id,
+            "Other",
+            "--receives",
+            "A",
+            "--produces",
+            "B",
+            "--verify",
+            "C",
+        ])
+        .expect("Create task failed");
+    let other_id = output
+        .lines()
+        .find(|line| line.contains("Created task:"))
+        .and_then(|line| line.split_whitespace().nth(2))
+        .unwrap()
+        .to_string();
+
+    // A task without a contract cannot start until one is set
+    assert!(env.run(&["task", "start", &task_id]).is_err());
+    env.run(&[
+        "task",
+        "contract",
+        &task_id,
+        "--receives",
+        "Nothing",
+        "--produces",
+        "Something",
+    ])
+    .expect("Contract failed");
+
+    // Only the given fields change; the rest are preserved
+    let output = env
+        .run(&[
+            "task",
+            "update",
+            &task_id,
+            "--description",
+            "Refined",
+            "--verify",
+            "It works",
+            "--add-blocked-by",
+            &other_id,
+            "--json",
+        ])
+        .expect("Update failed");
+    let parsed: Value = serde_json::from_str(&output).expect("Should be valid JSON");
+    assert_eq!(parsed["description"], "Refined");
+    assert_eq!(parsed["contract"]["receives"], "Nothing");
+    assert_eq!(parsed["contract"]["verify"], "It works");
+    assert_eq!(parsed["state"], "blocked");
+
+    let output = env
+        .run(&[
+            "task",
+            "update",
+            &task_id,
+            "--remove-blocked-by",
+            &other_id,
+            "--json",
+        ])
+        .expect("Update failed");
+    let parsed: Value = serde_json::from_str(&output).expect("Should be valid JSON");
+    assert_eq!(parsed["state"], "pending");
+    assert!(parsed["blocked_by"].is_null());
+
+    // Completed tasks are protected unless forced
+    env.run(&["task", "start", &other_id])
+        .expect("Start failed");
+    env.run(&["task", "complete", &other_id, "--result", "Done"])
+        .expect("Complete failed");
+    let result = env.run(&["task", "update", &other_id, "--description", "Late"]);
+    assert!(result.unwrap_err().contains("--force"));
+    env.run(&[
+        "task",
+        "update",
+        &other_id,
+        "--description",
+        "Late",
+        "--force",
+    ])
+    .expect("Forced update failed");
+
+    // Adding an already-completed blocker does not block the task
+    let output = env
+        .run(&[
+            "task",
+            "update",
+            &task_id,
+            "--add-blocked-by",
+            &other_id,
+            "--json",
+        ])
+        .expect("Update failed");
+    let parsed: Value = serde_json::from_str(&output).expect("Should be valid JSON");
+    assert_eq!(parsed["state"], "pending");
+}
+
+#[test]
+fn test_dependency_check() {
+    let env = TestEnv::new();
+    env.run(&["init"]).expect("Init failed");
+
+    let output = env
+        .run(&["goal", "create", "Graph test"])
+        .expect("Create goal failed");
+    let goal_id = output
+        .lines()
+        .find(|line| line.contains("Created goal:"))
+        .and_then(|line| line.split_whitespace().nth(2))
+        .unwrap()
+        .to_string();
+
+    let output = env
+        .run(&["task", "create", &goal_id, "First"])
+        .expect("Create task failed");
+    let first_id = output
+        .lines()
+        .find(|line| line.contains("Created task:"))
+        .and_then(|line| line.split_whitespace().nth(2))
+        .unwrap()
+        .to_string();
+
+    let output = env
+        .run(&[
+            "task",
+            "create",
+            &goal_id,
+            "Second",
+            "--blocked-by",
+            &first_id,
+        ])
+        .expect("Create task failed");
+    let second_id = output
+        .lines()
+        .find(|line| line.contains("Created task:"))
+        .and_then(|line| line.split_whitespace().nth(2))
+        .unwrap()
+        .to_string();
+
+    let output = env.run(&["check", &goal_id]).expect("Check failed");
+    assert!(output.contains("ok"));
+
+    // Closing the loop is refused
+    let result = env.run(&["task", "update", &first_id, "--add-blocked-by", &second_id]);
+    assert!(result.unwrap_err().contains("cycle"));
+
+    // Blockers from another goal are refused with a clear message
+    let output = env
+        .run(&["goal", "create", "Other goal"])
+        .expect("Create goal failed");
+    let other_goal = output
+        .lines()
+        .find(|line| line.contains("Created goal:"))
+        .and_then(|line| line.split_whitespace().nth(2))
+        .unwrap();
+    let result = env.run(&["task", "create", other_goal, "X", "--blocked-by", &first_id]);
+    assert!(result.unwrap_err().contains("same goal"));
+
+    // Deleting a blocker's file leaves a dangling reference that check reports
+    std::fs::remove_file(
+        env.work_dir
+            .join(".radial")
+            .join(&goal_id)
+            .join(format!("{first_id}.toml")),
+    )
+    .unwrap();
+    let output = Command::new(&env.binary_path)
+        .args(["check", "--json"])
+        .current_dir(&env.work_dir)
+        .output()
+        .expect("Failed to execute radial");
+    assert!(!output.status.success());
+    let parsed: Value = serde_json::from_slice(&output.stdout).expect("Should be valid JSON");
+    let checks = parsed.as_array().unwrap();
+    let issues = checks
+        .iter()
+        .find(|c| c["goal_id"] == goal_id.as_str())
+        .unwrap()["issues"]
+        .as_array()
+        .unwrap();
+    assert_eq!(issues.len(), 1);
+    assert_eq!(issues[0]["kind"], "dangling_blocker");
+    assert_eq!(issues[0]["task_id"], second_id.as_str());
+}
+
+#[test]
+fn test_submit_and_verify() {
+    let env = TestEnv::new();
+    env.run(&["init"]).expect("Init failed");
+
+    let output = env
+        .run(&["goal", "create", "Review test"])
+        .expect("Create goal failed");
+    let goal_id = output
+        .lines()
+        .find(|line| line.contains("Created goal:"))
+        .and_then(|line| line.split_whitespace().nth(2))
+        .unwrap()
+        .to_string();
+
+    let output = env
+        .run(&[
+            "task",
+            "create",
+            &goal_id,
+            "Maker task",
+            "--receives",
+            "Spec",
+            "--produces",
+            "Code",
+            "--verify",
+            "Tests pass",
+        ])
+        .expect("Create task failed");
+    let maker_id = output
+        .lines()
+        .find(|line| line.contains("Created task:"))
+        .and_then(|line| line.split_whitespace().nth(2))
+        .unwrap()
+        .to_string();
+
+    let output = env
+        .run(&[
+            "task",
+            "create",
+            &goal_id,
+            "Downstream task",
+            "--receives",
+            "Code",
+            "--produces",
+            "Docs",
+            "--verify",
+            "Docs build",
+            "--blocked-by",
+            &maker_id,
+        ])
+        .expect("Create task failed");
+    let downstream_id = output
+        .lines()
+        .find(|line| line.contains("Created task:"))
+        .and_then(|line| line.split_whitespace().nth(2))
+        .unwrap()
+        .to_string();
+
+    env.run(&["task", "start", &maker_id, "--agent", "maker"])
+        .expect("Start failed");
+    env.run(&[
+        "task",
+        "submit",
+        &maker_id,
+        "--result",
+        "Implemented",
+        "--agent",
+        "maker",
+    ])
+    .expect("Submit failed");
+
+    // Submission alone does not unblock dependents
+    let output = env
+        .run(&["status", "--task", &maker_id])
+        .expect("Status failed");
+    assert!(output.contains("[verifying]"));
+    let output = env
+        .run(&["status", "--task", &downstream_id])
+        .expect("Status failed");
+    assert!(output.contains("[blocked]"));
+
+    // The maker cannot approve their own work, an anonymous reviewer can't
+    // either, and a verdict is required
+    let result = env.run(&["task", "verify", &maker_id, "--pass", "--agent", "maker"]);
+    assert!(result.unwrap_err().contains("different agent"));
+    let result = env.run(&["task", "verify", &maker_id, "--pass"]);
+    assert!(result.unwrap_err().contains("reviewer identity"));
+    assert!(env.run(&["task", "verify", &maker_id]).is_err());
+
+    let output = env
+        .run(&[
+            "task",
+            "verify",
+            &maker_id,
+            "--pass",
+            "--note",
+            "Tests pass locally",
+            "--agent",
+            "checker",
+        ])
+        .expect("Verify failed");
+    assert!(output.contains("Completed task:"));
+    assert!(output.contains(&downstream_id));
+
+    let output = env
+        .run(&["status", "--task", &maker_id, "--json"])
+        .expect("Status failed");
+    let parsed: Value = serde_json::from_str(&output).expect("Should be valid JSON");
+    assert_eq!(parsed["state"], "completed");
+    assert_eq!(parsed["review"]["passed"], true);
+    assert_eq!(parsed["review"]["agent"], "checker");
+
+    // A failing verdict fails the task and keeps dependents blocked
+    env.run(&["task", "start", &downstream_id, "--agent", "maker"])
+        .expect("Start failed");
+    env.run(&["task", "submit", &downstream_id, "--result", "Wrote docs"])
+        .expect("Submit failed");
+    let output = env
+        .run(&[
+            "task",
+            "verify",
+            &downstream_id,
+            "--fail",
+            "--note",
+            "Docs do not build",
+            "--agent",
+            "checker",
+        ])
+        .expect("Verify failed");
+    assert!(output.contains("Verification failed"));
+    let output = env
+        .run(&["status", "--task", &downstream_id])
+        .expect("Status failed");
+    assert!(output.contains("[failed]"));
+    assert!(output.contains("Docs do not build"));
+}
+
+#[cfg(unix)]
+#[test]
+fn test_verify_command_run() {
+    let env = TestEnv::new();
+    env.run(&["init"]).expect("Init failed");
+
+    let output = env
+        .run(&["goal", "create", "Verify command test"])
+        .expect("Create goal failed");
+    let goal_id = output
+        .lines()
+        .find(|line| line.contains("Created goal:"))
+        .and_then(|line| line.split_whitespace().nth(2))
+        .unwrap();
+
+    let output = env
+        .run(&[
+            "task",
+            "create",
+            goal_id,
+            "Write the marker file",
+            "--receives",
+            "Nothing",
+            "--produces",
+            "done.txt",
+            "--verify",
+            "done.txt exists",
+            "--verify-cmd",
+            "echo checking; test -f done.txt",
+        ])
+        .expect("Create task failed");
+    let task_id = output
+        .lines()
+        .find(|line| line.contains("Created task:"))
+        .and_then(|line| line.split_whitespace().nth(2))
+        .unwrap();
+
+    env.run(&["task", "start", task_id]).expect("Start failed");
+
+    // A task with a verify command can't skip review
+    let result = env.run(&["task", "complete", task_id, "--result", "Done"]);
+    assert!(result.unwrap_err().contains("must be reviewed"));
+
+    // Running before submitting is refused
+    let result = env.run(&["task", "verify", task_id, "--run"]);
+    assert!(result.unwrap_err().contains("Submit it first"));
+
+    // A failing command fails the task and records its output
+    env.run(&["task", "submit", task_id, "--result", "Claimed done"])
+        .expect("Submit failed");
+    let output = env
+        .run(&["task", "verify", task_id, "--run"])
+        .expect("Verify failed");
+    assert!(output.contains("Verification failed"));
+
+    let output = env
+        .run(&["status", "--task", task_id, "--json"])
+        .expect("Status failed");
+    let parsed: Value = serde_json::from_str(&output).expect("Should be valid JSON");
+    assert_eq!(parsed["state"], "failed");
+    assert_eq!(parsed["verify_run"]["exit_code"], 1);
+    assert_eq!(parsed["verify_run"]["stdout"], "checking\n");
+
+    // Once the work really exists, the command passes and completes the task
+    env.run(&["task", "retry", task_id]).expect("Retry failed");
+    std::fs::write(env.work_dir.join("done.txt"), "").unwrap();
+    env.run(&["task", "submit", task_id, "--result", "Actually done"])
+        .expect("Submit failed");
+    let output = env
+        .run(&["task", "verify", task_id, "--run"])
+        .expect("Verify failed");
+    assert!(output.contains("Completed task:"));
+
+    let output = env
+        .run(&["status", "--task", task_id, "--json"])
+        .expect("Status failed");
+    let parsed: Value = serde_json::from_str(&output).expect("Should be valid JSON");
+    assert_eq!(parsed["state"], "completed");
+    assert_eq!(parsed["verify_run"]["exit_code"], 0);
+    assert_eq!(parsed["review"]["passed"], true);
+}
+
+#[test]
+fn test_sub_goals() {
+    let env = TestEnv::new();
+    env.run(&["init"]).expect("Init failed");
+
+    let output = env
+        .run(&["goal", "create", "Ship release"])
+        .expect("Create goal failed");
+    let parent_id = output
+        .lines()
+        .find(|line| line.contains("Created goal:"))
+        .and_then(|line| line.split_whitespace().nth(2))
+        .unwrap()
+        .to_string();
+
+    let output = env
+        .run(&["goal", "create", "Write docs", "--parent", &parent_id])
+        .expect("Create sub-goal failed");
+    assert!(output.contains(&format!("Parent: {parent_id}")));
+    let child_id = output
+        .lines()
+        .find(|line| line.contains("Created goal:"))
+        .and_then(|line| line.split_whitespace().nth(2))
+        .unwrap()
+        .to_string();
+
+    // Unknown parents are rejected
+    let result = env.run(&["goal", "create", "Orphan", "--parent", "nope"]);
+    assert!(result.unwrap_err().contains("Goal not found"));
+
+    // The sub-goal is listed beneath its parent
+    let output = env.run(&["goal", "list"]).expect("List goals failed");
+    let child_line = output.lines().find(|l| l.contains(&child_id)).unwrap();
+    assert!(child_line.starts_with("    "));
+    assert!(output.find(&parent_id).unwrap() < output.find(&child_id).unwrap());
+
+    let output = env
+        .run(&[
+            "task",
+            "create",
+            &child_id,
+            "Write the guide",
+            "--receives",
+            "Nothing",
+            "--produces",
+            "Guide",
+            "--verify",
+            "Guide exists",
+        ])
+        .expect("Create task failed");
+    let task_id = output
+        .lines()
+        .find(|line| line.contains("Created task:"))
+        .and_then(|line| line.split_whitespace().nth(2))
+        .unwrap()
+        .to_string();
+
+    env.run(&["task", "start", &task_id]).expect("Start failed");
+    env.run(&[
+        "task", "complete", &task_id, "--result", "Done", "--tokens", "42",
+    ])
+    .expect("Complete failed");
+
+    // Completing the sub-goal's last task completes the parent too
+    let output = env
+        .run(&["status", "--goal", &parent_id, "--json"])
+        .expect("Status failed");
+    let json: serde_json::Value = serde_json::from_str(&output).unwrap();
+    assert_eq!(json["state"], "completed");
+    assert_eq!(json["sub_goals"][0]["id"], child_id.as_str());
+    assert_eq!(json["metrics"]["task_count"], 1);
+    assert_eq!(json["metrics"]["total_tokens"], 42);
+
+    // New work under a completed goal reopens it
+    env.run(&["goal", "create", "Announce it", "--parent", &parent_id])
+        .expect("Create sub-goal failed");
+    let output = env
+        .run(&["status", "--goal", &parent_id, "--json"])
+        .expect("Status failed");
+    let json: serde_json::Value = serde_json::from_str(&output).unwrap();
+    assert_eq!(json["state"], "inprogress");
+}
+
+#[test]
+fn test_token_accounting_across_retries() {
+    let env = TestEnv::new();
+    env.run(&["init"]).expect("Init failed");
+
+    let output = env
+        .run(&["goal", "create", "Spend test"])
+        .expect("Create goal failed");
+    let goal_id = output
+        .lines()
+        .find(|line| line.contains("Created goal:"))
+        .and_then(|line| line.split_whitespace().nth(2))
+        .unwrap()
+        .to_string();
+
+    let output = env
+        .run(&[
+            "task",
+            "create",
+            &goal_id,
+            "Costly task",
+            "--receives",
+            "Spec",
+            "--produces",
+            "Code",
+            "--verify",
+            "Tests pass",
+        ])
+        .expect("Create task failed");
+    let task_id = output
+        .lines()
+        .find(|line| line.contains("Created task:"))
+        .and_then(|line| line.split_whitespace().nth(2))
+        .unwrap()
+        .to_string();
+
+    // First attempt is submitted and rejected, the second fails outright, and
+    // the third completes
+    env.run(&["task", "start", &task_id, "--agent", "maker"])
+        .expect("Start failed");
+    env.run(&[
+        "task",
+        "submit",
+        &task_id,
+        "--result",
+        "Attempt one",
+        "--prompt-tokens",
+        "1000",
+        "--completion-tokens",
+        "200",
+        "--agent",
+        "maker",
+    ])
+    .expect("Submit failed");
+    env.run(&["task", "verify", &task_id, "--fail", "--agent", "checker"])
+        .expect("Verify failed");
+    env.run(&["task", "retry", &task_id, "--agent", "maker"])
+        .expect("Retry failed");
+    env.run(&[
+        "task",
+        "fail",
+        &task_id,
+        "--prompt-tokens",
+        "200",
+        "--completion-tokens",
+        "50",
+        "--elapsed-ms",
+        "700",
+        "--agent",
+        "maker",
+    ])
+    .expect("Fail failed");
+    env.run(&["task", "retry", &task_id, "--agent", "maker"])
+        .expect("Retry failed");
+    env.run(&[
+        "task",
+        "complete",
+        &task_id,
+        "--result",
+        "Attempt three",
+        "--prompt-tokens",
+        "500",
+        "--completion-tokens",
+        "100",
+        "--model",
+        "big-model",
+        "--agent",
+        "maker",
+    ])
+    .expect("Complete failed");
+
+    let output = env
+        .run(&["status", "--task", &task_id, "--json"])
+        .expect("Status failed");
+    let json: Value = serde_json::from_str(&output).unwrap();
+    assert_eq!(json["metrics"]["tokens"], 2050);
+    assert_eq!(json["metrics"]["prompt_tokens"], 1700);
+    assert_eq!(json["metrics"]["completion_tokens"], 350);
+    assert_eq!(json["metrics"]["elapsed_ms"], 700);
+    assert_eq!(json["metrics"]["retry_count"], 2);
+    assert_eq!(json["metrics"]["model"], "big-model");
+
+    let output = env
+        .run(&["status", "--goal", &goal_id, "--json"])
+        .expect("Status failed");
+    let json: Value = serde_json::from_str(&output).unwrap();
+    assert_eq!(json["metrics"]["total_tokens"], 2050);
+    assert_eq!(json["metrics"]["prompt_tokens"], 1700);
+    assert_eq!(json["metrics"]["completion_tokens"], 350);
+}
+
+#[test]
+fn test_goal_budget_enforced() {
+    let env = TestEnv::new();
+    env.run(&["init"]).expect("Init failed");
+
+    let output = env
+        .run(&[
+            "goal",
+            "create",
+            "Budgeted goal",
+            "--max-tokens",
+            "1000",
+            "--max-retries",
+            "1",
+        ])
+        .expect("Create goal failed");
+    assert!(output.contains("Budget: 1000 tokens, 1 retry per task"));
+    let goal_id = output
+        .lines()
+        .find(|line| line.contains("Created goal:"))
+        .and_then(|line| line.split_whitespace().nth(2))
+        .unwrap()
+        .to_string();
+
+    let mut task_ids = Vec::new();
+    for name in ["First", "Second"] {
+        let output = env
+            .run(&[
+                "task",
+                "create",
+                &goal_id,
+                name,
+                "--receives",
+                "Spec",
+                "--produces",
+                "Code",
+                "--verify",
+                "Tests pass",
+            ])
+            .expect("Create task failed");
+        task_ids.push(
+            output
+                .lines()
+                .find(|line| line.contains("Created task:"))
+                .and_then(|line| line.split_whitespace().nth(2))
+                .unwrap()
+                .to_string(),
+        );
+    }
+
+    // Only one retry is allowed per task
+    env.run(&["task", "start", &task_ids[0]])
+        .expect("Start failed");
+    env.run(&["task", "fail", &task_ids[0]])
+        .expect("Fail failed");
+    env.run(&["task", "retry", &task_ids[0]])
+        .expect("Retry failed");
+    env.run(&["task", "fail", &task_ids[0]])
+        .expect("Fail failed");
+    let result = env.run(&["task", "retry", &task_ids[0]]);
+    assert!(result.unwrap_err().contains("retries"));
+
+    // Once nearly spent, status warns about the budget
+    env.run(&["task", "start", &task_ids[1]])
+        .expect("Start failed");
+    env.run(&["task", "fail", &task_ids[1]])
+        .expect("Fail failed");
+    env.run(&["task", "retry", &task_ids[1]])
+        .expect("Retry failed");
+    env.run(&[
+        "task",
+        "submit",
+        &task_ids[1],
+        "--result",
+        "Partial",
+        "--tokens",
+        "900",
+        "--agent",
+        "maker",
+    ])
+    .expect("Submit failed");
+    let output = env
+        .run(&["status", "--goal", &goal_id])
+        .expect("Status failed");
+    assert!(output.contains("Token budget at 90%"));
+
+    // Once exhausted, new work is refused until the budget is raised
+    env.run(&[
+        "task",
+        "verify",
+        &task_ids[1],
+        "--fail",
+        "--agent",
+        "checker",
+    ])
+    .expect("Verify failed");
+    env.run(&["goal", "budget", &goal_id, "--max-tokens", "900"])
+        .expect("Budget failed");
+    let result = env.run(&["task", "retry", &task_ids[1]]);
+    assert!(result.unwrap_err().contains("over budget"));
+
+    env.run(&["goal", "budget", &goal_id, "--max-tokens", "0"])
+        .expect("Budget failed");
+    let output = env
+        .run(&["status", "--goal", &goal_id, "--json"])
+        .expect("Status failed");
+    let json: Value = serde_json::from_str(&output).unwrap();
+    assert_eq!(json["budget"]["max_retries"], 1);
+    assert!(json["budget"].get("max_tokens").is_none());
+
+    // A limit of 0 at creation means no limit rather than an exhausted one
+    let output = env
+        .run(&["goal", "create", "Unlimited", "--max-tokens", "0", "--json"])
+        .expect("Create goal failed");
+    let json: Value = serde_json::from_str(&output).unwrap();
+    assert!(json["budget"].is_null());
+}
+
+#[test]
+fn test_cost_estimate_from_price_table() {
+    let env = TestEnv::new();
+    env.run(&["init"]).expect("Init failed");
+    std::fs::write(
+        env.work_dir.join(".radial").join("pricing.toml"),
+        "[pricing.\"big-model\"]\nprompt_per_million = 3.0\ncompletion_per_million = 15.0\n",
+    )
+    .expect("Failed to write price table");
+
+    let output = env
+        .run(&["goal", "create", "Cost test"])
+        .expect("Create goal failed");
+    let goal_id = output
+        .lines()
+        .find(|line| line.contains("Created goal:"))
+        .and_then(|line| line.split_whitespace().nth(2))
+        .unwrap()
+        .to_string();
+
+    let output = env
+        .run(&[
+            "task",
+            "create",
+            &goal_id,
+            "Priced task",
+            "--receives",
+            "Spec",
+            "--produces",
+            "Code",
+            "--verify",
+            "Tests pass",
+        ])
+        .expect("Create task failed");
+    let task_id = output
+        .lines()
+        .find(|line| line.contains("Created task:"))
+        .and_then(|line| line.split_whitespace().nth(2))
+        .unwrap()
+        .to_string();
+
+    env.run(&["task", "start", &task_id]).expect("Start failed");
+    env.run(&[
+        "task",
+        "complete",
+        &task_id,
+        "--result",
+        "Done",
+        "--prompt-tokens",
+        "1000000",
+        "--completion-tokens",
+        "100000",
+        "--model",
+        "big-model",
+    ])
+    .expect("Complete failed");
+
+    let output = env
+        .run(&["status", "--task", &task_id])
+        .expect("Status failed");
+    assert!(output.contains("Estimated cost: $4.5000"));
+
+    let output = env
+        .run(&["status", "--goal", &goal_id, "--json"])
+        .expect("Status failed");
+    let json: Value = serde_json::from_str(&output).unwrap();
+    let cost = json["metrics"]["estimated_cost"].as_f64().unwrap();
+    assert!((cost - 4.5).abs() < 1e-9);
+
+    // A broken price table only loses estimates; other commands keep working
+    std::fs::write(
+        env.work_dir.join(".radial").join("pricing.toml"),
+        "[pricing.\"big-model\"\n",
+    )
+    .unwrap();
+    let output = env
+        .run(&["status", "--task", &task_id])
+        .expect("Status failed");
+    assert!(output.contains("Estimated cost: $4.5000"));
+}
+
+#[test]
+fn test_config_settings() {
+    let env = TestEnv::new();
+    env.run(&["init"]).expect("Init failed");
+
+    let output = env
+        .run(&["config", "get", "id.length"])
+        .expect("Get failed");
+    assert_eq!(output.trim(), "8");
+
+    env.run(&["config", "set", "id.prefix", "rd"])
+        .expect("Set failed");
+    env.run(&["config", "set", "tasks.require_contract", "false"])
+        .expect("Set failed");
+    env.run(&["config", "set", "defaults.agent", "configured"])
+        .expect("Set failed");
+    assert!(env.run(&["config", "set", "id.length", "two"]).is_err());
+    assert!(env.run(&["config", "set", "no.such.key", "1"]).is_err());
+
+    let output = env.run(&["config", "list", "--json"]).expect("List failed");
+    let json: Value = serde_json::from_str(&output).unwrap();
+    assert_eq!(json["id.prefix"], "rd");
+    assert_eq!(json["tasks.require_contract"], "false");
+
+    let output = env
+        .run(&["goal", "create", "Configured goal"])
+        .expect("Create goal failed");
+    let goal_id = output
+        .lines()
+        .find(|line| line.contains("Created goal:"))
+        .and_then(|line| line.split_whitespace().nth(2))
+        .unwrap()
+        .to_string();
+    assert!(goal_id.starts_with("rdg-"));
+    assert_eq!(goal_id.len(), 12);
+
+    // Without a required contract, a bare task is ready and can be started,
+    // and the configured agent is recorded when none is given
+    let output = env
+        .run(&["task", "create", &goal_id, "Bare task"])
+        .expect("Create task failed");
+    let task_id = output
+        .lines()
+        .find(|line| line.contains("Created task:"))
+        .and_then(|line| line.split_whitespace().nth(2))
+        .unwrap()
+        .to_string();
+    let output = env.run(&["ready", &goal_id]).expect("Ready failed");
+    assert!(output.contains(&task_id));
+
+    env.run(&["task", "start", &task_id]).expect("Start failed");
+    let output = env
+        .run(&["status", "--task", &task_id, "--json"])
+        .expect("Status failed");
+    let json: Value = serde_json::from_str(&output).unwrap();
+    assert_eq!(json["claimed_by"], "configured");
+
+    // With review required, completing directly is refused
+    env.run(&["config", "set", "tasks.require_review", "true"])
+        .expect("Set failed");
+    let result = env.run(&["task", "complete", &task_id, "--result", "Done"]);
+    assert!(result.unwrap_err().contains("must be reviewed"));
+    env.run(&["task", "submit", &task_id, "--result", "Done"])
+        .expect("Submit failed");
+}
+
+#[test]
+fn test_typed_id_prefixes() {
+    let env = TestEnv::new();
+    env.run(&["init"]).expect("Init failed");
+
+    let output = env
+        .run(&["goal", "create", "Prefix test"])
+        .expect("Create goal failed");
+    let goal_id = output
+        .lines()
+        .find(|line| line.contains("Created goal:"))
+        .and_then(|line| line.split_whitespace().nth(2))
+        .unwrap()
+        .to_string();
+
+    let output = env
+        .run(&["task", "create", &goal_id, "Prefixed task"])
+        .expect("Create task failed");
+    let task_id = output
+        .lines()
+        .find(|line| line.contains("Created task:"))
+        .and_then(|line| line.split_whitespace().nth(2))
+        .unwrap()
+        .to_string();
+
+    // Passing the wrong kind of ID says what it is
+    let err = env.run(&["task", "start", &goal_id]).unwrap_err();
+    assert!(err.contains(&format!("{goal_id} is a goal ID, not a task ID")));
+    let err = env.run(&["task", "list", &task_id]).unwrap_err();
+    assert!(err.contains(&format!("{task_id} is a task ID, not a goal ID")));
+
+    env.run(&["task", "comment", &task_id, "A note"])
+        .expect("Comment failed");
+    let output = env
+        .run(&["status", "--task", &task_id, "--json"])
+        .expect("Status failed");
+    let json: Value = serde_json::from_str(&output).unwrap();
+    let comment_id = json["comments"][0]["id"].as_str().unwrap();
+    assert!(comment_id.starts_with("c-"));
+    let err = env.run(&["task", "start", comment_id]).unwrap_err();
+    assert!(err.contains("is a comment ID, not a task ID"));
+
+    // Goals and tasks created before prefixes existed still load
+    let goal_dir = env.work_dir.join(".radial").join(&goal_id);
+    let legacy = std::fs::read_to_string(goal_dir.join(format!("{task_id}.toml")))
+        .unwrap()
+        .replace(&task_id, "legacy01");
+    std::fs::write(goal_dir.join("legacy01.toml"), legacy).unwrap();
+    let output = env
+        .run(&["status", "--task", "legacy01"])
+        .expect("Legacy task should load");
+    assert!(output.contains("legacy01"));
+}
+
+#[test]
+fn test_id_prefix_resolution() {
+    let env = TestEnv::new();
+    env.run(&["init"]).expect("Init failed");
+
+    let output = env
+        .run(&["goal", "create", "Prefix resolution"])
+        .expect("Create goal failed");
+    let goal_id = output
+        .lines()
+        .find(|line| line.contains("Created goal:"))
+        .and_then(|line| line.split_whitespace().nth(2))
+        .unwrap()
+        .to_string();
+
+    // A goal can be named by the start of its ID, with or without "g-"
+    let short_goal = &goal_id[2..6];
+    let output = env
+        .run(&[
+            "task",
+            "create",
+            short_goal,
+            "First",
+            "--receives",
+            "r",
+            "--produces",
+            "p",
+            "--verify",
+            "v",
+        ])
+        .expect("Create task with short goal ID failed");
+    let first_id = output
+        .lines()
+        .find(|line| line.contains("Created task:"))
+        .and_then(|line| line.split_whitespace().nth(2))
+        .unwrap()
+        .to_string();
+
+    // Blockers are resolved too, and stored by full ID
+    let output = env
+        .run(&[
+            "task",
+            "create",
+            &goal_id[..6],
+            "Second",
+            "--blocked-by",
+            &first_id[..6],
+        ])
+        .expect("Create blocked task failed");
+    let second_id = output
+        .lines()
+        .find(|line| line.contains("Created task:"))
+        .and_then(|line| line.split_whitespace().nth(2))
+        .unwrap()
+        .to_string();
+    let output = env
+        .run(&["status", "--task", &second_id[2..], "--json"])
+        .expect("Status with short task ID failed");
+    let json: Value = serde_json::from_str(&output).unwrap();
+    assert_eq!(json["id"], second_id.as_str());
+    assert_eq!(json["blocked_by"][0], first_id.as_str());
+
+    env.run(&["task", "start", &first_id[..5]])
+        .expect("Start with short task ID failed");
+
+    // "t-" is a prefix of every task, but too short to name any of them
+    let err = env.run(&["task", "start", "t-"]).unwrap_err();
+    assert!(err.contains("Task not found: t-"));
+    assert!(err.contains("at least 3 characters"));
+}
+
+#[test]
+fn test_next_claims_ready_tasks() {
+    let env = TestEnv::new();
+    env.run(&["init"]).expect("Init failed");
+
+    let output = env
+        .run(&["goal", "create", "Next test"])
+        .expect("Create goal failed");
+    let goal_id = output
+        .lines()
+        .find(|line| line.contains("Created goal:"))
+        .and_then(|line| line.split_whitespace().nth(2))
+        .unwrap()
+        .to_string();
+
+    let create = |description: &str, blocked_by: Option<&str>| {
+        let mut args = vec![
+            "task",
+            "create",
+            &goal_id,
+            description,
+            "--receives",
+            "r",
+            "--produces",
+            "p",
+            "--verify",
+            "v",
+        ];
+        if let Some(blocker) = blocked_by {
+            args.extend(["--blocked-by", blocker]);
+        }
+        let output = env.run(&args).expect("Create task failed");
+        output
+            .lines()
+            .find(|line| line.contains("Created task:"))
+            .and_then(|line| line.split_whitespace().nth(2))
+            .unwrap()
+            .to_string()
+    };
+    let first_id = create("First", None);
+    let second_id = create("Second", Some(&first_id));
+
+    // ready, why and next agree on tasks that task start would refuse
+    env.run(&["config", "set", "tasks.require_verify_cmd", "true"])
+        .expect("Config set failed");
+    let output = env.run(&["ready", &goal_id]).expect("Ready failed");
+    assert!(!output.contains(&first_id));
+    let output = env.run(&["why", &first_id]).expect("Why failed");
+    assert!(output.contains("no verify command"));
+    let (code, _) = env.run_with_code(&["next", &goal_id]);
+    assert_eq!(code, Some(2));
+    env.run(&["config", "set", "tasks.require_verify_cmd", "false"])
+        .expect("Config set failed");
+
+    // The only ready task is claimed for the agent
+    let (code, output) = env.run_with_code(&["next", &goal_id, "--agent", "a1", "--json"]);
+    assert_eq!(code, Some(0));
+    let json: Value = serde_json::from_str(&output).unwrap();
+    assert_eq!(json["status"], "claimed");
+    assert_eq!(json["task"]["id"], first_id.as_str());
+    assert_eq!(json["task"]["state"], "inprogress");
+    assert_eq!(json["task"]["claimed_by"], "a1");
+
+    // The second task is still blocked, so nothing is ready
+    let (code, output) = env.run_with_code(&["next", &goal_id, "--json"]);
+    assert_eq!(code, Some(2));
+    let json: Value = serde_json::from_str(&output).unwrap();
+    assert_eq!(json["status"], "nothing_ready");
+
+    env.run(&[
+        "task",
+        "complete",
+        &first_id,
+        "--result",
+        "Wrote the parser",
+        "--artifacts",
+        "src/parser.rs",
+    ])
+    .expect("Complete failed");
+
+    // Once unblocked, the next task comes with its blocker's result
+    let (code, output) = env.run_with_code(&["next", &goal_id, "--json"]);
+    assert_eq!(code, Some(0));
+    let json: Value = serde_json::from_str(&output).unwrap();
+    assert_eq!(json["task"]["id"], second_id.as_str());
+    assert_eq!(json["context"]["upstream"][0]["task_id"], first_id.as_str());
+    assert_eq!(
+        json["context"]["upstream"][0]["summary"],
+        "Wrote the parser"
+    );
+
+    // Commands that change a task print it as JSON on request
+    let output = env
+        .run(&["task", "complete", &second_id, "--result", "Done", "--json"])
+        .expect("Complete failed");
+    let json: Value = serde_json::from_str(&output).unwrap();
+    assert_eq!(json["task"]["state"], "completed");
+
+    // With every task completed the goal is done
+    let (code, output) = env.run_with_code(&["next", &goal_id]);
+    assert_eq!(code, Some(3));
+    assert!(output.contains("is completed"));
+}
+
+#[test]
+fn test_next_claims_from_sub_goals() {
+    let env = TestEnv::new();
+    env.run(&["init"]).expect("Init failed");
+
+    let goal_id = |output: String| {
+        output
+            .lines()
+            .find(|line| line.contains("Created goal:"))
+            .and_then(|line| line.split_whitespace().nth(2))
+            .unwrap()
+            .to_string()
+    };
+    let epic_id = goal_id(env.run(&["goal", "create", "Epic"]).unwrap());
+    let child_id = goal_id(
+        env.run(&["goal", "create", "Login", "--parent", &epic_id])
+            .unwrap(),
+    );
+    env.run(&[
+        "task",
+        "create",
+        &child_id,
+        "Build form",
+        "--receives",
+        "r",
+        "--produces",
+        "p",
+        "--verify",
+        "v",
+    ])
+    .expect("Create task failed");
+
+    // The parent has no tasks of its own, but its sub-goal's task is ready
+    let output = env.run(&["ready", &epic_id]).expect("Ready failed");
+    assert!(output.contains("Build form"));
+
+    let (code, output) = env.run_with_code(&["next", &epic_id, "--agent", "a1", "--json"]);
+    assert_eq!(code, Some(0));
+    let json: Value = serde_json::from_str(&output).unwrap();
+    assert_eq!(json["task"]["goal_id"], child_id.as_str());
+
+    // With the only task claimed, nothing under the parent is ready
+    let (code, _) = env.run_with_code(&["next", &epic_id]);
+    assert_eq!(code, Some(2));
+}
+
+#[test]
+fn test_ready_orders_by_priority() {
+    let env = TestEnv::new();
+    env.run(&["init"]).expect("Init failed");
+    env.run(&["config", "set", "tasks.require_contract", "false"])
+        .expect("Config set failed");
+
+    let output = env
+        .run(&["goal", "create", "Priority test"])
+        .expect("Create goal failed");
+    let goal_id = output
+        .lines()
+        .find(|line| line.contains("Created goal:"))
+        .and_then(|line| line.split_whitespace().nth(2))
+        .unwrap()
+        .to_string();
+
+    let create = |args: &[&str]| {
+        let mut all = vec!["task", "create", &goal_id];
+        all.extend(args);
+        let output = env.run(&all).expect("Create task failed");
+        output
+            .lines()
+            .find(|line| line.contains("Created task:"))
+            .and_then(|line| line.split_whitespace().nth(2))
+            .unwrap()
+            .to_string()
+    };
+    let low = create(&["Low", "--priority", "p3"]);
+    let plain = create(&["Plain"]);
+    let urgent = create(&["Urgent", "--priority", "0"]);
+    let chain_head = create(&["Chain head"]);
+    create(&["Chain tail", "--blocked-by", &chain_head]);
+
+    let ready_ids = || -> Vec<String> {
+        let output = env
+            .run(&["ready", &goal_id, "--json"])
+            .expect("Ready failed");
+        let json: Value = serde_json::from_str(&output).unwrap();
+        json.as_array()
+            .unwrap()
+            .iter()
+            .map(|t| t["id"].as_str().unwrap().to_string())
+            .collect()
+    };
+
+    // Most urgent first, then oldest first within a priority
+    assert_eq!(
+        ready_ids(),
+        vec![
+            urgent.clone(),
+            plain.clone(),
+            chain_head.clone(),
+            low.clone()
+        ]
+    );
+
+    // The critical path boost moves the task that unblocks work ahead of its peers
+    env.run(&["config", "set", "tasks.critical_path_boost", "true"])
+        .expect("Config set failed");
+    assert_eq!(
+        ready_ids(),
+        vec![urgent, chain_head.clone(), plain.clone(), low]
+    );
+
+    // Priorities can be changed later and are shown by status
+    env.run(&["task", "update", &plain, "--priority", "p1"])
+        .expect("Update failed");
+    assert_eq!(ready_ids()[1], plain);
+    let output = env.run(&["task", "list", &goal_id]).expect("List failed");
+    assert!(output.contains("Priority: p1"));
+
+    let err = env
+        .run(&["task", "update", &plain, "--priority", "p7"])
+        .unwrap_err();
+    assert!(err.contains("expected p0 to p3"));
+}
+
+#[test]
+fn test_ready_across_goals() {
+    let env = TestEnv::new();
+    env.run(&["init"]).expect("Init failed");
+    env.run(&["config", "set", "tasks.require_contract", "false"])
+        .expect("Config set failed");
+
+    let mut goal_ids = Vec::new();
+    for description in ["Backend", "Frontend"] {
+        let output = env
+            .run(&["goal", "create", description])
+            .expect("Create goal failed");
+        goal_ids.push(
+            output
+                .lines()
+                .find(|line| line.contains("Created goal:"))
+                .and_then(|line| line.split_whitespace().nth(2))
+                .unwrap()
+                .to_string(),
+        );
+    }
+
+    env.run(&["task", "create", &goal_ids[0], "API", "--priority", "p3"])
+        .expect("Create task failed");
+    env.run(&["task", "create", &goal_ids[1], "UI", "--priority", "p1"])
+        .expect("Create task failed");
+
+    // Without a goal, ready tasks from every goal are listed by priority and
+    // carry their goal's description
+    let output = env.run(&["ready", "--json"]).expect("Ready failed");
+    let json: Value = serde_json::from_str(&output).unwrap();
+    let tasks = json.as_array().unwrap();
+    assert_eq!(tasks.len(), 2);
+    assert_eq!(tasks[0]["goal_id"], goal_ids[1].as_str());
+    assert_eq!(tasks[0]["goal_description"], "Frontend");
+    assert_eq!(tasks[1]["description"], "API");
+
+    let output = env
+        .run(&["ready", "--limit", "1", "--json"])
+        .expect("Ready failed");
+    let json: Value = serde_json::from_str(&output).unwrap();
+    assert_eq!(json.as_array().unwrap().len(), 1);
+
+    let output = env.run(&["ready"]).expect("Ready failed");
+    assert!(output.contains("2 task(s) ready"));
+    assert!(output.contains("Backend"));
+}
+
+#[test]
+fn test_why_explains_blocking_chain() {
+    let env = TestEnv::new();
+    env.run(&["init"]).expect("Init failed");
+
+    let output = env
+        .run(&["goal", "create", "Why test"])
+        .expect("Create goal failed");
+    let goal_id = output
+        .lines()
+        .find(|line| line.contains("Created goal:"))
+        .and_then(|line| line.split_whitespace().nth(2))
+        .unwrap()
+        .to_string();
+
+    let create = |args: &[&str]| {
+        let mut all = vec!["task", "create", &goal_id];
+        all.extend(args);
+        let output = env.run(&all).expect("Create task failed");
+        output
+            .lines()
+            .find(|line| line.contains("Created task:"))
+            .and_then(|line| line.split_whitespace().nth(2))
+            .unwrap()
+            .to_string()
+    };
+    let contract = ["--receives", "r", "--produces", "p", "--verify", "v"];
+    let mut args = vec!["Schema"];
+    args.extend(contract);
+    let schema = create(&args);
+    let mut args = vec!["API", "--blocked-by", &schema];
+    args.extend(contract);
+    let api = create(&args);
+    let client = create(&["Client", "--blocked-by", &api]);
+
+    // A pending task with a contract and no blockers is ready
+    let output = env.run(&["why", &schema, "--json"]).expect("Why failed");
+    let json: Value = serde_json::from_str(&output).unwrap();
+    assert_eq!(json["ready"], true);
+
+    env.run(&["task", "start", &schema, "--agent", "builder"])
+        .expect("Start failed");
+
+    // The chain lists every unfinished task upstream, with who holds it
+    let output = env.run(&["why", &client, "--json"]).expect("Why failed");
+    let json: Value = serde_json::from_str(&output).unwrap();
+    assert_eq!(json["ready"], false);
+    let blockers = json["blockers"].as_array().unwrap();
+    assert_eq!(blockers.len(), 2);
+    assert_eq!(blockers[0]["task_id"], api.as_str());
+    assert_eq!(blockers[0]["depth"], 1);
+    assert_eq!(blockers[1]["task_id"], schema.as_str());
+    assert_eq!(blockers[1]["depth"], 2);
+    assert_eq!(blockers[1]["claimed_by"], "builder");
+    let reasons: Vec<&str> = json["reasons"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|r| r.as_str().unwrap())
+        .collect();
+    assert!(reasons.contains(&"Blocked by 1 unfinished task(s)"));
+    // A blocked task is held to the same start checks as a pending one
+    assert!(reasons.iter().any(|r| r.contains("no contract")));
+
+    // A failed blocker anywhere in the chain is called out
+    env.run(&["task", "fail", &schema]).expect("Fail failed");
+    let output = env.run(&["why", &client]).expect("Why failed");
+    assert!(output.contains("Blocking chain:"));
+    assert!(output.contains(&format!("Blocker {schema} has failed")));
+
+    // The task's own state is explained too
+    let output = env.run(&["why", &schema]).expect("Why failed");
+    assert!(output.contains("Task has failed"));
+    assert!(output.contains("rd task retry"));
+
+    // Once the goal's retry limit is used up, why says so instead of suggesting a retry
+    env.run(&["goal", "budget", &goal_id, "--max-retries", "1"])
+        .expect("Budget failed");
+    env.run(&["task", "retry", &schema]).expect("Retry failed");
+    env.run(&["task", "fail", &schema]).expect("Fail failed");
+    let output = env.run(&["why", &schema]).expect("Why failed");
+    assert!(output.contains("used all 1 retries"));
+    assert!(!output.contains("rd task retry"));
+
+    // A task left blocked after its blockers completed isn't reported as ready
+    let mut args = vec!["Base"];
+    args.extend(contract);
+    let base = create(&args);
+    env.run(&["task", "start", &base]).expect("Start failed");
+    env.run(&["task", "complete", &base, "--result", "Done"])
+        .expect("Complete failed");
+    let mut args = vec!["Docs", "--blocked-by", &base];
+    args.extend(contract);
+    let docs = create(&args);
+    let path = env
+        .work_dir
+        .join(".radial")
+        .join(&goal_id)
+        .join(format!("{docs}.toml"));
+    let toml = fs::read_to_string(&path).unwrap();
+    fs::write(
+        &path,
+        toml.replace("state = \"pending\"", "state = \"blocked\""),
+    )
+    .unwrap();
+    let output = env.run(&["why", &docs, "--json"]).expect("Why failed");
+    let json: Value = serde_json::from_str(&output).unwrap();
+    assert_eq!(json["state"], "blocked");
+    assert_eq!(json["ready"], false);
+    assert_eq!(
+        json["reasons"][0],
+        "Task is marked blocked but all blockers are completed"
+    );
+}
+
+#[test]
+fn test_start_hands_off_upstream_context() {
+    let env = TestEnv::new();
+    env.run(&["init"]).expect("Init failed");
+
+    let output = env
+        .run(&["goal", "create", "Handoff test"])
+        .expect("Create goal failed");
+    let goal_id = output
+        .lines()
+        .find(|line| line.contains("Created goal:"))
+        .and_then(|line| line.split_whitespace().nth(2))
+        .unwrap()
+        .to_string();
+
+    let create = |args: &[&str]| {
+        let mut all = vec!["task", "create", &goal_id];
+        all.extend(args);
+        let output = env.run(&all).expect("Create task failed");
+        output
+            .lines()
+            .find(|line| line.contains("Created task:"))
+            .and_then(|line| line.split_whitespace().nth(2))
+            .unwrap()
+            .to_string()
+    };
+    let schema = create(&[
+        "Schema",
+        "--receives",
+        "Requirements",
+        "--produces",
+        "Database schema and migrations",
+        "--verify",
+        "Migrations apply",
+    ]);
+    let api = create(&[
+        "API",
+        "--receives",
+        "Database schema",
+        "--produces",
+        "REST endpoints",
+        "--verify",
+        "Tests pass",
+        "--blocked-by",
+        &schema,
+    ]);
+    let ui = create(&[
+        "UI",
+        "--receives",
+        "Login page mockups",
+        "--produces",
+        "Screens",
+        "--verify",
+        "Looks right",
+        "--blocked-by",
+        &schema,
+    ]);
+
+    env.run(&["task", "start", &schema]).expect("Start failed");
+    for note in ["one", "two", "three", "four"] {
+        env.run(&["task", "comment", &schema, note])
+            .expect("Comment failed");
+    }
+    env.run(&[
+        "task",
+        "complete",
+        &schema,
+        "--result",
+        "Added users table",
+        "--artifacts",
+        "migrations/001.sql",
+        "--agent",
+        "builder",
+    ])
+    .expect("Complete failed");
+
+    // The blocker's outcome, artifacts and last comments come with the start
+    let output = env
+        .run(&["task", "start", &api, "--json"])
+        .expect("Start failed");
+    let json: Value = serde_json::from_str(&output).unwrap();
+    assert_eq!(json["id"], api.as_str());
+    let upstream = &json["context"]["upstream"][0];
+    assert_eq!(upstream["task_id"], schema.as_str());
+    assert_eq!(upstream["summary"], "Added users table");
+    assert_eq!(upstream["artifacts"][0], "migrations/001.sql");
+    assert_eq!(upstream["agent"], "builder");
+    let comments = upstream["comments"].as_array().unwrap();
+    assert_eq!(comments.len(), 3);
+    assert_eq!(comments[2]["text"], "four");
+    assert!(json["context"].get("warnings").is_none());
+
+    // A task that receives something its blockers never mention is warned
+    let output = env.run(&["task", "start", &ui]).expect("Start failed");
+    assert!(output.contains("Context from completed blockers:"));
+    assert!(output.contains("Added users table"));
+    assert!(output.contains("Blockers may not produce what this task receives"));
+}
+
+#[test]
+fn test_prompt_renders_task_template() {
+    let env = TestEnv::new();
+    env.run(&["init"]).expect("Init failed");
+    let template_path = env
+        .work_dir
+        .join(".radial")
+        .join("templates")
+        .join("prompt.md");
+    assert!(template_path.is_file(), "init should write the template");
+
+    let output = env
+        .run(&["goal", "create", "Ship the login flow"])
+        .expect("Create goal failed");
+    let goal_id = output
+        .lines()
+        .find(|line| line.contains("Created goal:"))
+        .and_then(|line| line.split_whitespace().nth(2))
+        .unwrap()
+        .to_string();
+
+    let create = |args: &[&str]| {
+        let mut all = vec!["task", "create", &goal_id];
+        all.extend(args);
+        let output = env.run(&all).expect("Create task failed");
+        output
+            .lines()
+            .find(|line| line.contains("Created task:"))
+            .and_then(|line| line.split_whitespace().nth(2))
+            .unwrap()
+            .to_string()
+    };
+    let schema = create(&[
+        "Schema",
+        "--receives",
+        "Requirements",
+        "--produces",
+        "Users table",
+        "--verify",
+        "Migrations apply",
+    ]);
+    let form = create(&[
+        "Login form",
+        "--receives",
+        "Users table",
+        "--produces",
+        "Form posting to /login",
+        "--verify",
+        "Form submits",
+        "--verify-cmd",
+        "npm test",
+        "--blocked-by",
+        &schema,
+    ]);
+    env.run(&["task", "start", &schema]).expect("Start failed");
+    env.run(&["task", "complete", &schema, "--result", "Added users table"])
+        .expect("Complete failed");
+    env.run(&["task", "comment", &form, "Use the shared button styles"])
+        .expect("Comment failed");
+
+    // The built-in template covers the goal, contract, upstream results,
+    // comments and the commands to finish the task
+    let output = env.run(&["prompt", &form[..6]]).expect("Prompt failed");
+    assert!(output.contains("Ship the login flow"));
+    assert!(output.contains(&format!("{form} (priority p2): Login form")));
+    assert!(output.contains("- Produces: Form posting to /login"));
+    assert!(output.contains("- Verify command: `npm test`"));
+    assert!(output.contains(&format!("- {schema} (Schema): Added users table")));
+    assert!(output.contains("Use the shared button styles"));
+    // The form has a verify command, so it must go through review
+    assert!(output.contains(&format!("rd task submit {form} --result")));
+    assert!(!output.contains("rd task complete"));
+    assert!(output.contains("RD_AGENT"));
+
+    // Editing the project template changes every prompt
+    std::fs::write(
+        &template_path,
+        "Do {{task_id}}, check with {{ verify_cmd }}\n",
+    )
+    .unwrap();
+    let output = env.run(&["prompt", &form]).expect("Prompt failed");
+    assert_eq!(output, format!("Do {form}, check with npm test\n"));
+
+    // --template takes a path or a name in .radial/templates/
+    std::fs::write(env.work_dir.join("short.md"), "{{task_description}}").unwrap();
+    let output = env
+        .run(&["prompt", &form, "--template", "short.md"])
+        .expect("Prompt failed");
+    assert_eq!(output, "Login form");
+
+    std::fs::write(&template_path, "{{task_name}}").unwrap();
+    let err = env.run(&["prompt", &form]).unwrap_err();
+    assert!(err.contains("Unknown template variable: task_name"));
+}
+
+#[test]
+fn test_mcp_serves_tools_over_stdio() {
+    let env = TestEnv::new();
+    env.run(&["init"]).expect("Init failed");
+
+    let request = |id: i64, method: &str, params: Value| {
+        serde_json::json!({ "jsonrpc": "2.0", "id": id, "method": method, "params": params })
+            .to_string()
+    };
+    let call = |id: i64, name: &str, arguments: Value| {
+        request(
+            id,
+            "tools/call",
+            serde_json::json!({ "name": name, "arguments": arguments }),
+        )
+    };
+    let tool_result = |response: &Value| -> Value {
+        assert_eq!(response["result"]["isError"], false, "{response}");
+        let text = response["result"]["content"][0]["text"].as_str().unwrap();
+        serde_json::from_str(text).unwrap()
+    };
+    let exchange = |lines: &[String]| -> Vec<Value> {
+        env.run_with_stdin(&["mcp", "--agent", "bot"], &(lines.join("\n") + "\n"))
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect()
+    };
+
+    let responses = exchange(&[
+        request(1, "initialize", serde_json::json!({})),
+        serde_json::json!({ "jsonrpc": "2.0", "method": "notifications/initialized" }).to_string(),
+        request(2, "tools/list", serde_json::json!({})),
+        call(
+            3,
+            "goal_create",
+            serde_json::json!({ "description": "Ship it" }),
+        ),
+    ]);
+    assert_eq!(responses.len(), 3, "notifications get no reply");
+    assert_eq!(responses[0]["result"]["serverInfo"]["name"], "radial");
+    let tools: Vec<&str> = responses[1]["result"]["tools"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|t| t["name"].as_str().unwrap())
+        .collect();
+    assert!(tools.contains(&"next") && tools.contains(&"task_complete"));
+    let goal_id = tool_result(&responses[2])["id"]
+        .as_str()
+        .unwrap()
+        .to_string();
+
+    // Each session reopens the database, so later sessions see earlier writes.
+    let responses = exchange(&[
+        call(
+            1,
+            "task_create",
+            serde_json::json!({ "goal_id": goal_id, "description": "Build",
+                "receives": "Spec", "produces": "Binary", "verify": "It runs" }),
+        ),
+        call(2, "next", serde_json::json!({ "goal_id": goal_id })),
+    ]);
+    let task_id = tool_result(&responses[0])["id"]
+        .as_str()
+        .unwrap()
+        .to_string();
+    let next = tool_result(&responses[1]);
+    assert_eq!(next["status"], "claimed");
+    assert_eq!(next["task"]["claimed_by"], "bot");
+
+    let responses = exchange(&[
+        call(
+            1,
+            "task_complete",
+            serde_json::json!({ "task_id": task_id, "result": "Built", "tokens": 10 }),
+        ),
+        call(2, "task_fail", serde_json::json!({ "task_id": task_id })),
+        call(3, "status", serde_json::json!({ "goal_id": goal_id })),
+    ]);
+    assert_eq!(tool_result(&responses[0])["task"]["state"], "completed");
+    assert_eq!(responses[1]["result"]["isError"], true);
+    assert_eq!(tool_result(&responses[2])["state"], "completed");
+}
+
+#[test]
+fn test_serve_exposes_rest_api() {
+    let env = TestEnv::new();
+    env.run(&["init"]).expect("Init failed");
+
+    let mut server = Command::new(&env.binary_path)
+        .args(["serve", "--bind", "127.0.0.1:0", "--agent", "bot"])
+        .current_dir(&env.work_dir)
+        .stdout(Stdio::piped())
+        .spawn()
+        .expect("Failed to start server");
+    let mut banner = String::new();
+    BufReader::new(server.stdout.take().unwrap())
+        .read_line(&mut banner)
+        .expect("Failed to read banner");
+    let addr = banner
+        .split("http://")
+        .nth(1)
+        .expect("Missing address")
+        .trim()
+        .to_string();
+
+    let request = |method: &str, path: &str, body: Option<Value>| -> (u16, Value) {
+        let body = body.map(|b| b.to_string()).unwrap_or_default();
+        let mut stream = TcpStream::connect(&addr).expect("Failed to connect");
+        write!(
+            stream,
+            "{method} {path} HTTP/1.1\r\nHost: {addr}\r\nContent-Type: application/json\r\nContent-Length: {}\r\n\r\n{body}",
+            body.len()
+        )
+        .unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).unwrap();
+        let (head, body) = response.split_once("\r\n\r\n").unwrap();
+        let status = head.split_whitespace().nth(1).unwrap().parse().unwrap();
+        (status, serde_json::from_str(body).unwrap())
+    };
+
+    let (status, goal) = request(
+        "POST",
+        "/goals",
+        Some(serde_json::json!({ "description": "Ship it" })),
+    );
+    assert_eq!(status, 200);
+    let goal_id = goal["id"].as_str().unwrap().to_string();
+
+    let (_, task) = request(
+        "POST",
+        &format!("/goals/{goal_id}/tasks"),
+        Some(
+            serde_json::json!({ "description": "Build", "receives": "Spec",
+                                 "produces": "Binary", "verify": "It runs" }),
+        ),
+    );
+    let task_id = task["id"].as_str().unwrap().to_string();
+
+    let (_, ready) = request("GET", "/ready?limit=5", None);
+    assert_eq!(ready[0]["id"], task_id.as_str());
+
+    let (_, started) = request("POST", &format!("/tasks/{task_id}/start"), None);
+    assert_eq!(started["claimed_by"], "bot");
+
+    let (_, completed) = request(
+        "POST",
+        &format!("/tasks/{task_id}/complete"),
+        Some(serde_json::json!({ "result": "Built" })),
+    );
+    assert_eq!(completed["task"]["state"], "completed");
+
+    // Writes through the server are visible to the CLI and vice versa.
+    let status_output = env.run(&["status", "--goal", &goal_id]).unwrap();
+    assert!(status_output.contains("Build"));
+    let (_, goal_status) = request("GET", &format!("/goals/{goal_id}"), None);
+    assert_eq!(goal_status["state"], "completed");
+
+    let (status, error) = request("POST", &format!("/tasks/{task_id}/fail"), None);
+    assert_eq!(status, 400);
+    assert!(error["error"].is_string());
+    let (status, _) = request("GET", "/nowhere", None);
+    assert_eq!(status, 404);
+    let (status, _) = request("GET", "/tasks/t-nope", None);
+    assert_eq!(status, 404);
+
+    server.kill().unwrap();
+    server.wait().unwrap();
+
+    // Listening beyond loopback needs --allow-remote.
+    let refused = env.run(&["serve", "--bind", "0.0.0.0:0"]).unwrap_err();
+    assert!(refused.contains("--allow-remote"));
+}
+
+#[test]
+fn test_watch_streams_task_changes() {
+    let env = TestEnv::new();
+    env.run(&["init"]).expect("Init failed");
+    let output = env
+        .run(&["goal", "create", "Watched goal"])
+        .expect("Create goal failed");
+    let goal_id = output
+        .lines()
+        .find(|line| line.contains("Created goal:"))
+        .and_then(|line| line.split_whitespace().nth(2))
+        .unwrap()
+        .to_string();
+
+    let mut watcher = Command::new(&env.binary_path)
+        .args(["watch", "--goal", &goal_id, "--json"])
+        .current_dir(&env.work_dir)
+        .stdout(Stdio::piped())
+        .spawn()
+        .expect("Failed to start watch");
+    let stdout = watcher.stdout.take().unwrap();
+    let (sender, events) = std::sync::mpsc::channel();
+    std::thread::spawn(move || {
+        for line in BufReader::new(stdout).lines() {
+            let Ok(line) = line else { break };
+            if sender.send(line).is_err() {
+                break;
+            }
+        }
+    });
+
+    // Give the watcher time to load its first snapshot, and each change time
+    // to be polled on its own.
+    let pause = || std::thread::sleep(std::time::Duration::from_millis(1200));
+    pause();
+
+    let create = |args: &[&str]| {
+        let mut all = vec!["task", "create", &goal_id];
+        all.extend(args);
+        let output = env.run(&all).expect("Create task failed");
+        output
+            .lines()
+            .find(|line| line.contains("Created task:"))
+            .and_then(|line| line.split_whitespace().nth(2))
+            .unwrap()
+            .to_string()
+    };
+    let contract = [
+        "--receives",
+        "Spec",
+        "--produces",
+        "Code",
+        "--verify",
+        "Tests",
+    ];
+    let first = create(&[&["Build"][..], &contract].concat());
+    let second = create(&[&["Ship", "--blocked-by", &first][..], &contract].concat());
+    pause();
+    env.run(&["task", "start", &first, "--agent", "bot"])
+        .expect("Start failed");
+    pause();
+    env.run(&["task", "comment", &first, "Halfway", "--agent", "bot"])
+        .expect("Comment failed");
+    pause();
+    env.run(&["task", "complete", &first, "--result", "Built"])
+        .expect("Complete failed");
+
+    let expected = [
+        ("created", &first),
+        ("created", &second),
+        ("started", &first),
+        ("commented", &first),
+        ("completed", &first),
+        ("unblocked", &second),
+    ];
+    let mut seen = Vec::new();
+    for _ in &expected {
+        let line = events
+            .recv_timeout(std::time::Duration::from_secs(10))
+            .unwrap_or_else(|_| panic!("Timed out waiting for events; saw {seen:?}"));
+        let event: Value = serde_json::from_str(&line).unwrap();
+        seen.push((
+            event["event"].as_str().unwrap().to_string(),
+            event["task_id"].as_str().unwrap().to_string(),
+        ));
+    }
+
+    watcher.kill().unwrap();
+    watcher.wait().unwrap();
+
+    let expected: Vec<(String, String)> = expected
+        .iter()
+        .map(|(kind, id)| ((*kind).to_string(), (*id).clone()))
+        .collect();
+    assert_eq!(seen, expected);
+}